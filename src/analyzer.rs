@@ -62,7 +62,7 @@ pub fn scan_applications(
         .collect();
 
     // Sort by total size descending
-    apps.sort_by(|a, b| b.total_size.cmp(&a.total_size));
+    apps.sort_by_key(|app| std::cmp::Reverse(app.total_size));
     apps
 }
 
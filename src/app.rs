@@ -1,14 +1,32 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 
 use eframe::egui;
 
 use crate::analyzer::AppInfo;
-use crate::cleaner::ScanResult;
+use crate::archive;
+use crate::categories::{self, FileKind, KeepPolicy, KeepRule, TypeReport};
+use crate::cleaner::{Cleaner, ImageInfo, ScanResult};
+use crate::dedupe;
 use crate::disk_info::{self, DiskInfo};
+use crate::disk_usage::{self, SortMode, UsageCache};
+use crate::file_actions::{self, FileAction};
+use crate::hash_cache;
+use crate::in_use::{self, InUseSnapshot};
 use crate::monitor::Monitor;
+use crate::offload;
+use crate::progress::{self, WorkKind};
+use crate::shredder;
+use crate::throttle::{self, IoLimits};
 use crate::utils;
 
+/// Large-file threshold for every scan started from the window.
+const SCAN_MIN_SIZE: u64 = 104_857_600;
+
+/// Root of scans started from the window; `None` is the home folder.
+const SCAN_PATH: Option<&str> = None;
+
 // ── Color palette ──────────────────────────────────────────────────────
 
 const BG_PANEL: egui::Color32 = egui::Color32::from_rgb(28, 28, 38);
@@ -77,13 +95,18 @@ fn icon_for_category(name: &str) -> (&'static str, egui::Color32) {
         "xcode-device-support" => ("D", egui::Color32::from_rgb(140, 100, 220)),
         "xcode-archives" => ("A", egui::Color32::from_rgb(220, 100, 140)),
         "core-simulator" => ("S", egui::Color32::from_rgb(60, 200, 200)),
+        "runtime-versions" => ("Rt", egui::Color32::from_rgb(110, 160, 200)),
+        "project-artifacts" => ("Bd", egui::Color32::from_rgb(200, 120, 90)),
         "homebrew" => ("H", egui::Color32::from_rgb(220, 180, 50)),
         "package-managers" => ("P", egui::Color32::from_rgb(180, 120, 60)),
         "trash" => ("T", egui::Color32::from_rgb(190, 60, 60)),
         "duplicates" => ("2x", egui::Color32::from_rgb(230, 150, 50)),
+        "similar-images" => ("Im", egui::Color32::from_rgb(90, 170, 210)),
         "ds-store" => (".", egui::Color32::from_rgb(140, 140, 160)),
         "language-files" => ("i", egui::Color32::from_rgb(50, 180, 180)),
         "privacy" => ("R", egui::Color32::from_rgb(220, 70, 70)),
+        "incomplete-downloads" => ("Dl", egui::Color32::from_rgb(90, 150, 200)),
+        "installers" => ("In", egui::Color32::from_rgb(120, 170, 90)),
         "old-files" => ("O", egui::Color32::from_rgb(200, 160, 50)),
         "broken-symlinks" => ("~", egui::Color32::from_rgb(180, 80, 80)),
         "empty-folders" => ("E", egui::Color32::from_rgb(110, 110, 130)),
@@ -93,6 +116,31 @@ fn icon_for_category(name: &str) -> (&'static str, egui::Color32) {
     }
 }
 
+fn kind_color(kind: FileKind) -> egui::Color32 {
+    match kind {
+        FileKind::Video => egui::Color32::from_rgb(200, 90, 110),
+        FileKind::Images => egui::Color32::from_rgb(90, 170, 210),
+        FileKind::Audio => egui::Color32::from_rgb(180, 120, 210),
+        FileKind::Archives => egui::Color32::from_rgb(210, 160, 70),
+        FileKind::DiskImages => egui::Color32::from_rgb(150, 150, 170),
+        FileKind::Documents => egui::Color32::from_rgb(90, 190, 130),
+        FileKind::SourceCode => egui::Color32::from_rgb(100, 130, 220),
+        FileKind::Apps => egui::Color32::from_rgb(70, 160, 160),
+        FileKind::Other => egui::Color32::from_rgb(100, 100, 115),
+    }
+}
+
+/// Offloaded files, checking once which volumes are connected.
+fn load_offloaded() -> Vec<(offload::Offloaded, bool)> {
+    offload::list()
+        .into_iter()
+        .map(|record| {
+            let connected = record.available();
+            (record, connected)
+        })
+        .collect()
+}
+
 fn paint_icon(ui: &mut egui::Ui, letter: &str, color: egui::Color32) {
     let size = 28.0;
     let (rect, _) = ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::hover());
@@ -119,6 +167,24 @@ pub struct CategoryState {
     pub scan_result: Option<ScanResult>,
     pub entry_selected: Vec<bool>,
     pub is_report_only: bool,
+    pub skips_in_use: bool,
+    /// App the user asked to quit from this category's in-use notice.
+    pub pending_quit: Option<String>,
+    /// The user asked to link this category's selected duplicates to their originals.
+    pub pending_dedupe: bool,
+    /// Entries can be selected one by one for a [`FileAction`], though the
+    /// category itself is never cleaned.
+    pub per_file_actions: bool,
+    /// Action the user chose for this category's selected entries.
+    pub pending_action: Option<FileAction>,
+    /// Selected entries can be packed into an archive instead of deleted.
+    pub archivable: bool,
+    /// Folder the user chose to archive the selected entries into.
+    pub pending_archive: Option<PathBuf>,
+    /// Selected entries can be offloaded to another volume.
+    pub offloadable: bool,
+    /// Previews of similar-image entries, uploaded on first display.
+    pub thumbnails: HashMap<PathBuf, egui::TextureHandle>,
 }
 
 impl CategoryState {
@@ -144,11 +210,38 @@ impl CategoryState {
     }
 
     fn set_all_entries(&mut self, val: bool) {
-        for s in &mut self.entry_selected {
-            *s = val;
+        let entries = self.scan_result.as_ref().map(|r| &r.entries);
+        for (idx, s) in self.entry_selected.iter_mut().enumerate() {
+            // Entries held by a running app can never be selected
+            let in_use = entries
+                .and_then(|e| e.get(idx))
+                .is_some_and(|e| e.in_use_by.is_some());
+            *s = val && !in_use;
+        }
+        if let Some(result) = &self.scan_result {
+            result.enforce_survivors(&mut self.entry_selected);
         }
     }
 
+    /// Distinct apps and processes holding entries of this category, with
+    /// entry counts and whether the holder is an app that can be quit.
+    fn in_use_owners(&self) -> Vec<(String, usize, bool)> {
+        let mut owners: Vec<(String, usize, bool)> = Vec::new();
+        let Some(result) = &self.scan_result else {
+            return owners;
+        };
+        for entry in &result.entries {
+            let Some(owner) = &entry.in_use_by else {
+                continue;
+            };
+            match owners.iter_mut().find(|(name, _, _)| name == owner) {
+                Some((_, count, _)) => *count += 1,
+                None => owners.push((owner.clone(), 1, entry.held_by_app)),
+            }
+        }
+        owners
+    }
+
     fn sync_category_from_entries(&mut self) {
         if !self.is_report_only {
             self.selected = self.entry_selected.iter().any(|s| *s);
@@ -159,28 +252,45 @@ impl CategoryState {
 struct DeleteItem {
     category_name: String,
     path: PathBuf,
-    #[allow(dead_code)]
     size_bytes: u64,
+    /// Re-check right before deleting whether the owner app started meanwhile.
+    check_in_use: bool,
+}
+
+/// Work items of a full scan, run side by side on the scan workers.
+enum ScanJob {
+    Category(Box<dyn Cleaner>),
+    StorageByType,
 }
 
 pub enum BgMessage {
     ScanComplete(String, ScanResult),
+    TypeReport(TypeReport),
     AllScansComplete { smart_clean: bool },
     DeletedFile(String, PathBuf, u64),
+    /// An entry was dealt with other than by deleting it: (category, path,
+    /// bytes freed, what was done).
+    ProcessedFile(String, PathBuf, u64, String),
+    /// (path, attempted action, error)
+    ActionError(PathBuf, &'static str, String),
     DeleteError(String, PathBuf, String),
     AllCleansComplete,
     AllShredsComplete,
     Progress(String),
     AnalyzerProgress(usize, usize, String),
     AnalyzerComplete(Vec<AppInfo>),
+    /// Folder measured for the disk usage explorer, with every listing below it.
+    UsageComplete(PathBuf, HashMap<PathBuf, disk_usage::Listing>),
     RamOptimizeComplete(u64, u64),
     RamOptimizeError(String),
+    AppQuitFailed(String),
 }
 
 #[derive(PartialEq)]
 pub enum ViewMode {
     Main,
     Analyzer,
+    DiskUsage,
 }
 
 #[derive(PartialEq)]
@@ -203,12 +313,19 @@ pub struct TidyMacApp {
     phase: AppPhase,
     receiver: Option<mpsc::Receiver<BgMessage>>,
     progress_label: String,
-    progress_total: usize,
-    progress_completed: usize,
     confirm_dialog: ConfirmDialog,
     errors: Vec<String>,
     cleaned_bytes: u64,
     about_visible: bool,
+    settings_visible: bool,
+    settings_draft: IoLimits,
+    keep_draft: KeepRule,
+    similar_draft: u32,
+    stale_days_draft: u32,
+    derived_days_draft: u32,
+    /// One folder per line, edited as text in the settings dialog.
+    preferred_text: String,
+    protected_text: String,
     disk_info: Option<DiskInfo>,
     monitor: Option<Monitor>,
     monitor_enabled: bool,
@@ -227,6 +344,24 @@ pub struct TidyMacApp {
     analyzer_total: usize,
     analyzer_current: String,
     analyzer_hover: Vec<f32>,
+    /// Storage by file type, gathered alongside each full scan.
+    type_report: Option<TypeReport>,
+    type_expanded: Option<FileKind>,
+    usage_cache: UsageCache,
+    /// Other mounted volumes, listed again after every scan and file action
+    /// rather than on each frame.
+    volumes: Vec<PathBuf>,
+    /// Folder shown in the disk usage explorer.
+    usage_dir: PathBuf,
+    usage_sort: SortMode,
+    usage_treemap: bool,
+    /// Folder being measured, if any.
+    usage_measuring: Option<PathBuf>,
+    /// Files moved to other volumes, each with whether its volume was
+    /// connected when the list was loaded; reloaded with `volumes`.
+    offloaded: Vec<(offload::Offloaded, bool)>,
+    /// Offloaded files the user asked to bring back.
+    pending_bring_back: Vec<(PathBuf, u64)>,
     ram_optimizing: bool,
     ram_before: Option<(u64, u64)>,
     ram_after: Option<(u64, u64)>,
@@ -312,7 +447,7 @@ impl TidyMacApp {
         cc.egui_ctx.set_style(style);
 
         // ── Build categories ──
-        let cleaners = crate::categories::all_cleaners(104_857_600, None);
+        let cleaners = categories::all_cleaners(SCAN_MIN_SIZE, SCAN_PATH);
         let categories: Vec<CategoryState> = cleaners
             .iter()
             .map(|c| {
@@ -322,11 +457,20 @@ impl TidyMacApp {
                 label: c.label(),
                 icon,
                 icon_color,
-                selected: c.name() != "large-files" && c.name() != "old-files",
+                selected: !matches!(c.name(), "large-files" | "old-files" | "similar-images"),
                 expanded: false,
                 scan_result: None,
                 entry_selected: vec![],
                 is_report_only: c.name() == "large-files",
+                skips_in_use: c.skips_in_use(),
+                pending_quit: None,
+                pending_dedupe: false,
+                per_file_actions: c.name() == "large-files",
+                pending_action: None,
+                archivable: c.name() == "old-files",
+                pending_archive: None,
+                offloadable: matches!(c.name(), "large-files" | "old-files"),
+                thumbnails: HashMap::new(),
             }})
             .collect();
 
@@ -336,8 +480,6 @@ impl TidyMacApp {
             phase: AppPhase::Idle,
             receiver: None,
             progress_label: String::new(),
-            progress_total: 0,
-            progress_completed: 0,
             confirm_dialog: ConfirmDialog {
                 visible: false,
                 shred_mode: false,
@@ -348,6 +490,14 @@ impl TidyMacApp {
             errors: vec![],
             cleaned_bytes: 0,
             about_visible: false,
+            settings_visible: false,
+            settings_draft: throttle::limits(),
+            keep_draft: KeepRule::default(),
+            similar_draft: categories::similarity_distance(),
+            stale_days_draft: categories::stale_project_days(),
+            derived_days_draft: categories::derived_data_days(),
+            preferred_text: String::new(),
+            protected_text: String::new(),
             disk_info: disk_info::get_disk_info(),
             monitor: None,
             monitor_enabled: false,
@@ -370,6 +520,16 @@ impl TidyMacApp {
             analyzer_total: 0,
             analyzer_current: String::new(),
             analyzer_hover: vec![],
+            type_report: None,
+            type_expanded: None,
+            usage_cache: UsageCache::default(),
+            volumes: file_actions::other_volumes(),
+            usage_dir: utils::home_dir(),
+            usage_sort: SortMode::Size,
+            usage_treemap: true,
+            usage_measuring: None,
+            offloaded: load_offloaded(),
+            pending_bring_back: Vec::new(),
             ram_optimizing: false,
             ram_before: None,
             ram_after: None,
@@ -395,38 +555,40 @@ impl TidyMacApp {
         self.progress_label = "Starting scan...".to_string();
         self.errors.clear();
         self.cleaned_bytes = 0;
-        self.progress_total = self.categories.len();
-        self.progress_completed = 0;
+        let operation = progress::begin(WorkKind::Scan, self.categories.len() as u64 + 1, 0);
 
         for cat in &mut self.categories {
             cat.scan_result = None;
             cat.entry_selected.clear();
         }
+        self.type_report = None;
+        self.type_expanded = None;
 
         let (tx, rx) = mpsc::channel::<BgMessage>();
         self.receiver = Some(rx);
 
-        // Parallel scanning: spawn one thread per category
+        // Parallel scanning on a bounded set of worker threads
         std::thread::spawn(move || {
-            let cleaners = crate::categories::all_cleaners(104_857_600, None);
-            let handles: Vec<_> = cleaners
+            let _operation = operation;
+            let mut jobs: Vec<ScanJob> = categories::all_cleaners(SCAN_MIN_SIZE, SCAN_PATH)
                 .into_iter()
-                .map(|cleaner| {
-                    let tx = tx.clone();
-                    std::thread::spawn(move || {
+                .map(ScanJob::Category)
+                .collect();
+            jobs.push(ScanJob::StorageByType);
+            throttle::run_bounded(jobs, |job| {
+                match job {
+                    ScanJob::Category(cleaner) => {
                         let _ = tx.send(BgMessage::Progress(cleaner.label().to_string()));
                         let result = cleaner.scan();
-                        let _ = tx.send(BgMessage::ScanComplete(
-                            cleaner.name().to_string(),
-                            result,
-                        ));
-                    })
-                })
-                .collect();
-
-            for h in handles {
-                let _ = h.join();
-            }
+                        let _ = tx.send(BgMessage::ScanComplete(cleaner.name().to_string(), result));
+                    }
+                    ScanJob::StorageByType => {
+                        let _ = tx.send(BgMessage::Progress("Storage by type".to_string()));
+                        let _ = tx.send(BgMessage::TypeReport(categories::storage_by_type(None)));
+                    }
+                }
+                progress::item_done();
+            });
             let _ = tx.send(BgMessage::AllScansComplete { smart_clean: false });
         });
     }
@@ -456,34 +618,24 @@ impl TidyMacApp {
         }
 
         let safe_names: Vec<String> = safe.iter().map(|s| s.to_string()).collect();
-        self.progress_total = safe_names.len();
-        self.progress_completed = 0;
+        let operation = progress::begin(WorkKind::Scan, safe_names.len() as u64, 0);
 
         let (tx, rx) = mpsc::channel::<BgMessage>();
         self.receiver = Some(rx);
 
         // Parallel scanning for smart clean
         std::thread::spawn(move || {
-            let cleaners = crate::categories::all_cleaners(104_857_600, None);
-            let handles: Vec<_> = cleaners
+            let _operation = operation;
+            let cleaners: Vec<_> = categories::all_cleaners(SCAN_MIN_SIZE, SCAN_PATH)
                 .into_iter()
                 .filter(|c| safe_names.contains(&c.name().to_string()))
-                .map(|cleaner| {
-                    let tx = tx.clone();
-                    std::thread::spawn(move || {
-                        let _ = tx.send(BgMessage::Progress(cleaner.label().to_string()));
-                        let result = cleaner.scan();
-                        let _ = tx.send(BgMessage::ScanComplete(
-                            cleaner.name().to_string(),
-                            result,
-                        ));
-                    })
-                })
                 .collect();
-
-            for h in handles {
-                let _ = h.join();
-            }
+            throttle::run_bounded(cleaners, |cleaner| {
+                let _ = tx.send(BgMessage::Progress(cleaner.label().to_string()));
+                let result = cleaner.scan();
+                let _ = tx.send(BgMessage::ScanComplete(cleaner.name().to_string(), result));
+                progress::item_done();
+            });
             let _ = tx.send(BgMessage::AllScansComplete { smart_clean: true });
         });
     }
@@ -501,28 +653,60 @@ impl TidyMacApp {
                 continue;
             }
             if let Some(ref result) = cat.scan_result {
-                for (entry, sel) in result.entries.iter().zip(cat.entry_selected.iter()) {
+                // Last line of defence: every duplicate group keeps a copy
+                let mut selected = cat.entry_selected.clone();
+                result.enforce_survivors(&mut selected);
+                for (entry, sel) in result.entries.iter().zip(selected.iter()) {
                     if *sel {
+                        if entry.in_use_by.is_some() {
+                            continue;
+                        }
                         items.push(DeleteItem {
                             category_name: cat.name.to_string(),
                             path: entry.path.clone(),
                             size_bytes: entry.size_bytes,
+                            check_in_use: cat.skips_in_use,
                         });
                     }
                 }
             }
         }
 
+        let total_bytes = items.iter().map(|i| i.size_bytes).sum();
+        let operation = progress::begin(WorkKind::Transfer, items.len() as u64, total_bytes);
+
         let (tx, rx) = mpsc::channel::<BgMessage>();
         self.receiver = Some(rx);
 
         std::thread::spawn(move || {
+            let _operation = operation;
+            throttle::apply_thread_priority();
+            let in_use = items
+                .iter()
+                .any(|i| i.check_in_use)
+                .then(InUseSnapshot::capture);
+            // Some categories remove their entries in their own way
+            let cleaners = categories::all_cleaners(SCAN_MIN_SIZE, SCAN_PATH);
             for item in &items {
+                if let Some(owner) = Self::still_in_use(item, in_use.as_ref()) {
+                    let _ = tx.send(BgMessage::DeleteError(
+                        item.category_name.clone(),
+                        item.path.clone(),
+                        format!("in use by {owner} \u{2014} skipped"),
+                    ));
+                    progress::bytes_done(item.size_bytes);
+                    progress::item_done();
+                    continue;
+                }
                 let _ = tx.send(BgMessage::Progress(format!(
                     "Deleting: {}",
                     item.path.display()
                 )));
-                match utils::safe_remove(&item.path) {
+                let removed = match cleaners.iter().find(|c| c.name() == item.category_name) {
+                    Some(cleaner) => cleaner.remove(&item.path),
+                    None => utils::safe_remove(&item.path),
+                };
+                match removed {
                     Ok(freed) => {
                         let _ = tx.send(BgMessage::DeletedFile(
                             item.category_name.clone(),
@@ -538,11 +722,238 @@ impl TidyMacApp {
                         ));
                     }
                 }
+                progress::bytes_done(item.size_bytes);
+                progress::item_done();
+            }
+            let _ = tx.send(BgMessage::AllCleansComplete);
+        });
+    }
+
+    fn still_in_use(item: &DeleteItem, snapshot: Option<&InUseSnapshot>) -> Option<String> {
+        if !item.check_in_use {
+            return None;
+        }
+        snapshot?.owner_of(&item.path)
+    }
+
+    /// Replace the selected duplicate copies of a category with clones of, or
+    /// hard links to, their group's original instead of deleting them.
+    fn start_dedupe(&mut self, cat_idx: usize) {
+        let cat = &self.categories[cat_idx];
+        let Some(ref result) = cat.scan_result else {
+            return;
+        };
+
+        let mut selected = cat.entry_selected.clone();
+        result.enforce_survivors(&mut selected);
+        let items: Vec<(PathBuf, PathBuf, u64)> = result
+            .entries
+            .iter()
+            .zip(selected.iter())
+            // Whole duplicate folders can only be deleted
+            .filter(|(e, sel)| **sel && e.in_use_by.is_none() && !e.path.is_dir())
+            .filter_map(|(e, _)| {
+                let original = e.duplicate_of.clone()?;
+                Some((e.path.clone(), original, e.size_bytes))
+            })
+            .collect();
+        if items.is_empty() {
+            return;
+        }
+
+        let cat_name = cat.name.to_string();
+        self.phase = AppPhase::Cleaning;
+        self.progress_label = "Linking duplicates...".to_string();
+        self.cleaned_bytes = 0;
+        self.clean_report.clear();
+
+        // Every pair is read in full for verification
+        let total_bytes: u64 = items.iter().map(|(_, _, size)| size * 2).sum();
+        let operation = progress::begin(WorkKind::Transfer, items.len() as u64, total_bytes);
+
+        let (tx, rx) = mpsc::channel::<BgMessage>();
+        self.receiver = Some(rx);
+
+        std::thread::spawn(move || {
+            let _operation = operation;
+            throttle::apply_thread_priority();
+            let mut expected = 0u64;
+            for (path, original, size) in &items {
+                let _ = tx.send(BgMessage::Progress(format!("Linking: {}", path.display())));
+                match dedupe::link_to_original(original, path) {
+                    Ok((method, freed)) => {
+                        let _ = tx.send(BgMessage::ProcessedFile(
+                            cat_name.clone(),
+                            path.clone(),
+                            freed,
+                            method.label().to_string(),
+                        ));
+                    }
+                    Err(e) => {
+                        let _ = tx.send(BgMessage::ActionError(path.clone(), "link", e.to_string()));
+                    }
+                }
+                // Verification stops early on a mismatch; credit the rest
+                expected += size * 2;
+                let done = progress::snapshot().bytes_done;
+                progress::bytes_done(expected.saturating_sub(done));
+                progress::item_done();
+            }
+            let _ = tx.send(BgMessage::AllCleansComplete);
+        });
+    }
+
+    /// Apply `action` to each selected entry of a category, one file at a time.
+    fn start_file_action(&mut self, cat_idx: usize, action: FileAction) {
+        let cat = &self.categories[cat_idx];
+        let Some(ref result) = cat.scan_result else {
+            return;
+        };
+
+        let items: Vec<(PathBuf, u64)> = result
+            .entries
+            .iter()
+            .zip(cat.entry_selected.iter())
+            .filter(|(e, sel)| **sel && e.in_use_by.is_none())
+            .map(|(e, _)| (e.path.clone(), e.size_bytes))
+            .collect();
+        let cat_name = cat.name.to_string();
+        self.run_file_action(cat_name, items, action);
+    }
+
+    /// Apply `action` to each of `items` (path, size) in the background,
+    /// reporting under `cat_name`.
+    fn run_file_action(&mut self, cat_name: String, items: Vec<(PathBuf, u64)>, action: FileAction) {
+        if items.is_empty() {
+            return;
+        }
+
+        self.phase = AppPhase::Cleaning;
+        self.progress_label = "Working...".to_string();
+        self.cleaned_bytes = 0;
+        self.clean_report.clear();
+
+        let total_bytes: u64 = items.iter().map(|(_, size)| size).sum();
+        let operation = progress::begin(WorkKind::Transfer, items.len() as u64, total_bytes);
+
+        let (tx, rx) = mpsc::channel::<BgMessage>();
+        self.receiver = Some(rx);
+
+        std::thread::spawn(move || {
+            let _operation = operation;
+            throttle::apply_thread_priority();
+            let mut expected = 0u64;
+            for (path, size) in &items {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let _ = tx.send(BgMessage::Progress(format!("{}: {name}", action.progress_label())));
+                match action.apply(path) {
+                    Ok((freed, note)) => {
+                        let _ = tx.send(BgMessage::ProcessedFile(cat_name.clone(), path.clone(), freed, note));
+                    }
+                    Err(e) => {
+                        let _ = tx.send(BgMessage::ActionError(path.clone(), action.verb(), e.to_string()));
+                    }
+                }
+                // Only copies across volumes report bytes as they go
+                expected += size;
+                let done = progress::snapshot().bytes_done;
+                progress::bytes_done(expected.saturating_sub(done));
+                progress::item_done();
+            }
+            let _ = tx.send(BgMessage::AllCleansComplete);
+        });
+    }
+
+    /// Pack the selected entries of a category into one verified archive in
+    /// `dest`, then remove the originals that didn't change meanwhile.
+    fn start_archive(&mut self, cat_idx: usize, dest: PathBuf) {
+        let cat = &self.categories[cat_idx];
+        let Some(ref result) = cat.scan_result else {
+            return;
+        };
+
+        let items: Vec<(PathBuf, u64)> = result
+            .entries
+            .iter()
+            .zip(cat.entry_selected.iter())
+            .filter(|(e, sel)| **sel && e.in_use_by.is_none() && !e.path.is_dir())
+            .map(|(e, _)| (e.path.clone(), e.size_bytes))
+            .collect();
+        if items.is_empty() {
+            return;
+        }
+
+        let cat_name = cat.name.to_string();
+        let title = cat.label.to_string();
+        self.phase = AppPhase::Cleaning;
+        self.progress_label = "Archiving...".to_string();
+        self.cleaned_bytes = 0;
+        self.clean_report.clear();
+
+        // Every file is read once to pack it and once more to verify
+        let total_bytes: u64 = items.iter().map(|(_, size)| size * 2).sum();
+        let operation = progress::begin(WorkKind::Transfer, items.len() as u64, total_bytes);
+
+        let (tx, rx) = mpsc::channel::<BgMessage>();
+        self.receiver = Some(rx);
+
+        std::thread::spawn(move || {
+            let _operation = operation;
+            throttle::apply_thread_priority();
+            let paths: Vec<PathBuf> = items.into_iter().map(|(path, _)| path).collect();
+            let _ = tx.send(BgMessage::Progress(format!("Archiving {} files...", paths.len())));
+            match archive::archive_files(&paths, &dest, &title) {
+                Ok(outcome) => {
+                    let name = outcome.archive.file_name().unwrap_or_default().to_string_lossy().to_string();
+                    for file in &outcome.files {
+                        match archive::remove_original(file) {
+                            Ok(freed) => {
+                                let _ = tx.send(BgMessage::ProcessedFile(
+                                    cat_name.clone(),
+                                    file.path.clone(),
+                                    freed,
+                                    format!("archived in {name}"),
+                                ));
+                            }
+                            Err(e) => {
+                                let _ = tx.send(BgMessage::ActionError(file.path.clone(), "remove", e.to_string()));
+                            }
+                        }
+                        progress::item_done();
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.send(BgMessage::ActionError(dest.clone(), "archive into", e.to_string()));
+                }
             }
             let _ = tx.send(BgMessage::AllCleansComplete);
         });
     }
 
+    /// Quit the app holding entries of a category, then rescan that category.
+    fn start_quit_and_rescan(&mut self, app_name: String, category: &'static str) {
+        self.phase = AppPhase::Scanning;
+        self.progress_label = format!("Quitting {app_name}...");
+        let operation = progress::begin(WorkKind::Scan, 1, 0);
+
+        let (tx, rx) = mpsc::channel::<BgMessage>();
+        self.receiver = Some(rx);
+
+        std::thread::spawn(move || {
+            let _operation = operation;
+            if !in_use::quit_app(&app_name, std::time::Duration::from_secs(15)) {
+                let _ = tx.send(BgMessage::AppQuitFailed(app_name));
+            }
+            if let Some(cleaner) = categories::find_cleaner(category, SCAN_MIN_SIZE, SCAN_PATH) {
+                let _ = tx.send(BgMessage::Progress(cleaner.label().to_string()));
+                let result = cleaner.scan();
+                let _ = tx.send(BgMessage::ScanComplete(cleaner.name().to_string(), result));
+                progress::item_done();
+            }
+            let _ = tx.send(BgMessage::AllScansComplete { smart_clean: false });
+        });
+    }
+
     fn drain_messages(&mut self) {
         let mut trigger_smart_confirm = false;
 
@@ -554,15 +965,24 @@ impl TidyMacApp {
                     }
                     BgMessage::ScanComplete(name, result) => {
                         if let Some(cat) = self.categories.iter_mut().find(|c| c.name == name) {
-                            let count = result.entries.len();
+                            // Per-file actions are opt-in, one file at a time
+                            cat.entry_selected = if cat.per_file_actions {
+                                vec![false; result.entries.len()]
+                            } else {
+                                result.default_selection()
+                            };
+                            cat.thumbnails.clear();
                             cat.scan_result = Some(result);
-                            cat.entry_selected = vec![true; count];
                         }
-                        self.progress_completed += 1;
+                    }
+                    BgMessage::TypeReport(report) => {
+                        self.type_report = Some(report);
                     }
                     BgMessage::AllScansComplete { smart_clean } => {
                         self.phase = AppPhase::Idle;
                         self.progress_label.clear();
+                        self.volumes = file_actions::other_volumes();
+                        self.offloaded = load_offloaded();
                         if smart_clean {
                             trigger_smart_confirm = true;
                         }
@@ -575,26 +995,33 @@ impl TidyMacApp {
                             path.display(),
                             utils::format_size(freed),
                         ));
-                        if let Some(cat) = self.categories.iter_mut().find(|c| c.name == cat_name) {
-                            if let Some(ref mut result) = cat.scan_result {
-                                if let Some(idx) = result.entries.iter().position(|e| e.path == path)
-                                {
-                                    result.entries.remove(idx);
-                                    cat.entry_selected.remove(idx);
-                                    result.total_bytes =
-                                        result.entries.iter().map(|e| e.size_bytes).sum();
-                                }
-                            }
-                        }
+                        Self::forget_entry(&mut self.categories, &cat_name, &path);
+                    }
+                    BgMessage::ProcessedFile(cat_name, path, freed, note) => {
+                        self.cleaned_bytes += freed;
+                        self.clean_report.push(format!(
+                            "[{}] {} ({}, {})",
+                            cat_name,
+                            path.display(),
+                            note,
+                            utils::format_size(freed),
+                        ));
+                        Self::forget_entry(&mut self.categories, &cat_name, &path);
                     }
                     BgMessage::DeleteError(_cat_name, path, err) => {
                         self.errors
                             .push(format!("Failed to delete {}: {err}", path.display()));
                     }
+                    BgMessage::ActionError(path, action, err) => {
+                        self.errors
+                            .push(format!("Could not {action} {}: {err}", path.display()));
+                    }
                     BgMessage::AllCleansComplete | BgMessage::AllShredsComplete => {
                         self.phase = AppPhase::Idle;
                         self.progress_label.clear();
                         self.disk_info = disk_info::get_disk_info();
+                        self.offloaded = load_offloaded();
+                        self.volumes = file_actions::other_volumes();
                         if let Some(ref mut mon) = self.monitor {
                             mon.refresh();
                         }
@@ -614,6 +1041,13 @@ impl TidyMacApp {
                         self.analyzer_current.clear();
                         self.progress_label.clear();
                     }
+                    BgMessage::UsageComplete(dir, listings) => {
+                        self.usage_cache.update(&dir, listings);
+                        self.usage_measuring = None;
+                        self.volumes = file_actions::other_volumes();
+                        self.phase = AppPhase::Idle;
+                        self.progress_label.clear();
+                    }
                     BgMessage::RamOptimizeComplete(used, total) => {
                         self.ram_after = Some((used, total));
                         self.ram_optimizing = false;
@@ -622,6 +1056,11 @@ impl TidyMacApp {
                         self.ram_error = Some(err);
                         self.ram_optimizing = false;
                     }
+                    BgMessage::AppQuitFailed(app_name) => {
+                        self.errors.push(format!(
+                            "{app_name} did not quit; its data was left untouched."
+                        ));
+                    }
                 }
             }
         }
@@ -636,6 +1075,19 @@ impl TidyMacApp {
         }
     }
 
+    /// Drop an entry that no longer needs attention from its category's result.
+    fn forget_entry(categories: &mut [CategoryState], cat_name: &str, path: &Path) {
+        let Some(cat) = categories.iter_mut().find(|c| c.name == cat_name) else {
+            return;
+        };
+        if let Some(ref mut result) = cat.scan_result {
+            if let Some(idx) = result.entries.iter().position(|e| e.path == path) {
+                result.remove_entry(idx);
+                cat.entry_selected.remove(idx);
+            }
+        }
+    }
+
     fn show_confirm_dialog(&mut self, shred_mode: bool) {
         let mut total_bytes = 0u64;
         let mut file_count = 0usize;
@@ -680,28 +1132,55 @@ impl TidyMacApp {
                 continue;
             }
             if let Some(ref result) = cat.scan_result {
-                for (entry, sel) in result.entries.iter().zip(cat.entry_selected.iter()) {
+                // Last line of defence: every duplicate group keeps a copy
+                let mut selected = cat.entry_selected.clone();
+                result.enforce_survivors(&mut selected);
+                for (entry, sel) in result.entries.iter().zip(selected.iter()) {
                     if *sel {
+                        if entry.in_use_by.is_some() {
+                            continue;
+                        }
                         items.push(DeleteItem {
                             category_name: cat.name.to_string(),
                             path: entry.path.clone(),
                             size_bytes: entry.size_bytes,
+                            check_in_use: cat.skips_in_use,
                         });
                     }
                 }
             }
         }
 
+        // Every pass rewrites the whole file
+        let total_bytes = items.iter().map(|i| i.size_bytes).sum::<u64>() * shredder::PASSES as u64;
+        let operation = progress::begin(WorkKind::Transfer, items.len() as u64, total_bytes);
+
         let (tx, rx) = mpsc::channel::<BgMessage>();
         self.receiver = Some(rx);
 
         std::thread::spawn(move || {
+            let _operation = operation;
+            throttle::apply_thread_priority();
+            let in_use = items
+                .iter()
+                .any(|i| i.check_in_use)
+                .then(InUseSnapshot::capture);
             for item in &items {
+                if let Some(owner) = Self::still_in_use(item, in_use.as_ref()) {
+                    let _ = tx.send(BgMessage::DeleteError(
+                        item.category_name.clone(),
+                        item.path.clone(),
+                        format!("in use by {owner} \u{2014} skipped"),
+                    ));
+                    progress::bytes_done(item.size_bytes * shredder::PASSES as u64);
+                    progress::item_done();
+                    continue;
+                }
                 let tx_ref = &tx;
                 let mut progress_fn = |msg: &str| {
                     let _ = tx_ref.send(BgMessage::Progress(msg.to_string()));
                 };
-                match crate::shredder::shred_file(&item.path, &mut progress_fn) {
+                match shredder::shred_file(&item.path, &mut progress_fn) {
                     Ok(freed) => {
                         let _ = tx.send(BgMessage::DeletedFile(
                             item.category_name.clone(),
@@ -717,6 +1196,7 @@ impl TidyMacApp {
                         ));
                     }
                 }
+                progress::item_done();
             }
             let _ = tx.send(BgMessage::AllShredsComplete);
         });
@@ -742,6 +1222,24 @@ impl TidyMacApp {
 
             ui.add_space(4.0);
 
+            let usage_btn = egui::Button::new(
+                egui::RichText::new("Disk Usage")
+                    .size(12.0)
+                    .color(ACCENT),
+            )
+            .corner_radius(egui::CornerRadius::same(6))
+            .min_size(egui::vec2(90.0, 24.0));
+            if ui.add(usage_btn).on_hover_text("Browse folders by size").clicked() {
+                self.view_mode = ViewMode::DiskUsage;
+                self.view_alpha = 0.0;
+                if self.usage_cache.get(&self.usage_dir).is_none() {
+                    let dir = self.usage_dir.clone();
+                    self.start_usage_measure(dir);
+                }
+            }
+
+            ui.add_space(4.0);
+
             // Monitor toggle button
             let mon_label = if self.monitor_enabled { "Monitor: ON" } else { "Monitor: OFF" };
             let mon_color = if self.monitor_enabled { GREEN } else { TEXT_SECONDARY };
@@ -761,6 +1259,26 @@ impl TidyMacApp {
                 }
             }
 
+            ui.add_space(4.0);
+
+            let settings_btn = egui::Button::new(
+                egui::RichText::new("Settings")
+                    .size(11.0)
+                    .color(TEXT_SECONDARY),
+            )
+            .corner_radius(egui::CornerRadius::same(6))
+            .min_size(egui::vec2(70.0, 24.0));
+            if ui.add(settings_btn).on_hover_text("Scan speed, priority and duplicate handling").clicked() {
+                self.settings_draft = throttle::limits();
+                self.keep_draft = categories::keep_rule();
+                self.similar_draft = categories::similarity_distance();
+                self.stale_days_draft = categories::stale_project_days();
+                self.derived_days_draft = categories::derived_data_days();
+                self.preferred_text = Self::paths_to_text(&self.keep_draft.preferred_folders);
+                self.protected_text = Self::paths_to_text(&self.keep_draft.protected_roots);
+                self.settings_visible = true;
+            }
+
             ui.add_space(ui.available_width() - 30.0);
             let about_btn = egui::Button::new(
                 egui::RichText::new("i")
@@ -1056,11 +1574,8 @@ impl TidyMacApp {
         if is_busy {
             ui.add_space(8.0);
 
-            let target_frac = if self.progress_total > 0 {
-                self.progress_completed as f32 / self.progress_total as f32
-            } else {
-                0.0
-            };
+            let work = progress::snapshot();
+            let target_frac = work.fraction();
             self.anim_progress = lerp_f32(self.anim_progress, target_frac, 0.10);
             let frac = self.anim_progress;
 
@@ -1125,11 +1640,8 @@ impl TidyMacApp {
                 let dot_count = (time * 3.0) as usize % 4;
                 let dots: String = ".".repeat(dot_count);
 
-                let counter = if self.progress_total > 0 && self.phase == AppPhase::Scanning {
-                    format!(
-                        " ({}/{})",
-                        self.progress_completed, self.progress_total
-                    )
+                let counter = if work.items_total > 0 {
+                    format!(" ({}/{})", work.items_done, work.items_total)
                 } else {
                     String::new()
                 };
@@ -1139,6 +1651,40 @@ impl TidyMacApp {
                         .color(TEXT_SECONDARY),
                 );
             });
+
+            // Work units: files visited, bytes processed, throughput and ETA
+            let mut details = Vec::new();
+            if work.kind == WorkKind::Scan && work.files_visited > 0 {
+                details.push(format!("{} files scanned", work.files_visited));
+            }
+            if work.bytes_total > 0 {
+                let verb = match work.kind {
+                    WorkKind::Scan => "hashed",
+                    WorkKind::Transfer => "processed",
+                };
+                details.push(format!(
+                    "{} / {} {}",
+                    utils::format_size(work.bytes_done.min(work.bytes_total)),
+                    utils::format_size(work.bytes_total),
+                    verb
+                ));
+                if work.bytes_done > 0 {
+                    details.push(Self::format_rate(work.throughput()));
+                }
+            }
+            if let Some(eta) = work.eta() {
+                details.push(format!("ETA {}", progress::format_eta(eta)));
+            }
+            if !details.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.add_space(8.0);
+                    ui.label(
+                        egui::RichText::new(details.join("  \u{00b7}  "))
+                            .size(11.0)
+                            .color(TEXT_SECONDARY),
+                    );
+                });
+            }
         } else {
             // Reset progress animation when idle
             self.anim_progress = 0.0;
@@ -1230,7 +1776,7 @@ impl TidyMacApp {
                 let cat = &self.categories[i];
                 let matches_label = cat.label.to_lowercase().contains(&filter);
                 let matches_name = cat.name.to_lowercase().contains(&filter);
-                let matches_files = cat.scan_result.as_ref().map_or(false, |r| {
+                let matches_files = cat.scan_result.as_ref().is_some_and(|r| {
                     r.entries.iter().any(|e| {
                         e.path.to_string_lossy().to_lowercase().contains(&filter)
                     })
@@ -1240,47 +1786,358 @@ impl TidyMacApp {
                 }
             }
             let hover_t = self.category_hover[i];
-            let resp = Self::render_category_row(ui, &mut self.categories[i], hover_t);
+            let resp = Self::render_category_row(ui, &mut self.categories[i], &self.volumes, hover_t);
             // Update hover state
             let target = if resp.hovered() { 1.0 } else { 0.0 };
             self.category_hover[i] = lerp_f32(self.category_hover[i], target, 0.15);
             ui.add_space(4.0);
+
+            if let Some(app_name) = self.categories[i].pending_quit.take() {
+                if self.phase == AppPhase::Idle {
+                    let name = self.categories[i].name;
+                    self.start_quit_and_rescan(app_name, name);
+                }
+            }
+            if std::mem::take(&mut self.categories[i].pending_dedupe) && self.phase == AppPhase::Idle {
+                self.start_dedupe(i);
+            }
+            if let Some(action) = self.categories[i].pending_action.take() {
+                if self.phase == AppPhase::Idle {
+                    self.start_file_action(i, action);
+                }
+            }
+            if let Some(dest) = self.categories[i].pending_archive.take() {
+                if self.phase == AppPhase::Idle {
+                    self.start_archive(i, dest);
+                }
+            }
         }
     }
 
-    fn render_category_row(ui: &mut egui::Ui, cat: &mut CategoryState, hover_t: f32) -> egui::Response {
-        let selected_size = cat.selected_bytes();
-        let total_size = cat.scan_result.as_ref().map(|r| r.total_bytes).unwrap_or(0);
-
-        let size_text = if cat.scan_result.is_none() {
-            "---".to_string()
-        } else if selected_size == total_size {
-            utils::format_size(total_size)
-        } else {
-            format!(
-                "{} / {}",
-                utils::format_size(selected_size),
-                utils::format_size(total_size)
+    /// Trash / move / offload / compress buttons for the files ticked one by one.
+    fn render_file_actions(ui: &mut egui::Ui, cat: &mut CategoryState, volumes: &[PathBuf]) {
+        let selected = cat.selected_count();
+        let action_btn = |text: &str| {
+            egui::Button::new(
+                egui::RichText::new(text)
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(160, 160, 180)),
             )
+            .corner_radius(egui::CornerRadius::same(4))
+            .min_size(egui::vec2(70.0, 22.0))
         };
 
-        let base_fill = if cat.expanded { CARD_EXPANDED } else { CARD_FILL };
-        let card_fill = lerp_color(base_fill, CARD_HOVER, hover_t);
-        let border_color = lerp_color(BORDER, BORDER_HOVER, hover_t);
+        ui.horizontal(|ui| {
+            if selected == 0 {
+                ui.label(
+                    egui::RichText::new("Tick files to move them to the Trash or another volume, offload them, or compress them.")
+                        .size(11.0)
+                        .color(TEXT_SECONDARY),
+                );
+                return;
+            }
 
-        let frame_resp = egui::Frame::NONE
-            .fill(card_fill)
-            .corner_radius(egui::CornerRadius::same(10))
-            .inner_margin(egui::Margin::symmetric(12, 10))
-            .stroke(egui::Stroke::new(0.5 + hover_t * 0.5, border_color))
-            .show(ui, |ui| {
-                // ── Header row ──
-                ui.horizontal(|ui| {
-                    if cat.is_report_only {
-                        let mut dummy = false;
-                        ui.add_enabled(false, egui::Checkbox::new(&mut dummy, ""));
-                    } else {
-                        let before = cat.selected;
+            if ui
+                .add(action_btn(&format!("Move {selected} to Trash")))
+                .on_hover_text("Finder's Trash, so they can be put back. Space is freed once it is emptied.")
+                .clicked()
+            {
+                cat.pending_action = Some(FileAction::Trash);
+            }
+
+            ui.add_enabled_ui(!volumes.is_empty(), |ui| {
+                ui.menu_button(
+                    egui::RichText::new("Move to\u{2026}")
+                        .size(11.0)
+                        .color(egui::Color32::from_rgb(160, 160, 180)),
+                    |ui| {
+                        for volume in volumes {
+                            let name = volume.file_name().unwrap_or_default().to_string_lossy();
+                            if ui.button(name).clicked() {
+                                cat.pending_action = Some(FileAction::MoveTo(volume.clone()));
+                                ui.close_menu();
+                            }
+                        }
+                    },
+                )
+                .response
+                .on_disabled_hover_text("No other volume is mounted")
+                .on_hover_text("Copied and checked, then removed from this disk. Existing files are never overwritten.");
+            });
+
+            Self::render_offload_menu(ui, cat, volumes);
+
+            if ui
+                .add(action_btn("Compress"))
+                .on_hover_text("Replace each file with a zip next to it. Files that barely shrink are left alone.")
+                .clicked()
+            {
+                cat.pending_action = Some(FileAction::Compress);
+            }
+        });
+        ui.add_space(4.0);
+    }
+
+    /// "Offload to…" menu listing the other mounted volumes.
+    fn render_offload_menu(ui: &mut egui::Ui, cat: &mut CategoryState, volumes: &[PathBuf]) {
+        ui.add_enabled_ui(!volumes.is_empty(), |ui| {
+            ui.menu_button(
+                egui::RichText::new("Offload to\u{2026}")
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(160, 160, 180)),
+                |ui| {
+                    for volume in volumes {
+                        let name = volume.file_name().unwrap_or_default().to_string_lossy();
+                        if ui.button(name).clicked() {
+                            cat.pending_action = Some(FileAction::Offload(volume.clone()));
+                            ui.close_menu();
+                        }
+                    }
+                },
+            )
+            .response
+            .on_disabled_hover_text("No other volume is mounted")
+            .on_hover_text(
+                "Copied to the volume and checked by hash, with a link left in the original place. \
+                 Offloaded files can be brought back later.",
+            );
+        });
+    }
+
+    /// "Archive N files to…" with a choice of home or another volume.
+    fn render_archive_button(ui: &mut egui::Ui, cat: &mut CategoryState, volumes: &[PathBuf]) {
+        let mut destinations = vec![utils::home_dir().join("Archives")];
+        destinations.extend(
+            volumes.iter().map(|v| v.join("TidyMac Archives")),
+        );
+
+        ui.horizontal(|ui| {
+            ui.menu_button(
+                egui::RichText::new(format!("Archive {} instead of deleting\u{2026}", cat.selected_count()))
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(160, 160, 180)),
+                |ui| {
+                    for dest in &destinations {
+                        if ui.button(utils::display_path(dest)).clicked() {
+                            cat.pending_archive = Some(dest.clone());
+                            ui.close_menu();
+                        }
+                    }
+                },
+            )
+            .response
+            .on_hover_text(
+                "Pack the selected files into a compressed .tar.zst with a manifest beside it. \
+                 The archive is read back and checked before any original is removed.",
+            );
+            if cat.offloadable {
+                Self::render_offload_menu(ui, cat, volumes);
+            }
+        });
+        ui.add_space(4.0);
+    }
+
+    /// Duplicate groups: one "keep" choice per group, the other copies selectable.
+    fn render_duplicate_groups(ui: &mut egui::Ui, cat: &mut CategoryState) {
+        let Some(result) = cat.scan_result.as_mut() else {
+            return;
+        };
+
+        let copies_selected = result
+            .entries
+            .iter()
+            .zip(cat.entry_selected.iter())
+            .filter(|(e, sel)| **sel && e.duplicate_of.is_some() && !e.path.is_dir())
+            .count();
+        // Similar images differ in content, so only exact copies can be linked
+        let exact = !result.entries.iter().any(|e| e.image.is_some());
+        if exact && copies_selected > 0 {
+            let link_btn = egui::Button::new(
+                egui::RichText::new(format!("Link {copies_selected} copies instead of deleting"))
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(160, 160, 180)),
+            )
+            .corner_radius(egui::CornerRadius::same(4))
+            .min_size(egui::vec2(70.0, 22.0));
+            if ui
+                .add(link_btn)
+                .on_hover_text(
+                    "Replace the selected copies with clones of (or hard links to) the kept copy. \
+                     Every path stays in place with its own permissions and dates, and \
+                     contents are verified byte for byte first. Copies are only hard-linked \
+                     when those already match; later edits to a hard-linked path show up \
+                     in all of them.",
+                )
+                .clicked()
+            {
+                cat.pending_dedupe = true;
+            }
+            ui.add_space(4.0);
+        }
+
+        let mut keep_change: Option<(usize, PathBuf)> = None;
+        let mut selection_changed = false;
+
+        for (g, group) in result.groups.iter().enumerate() {
+            if g > 0 {
+                ui.add_space(6.0);
+            }
+            let similar = result
+                .entries
+                .iter()
+                .any(|e| e.image.is_some() && group.members.contains(&e.path));
+            let header = if similar {
+                format!("{} similar images \u{00b7} {}", group.members.len(), group.hash)
+            } else {
+                format!(
+                    "{} copies \u{00b7} {} each \u{00b7} {}",
+                    group.members.len(),
+                    utils::format_size(group.size_bytes),
+                    &group.hash[..group.hash.len().min(12)],
+                )
+            };
+            ui.label(egui::RichText::new(header).size(11.0).color(TEXT_SECONDARY));
+
+            for member in &group.members {
+                let Some(idx) = result.entries.iter().position(|e| e.path == *member) else {
+                    continue;
+                };
+                let is_original = group.original == *member;
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .radio(is_original, egui::RichText::new("keep").size(11.0))
+                        .on_hover_text("Keep this copy and offer the others for deletion")
+                        .clicked()
+                        && !is_original
+                    {
+                        keep_change = Some((g, member.clone()));
+                    }
+
+                    if let Some(sel) = cat.entry_selected.get_mut(idx) {
+                        if is_original {
+                            let mut kept = false;
+                            ui.add_enabled(false, egui::Checkbox::new(&mut kept, ""));
+                        } else if ui.checkbox(sel, "").changed() {
+                            selection_changed = true;
+                        }
+                    }
+
+                    let color = if is_original { GREEN } else { egui::Color32::from_rgb(150, 150, 165) };
+                    let entry = &result.entries[idx];
+                    if let Some(ref image) = entry.image {
+                        Self::render_thumbnail(ui, &mut cat.thumbnails, member, image);
+                        ui.vertical(|ui| {
+                            ui.label(
+                                egui::RichText::new(utils::display_path(member))
+                                    .size(12.0)
+                                    .color(color),
+                            );
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "{} \u{00d7} {} \u{00b7} {}",
+                                    image.width,
+                                    image.height,
+                                    utils::format_size(entry.size_bytes),
+                                ))
+                                .size(11.0)
+                                .color(TEXT_SECONDARY),
+                            );
+                        });
+                    } else {
+                        ui.label(
+                            egui::RichText::new(utils::display_path(member))
+                                .size(12.0)
+                                .color(color),
+                        );
+                    }
+                    if is_original {
+                        ui.label(egui::RichText::new("original").size(11.0).color(GREEN));
+                    }
+                });
+            }
+        }
+
+        if let Some((g, path)) = keep_change {
+            // The previous original becomes an ordinary, selected copy
+            let previous = result.groups[g].original.clone();
+            result.set_original(g, &path);
+            if let Some(idx) = result.entries.iter().position(|e| e.path == previous) {
+                cat.entry_selected[idx] = true;
+            }
+            selection_changed = true;
+        }
+        if selection_changed {
+            result.enforce_survivors(&mut cat.entry_selected);
+            cat.sync_category_from_entries();
+        }
+    }
+
+    fn render_thumbnail(
+        ui: &mut egui::Ui,
+        cache: &mut HashMap<PathBuf, egui::TextureHandle>,
+        path: &Path,
+        image: &ImageInfo,
+    ) {
+        const SIZE: f32 = 48.0;
+        let [w, h] = image.thumb_size;
+        if w == 0 || h == 0 {
+            ui.allocate_exact_size(egui::vec2(SIZE, SIZE), egui::Sense::hover());
+            return;
+        }
+        let texture = cache.entry(path.to_path_buf()).or_insert_with(|| {
+            let pixels = egui::ColorImage::from_rgba_unmultiplied([w, h], &image.thumb_rgba);
+            ui.ctx().load_texture(
+                path.to_string_lossy(),
+                pixels,
+                egui::TextureOptions::LINEAR,
+            )
+        });
+        let scale = SIZE / w.max(h) as f32;
+        ui.add(egui::Image::new((
+            texture.id(),
+            egui::vec2(w as f32 * scale, h as f32 * scale),
+        )));
+    }
+
+    fn render_category_row(
+        ui: &mut egui::Ui,
+        cat: &mut CategoryState,
+        volumes: &[PathBuf],
+        hover_t: f32,
+    ) -> egui::Response {
+        let selected_size = cat.selected_bytes();
+        let total_size = cat.scan_result.as_ref().map(|r| r.total_bytes).unwrap_or(0);
+
+        let size_text = if cat.scan_result.is_none() {
+            "---".to_string()
+        } else if selected_size == total_size {
+            utils::format_size(total_size)
+        } else {
+            format!(
+                "{} / {}",
+                utils::format_size(selected_size),
+                utils::format_size(total_size)
+            )
+        };
+
+        let base_fill = if cat.expanded { CARD_EXPANDED } else { CARD_FILL };
+        let card_fill = lerp_color(base_fill, CARD_HOVER, hover_t);
+        let border_color = lerp_color(BORDER, BORDER_HOVER, hover_t);
+
+        let frame_resp = egui::Frame::NONE
+            .fill(card_fill)
+            .corner_radius(egui::CornerRadius::same(10))
+            .inner_margin(egui::Margin::symmetric(12, 10))
+            .stroke(egui::Stroke::new(0.5 + hover_t * 0.5, border_color))
+            .show(ui, |ui| {
+                // ── Header row ──
+                ui.horizontal(|ui| {
+                    if cat.is_report_only {
+                        let mut dummy = false;
+                        ui.add_enabled(false, egui::Checkbox::new(&mut dummy, ""));
+                    } else {
+                        let before = cat.selected;
                         ui.checkbox(&mut cat.selected, "");
                         if cat.selected != before {
                             cat.set_all_entries(cat.selected);
@@ -1290,13 +2147,15 @@ impl TidyMacApp {
                     paint_icon(ui, cat.icon, cat.icon_color);
                     ui.add_space(4.0);
 
-                    let label_text = if cat.is_report_only {
+                    let label_text = if cat.per_file_actions {
+                        format!("{} [per-file actions only]", cat.label)
+                    } else if cat.is_report_only {
                         format!("{} [report only]", cat.label)
                     } else {
                         cat.label.to_string()
                     };
 
-                    let sel_info = if cat.entry_count() > 0 && !cat.is_report_only {
+                    let sel_info = if cat.entry_count() > 0 && (!cat.is_report_only || cat.per_file_actions) {
                         format!(" ({}/{})", cat.selected_count(), cat.entry_count())
                     } else {
                         String::new()
@@ -1389,41 +2248,126 @@ impl TidyMacApp {
                                 });
                                 ui.add_space(4.0);
                             }
+                            if cat.per_file_actions {
+                                Self::render_file_actions(ui, cat, volumes);
+                            }
+                            if cat.archivable && cat.selected_count() > 0 {
+                                Self::render_archive_button(ui, cat, volumes);
+                            }
 
-                            for idx in 0..entry_count {
-                                let (path_display, size_bytes) = {
-                                    let entry = &cat.scan_result.as_ref().unwrap().entries[idx];
-                                    (utils::display_path(&entry.path), entry.size_bytes)
-                                };
-
-                                ui.horizontal(|ui| {
-                                    if !cat.is_report_only && idx < cat.entry_selected.len() {
-                                        let before = cat.entry_selected[idx];
-                                        ui.checkbox(&mut cat.entry_selected[idx], "");
-                                        if cat.entry_selected[idx] != before {
-                                            cat.sync_category_from_entries();
+                            let grouped = cat
+                                .scan_result
+                                .as_ref()
+                                .is_some_and(|r| !r.groups.is_empty());
+                            if grouped {
+                                Self::render_duplicate_groups(ui, cat);
+                            } else {
+                                for idx in 0..entry_count {
+                                    let (path_display, size_bytes, in_use_by, detail, heading) = {
+                                        let entries = &cat.scan_result.as_ref().unwrap().entries;
+                                        let entry = &entries[idx];
+                                        let starts_section = idx == 0 || entries[idx - 1].heading != entry.heading;
+                                        (
+                                            utils::display_path(&entry.path),
+                                            entry.size_bytes,
+                                            entry.in_use_by.clone(),
+                                            entry.detail.clone(),
+                                            entry.heading.clone().filter(|_| starts_section),
+                                        )
+                                    };
+
+                                    if let Some(heading) = &heading {
+                                        if idx > 0 {
+                                            ui.add_space(4.0);
                                         }
+                                        ui.label(
+                                            egui::RichText::new(heading)
+                                                .size(12.0)
+                                                .strong()
+                                                .color(TEXT_PRIMARY),
+                                        );
                                     }
 
-                                    ui.label(
-                                        egui::RichText::new(&path_display)
-                                            .size(12.0)
-                                            .color(egui::Color32::from_rgb(150, 150, 165)),
-                                    );
-                                    ui.with_layout(
-                                        egui::Layout::right_to_left(egui::Align::Center),
-                                        |ui| {
+                                    ui.horizontal(|ui| {
+                                        if (!cat.is_report_only || cat.per_file_actions)
+                                            && idx < cat.entry_selected.len()
+                                        {
+                                            if in_use_by.is_some() {
+                                                let mut dummy = false;
+                                                ui.add_enabled(false, egui::Checkbox::new(&mut dummy, ""));
+                                            } else {
+                                                let before = cat.entry_selected[idx];
+                                                ui.checkbox(&mut cat.entry_selected[idx], "");
+                                                if cat.entry_selected[idx] != before {
+                                                    cat.sync_category_from_entries();
+                                                }
+                                            }
+                                        }
+
+                                        ui.label(
+                                            egui::RichText::new(&path_display)
+                                                .size(12.0)
+                                                .color(egui::Color32::from_rgb(150, 150, 165)),
+                                        );
+                                        if let Some(detail) = &detail {
                                             ui.label(
-                                                egui::RichText::new(utils::format_size(size_bytes))
-                                                    .size(12.0)
-                                                    .color(YELLOW),
+                                                egui::RichText::new(detail)
+                                                    .size(11.0)
+                                                    .color(TEXT_SECONDARY),
                                             );
-                                        },
-                                    );
-                                });
+                                        }
+                                        if let Some(owner) = &in_use_by {
+                                            ui.label(
+                                                egui::RichText::new("in use \u{2014} skipped")
+                                                    .size(11.0)
+                                                    .color(YELLOW),
+                                            )
+                                            .on_hover_text(format!("Used by {owner}"));
+                                        }
+                                        ui.with_layout(
+                                            egui::Layout::right_to_left(egui::Align::Center),
+                                            |ui| {
+                                                ui.label(
+                                                    egui::RichText::new(utils::format_size(size_bytes))
+                                                        .size(12.0)
+                                                        .color(YELLOW),
+                                                );
+                                            },
+                                        );
+                                    });
+                                }
                             }
                         }
 
+                        // Running apps holding entries — offer to quit them first;
+                        // daemons and command-line tools can't be asked to
+                        for (owner, count, is_app) in cat.in_use_owners() {
+                            ui.add_space(4.0);
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "[!] {count} item(s) in use by {owner} \u{2014} skipped."
+                                    ))
+                                    .size(12.0)
+                                    .color(YELLOW),
+                                );
+                                if !is_app {
+                                    return;
+                                }
+                                let btn = egui::Button::new(
+                                    egui::RichText::new(format!("Quit {owner} & Rescan")).size(11.0),
+                                )
+                                .corner_radius(egui::CornerRadius::same(4));
+                                if ui
+                                    .add(btn)
+                                    .on_hover_text("Quit the app, then rescan this category")
+                                    .clicked()
+                                {
+                                    cat.pending_quit = Some(owner.clone());
+                                }
+                            });
+                        }
+
                         // Errors
                         let errors: Vec<String> = cat
                             .scan_result
@@ -1489,7 +2433,7 @@ impl TidyMacApp {
             return;
         }
 
-        bars.sort_by(|a, b| b.2.cmp(&a.2));
+        bars.sort_by_key(|bar| std::cmp::Reverse(bar.2));
         let max_size = bars[0].2 as f64;
 
         egui::Frame::NONE
@@ -1563,6 +2507,252 @@ impl TidyMacApp {
         ui.add_space(6.0);
     }
 
+    /// Storage by file type; click a kind to list its largest files.
+    fn render_type_breakdown(&mut self, ui: &mut egui::Ui) {
+        if self.phase == AppPhase::Scanning {
+            return;
+        }
+        let Some(ref report) = self.type_report else {
+            return;
+        };
+        if report.total_bytes == 0 {
+            return;
+        }
+        let max_size = report.kinds.first().map_or(1, |k| k.bytes.max(1)) as f64;
+        let mut toggle: Option<FileKind> = None;
+
+        egui::Frame::NONE
+            .fill(CARD_FILL)
+            .corner_radius(egui::CornerRadius::same(10))
+            .stroke(egui::Stroke::new(0.5, BORDER))
+            .inner_margin(egui::Margin::symmetric(12, 10))
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new("Storage by Type")
+                            .size(12.0)
+                            .strong()
+                            .color(TEXT_PRIMARY),
+                    );
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} in your files (Library and developer folders excluded)",
+                            utils::format_size(report.total_bytes)
+                        ))
+                        .size(11.0)
+                        .color(TEXT_SECONDARY),
+                    );
+                });
+                ui.add_space(6.0);
+
+                let available_w = ui.available_width();
+                let label_w = 130.0;
+                let size_w = 150.0;
+                let bar_area = (available_w - label_w - size_w - 12.0).max(40.0);
+
+                for stats in &report.kinds {
+                    let bar_w = (bar_area * (stats.bytes as f64 / max_size) as f32).max(4.0);
+                    let bar_h = 14.0;
+                    let expanded = self.type_expanded == Some(stats.kind);
+
+                    ui.horizontal(|ui| {
+                        ui.allocate_ui_with_layout(
+                            egui::vec2(label_w, bar_h),
+                            egui::Layout::left_to_right(egui::Align::Center),
+                            |ui| {
+                                let arrow = if expanded { "\u{25BC}" } else { "\u{25B6}" };
+                                if ui
+                                    .selectable_label(
+                                        false,
+                                        egui::RichText::new(format!("{arrow} {}", stats.kind.label()))
+                                            .size(11.0)
+                                            .color(TEXT_PRIMARY),
+                                    )
+                                    .clicked()
+                                {
+                                    toggle = Some(stats.kind);
+                                }
+                            },
+                        );
+
+                        let (bar_rect, _) = ui.allocate_exact_size(
+                            egui::vec2(bar_w, bar_h),
+                            egui::Sense::hover(),
+                        );
+                        let painter = ui.painter();
+                        painter.rect_filled(bar_rect, 3.0, kind_color(stats.kind));
+                        let hl = egui::Rect::from_min_size(
+                            bar_rect.min,
+                            egui::vec2(bar_w, bar_h * 0.4),
+                        );
+                        painter.rect_filled(hl, 3.0, egui::Color32::from_white_alpha(15));
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "{} \u{00B7} {} files",
+                                    utils::format_size(stats.bytes),
+                                    stats.count
+                                ))
+                                .size(11.0)
+                                .color(TEXT_SECONDARY),
+                            );
+                        });
+                    });
+
+                    if expanded {
+                        egui::Frame::NONE
+                            .fill(INSET_FILL)
+                            .corner_radius(egui::CornerRadius::same(6))
+                            .inner_margin(egui::Margin::symmetric(8, 6))
+                            .show(ui, |ui| {
+                                ui.set_min_width(ui.available_width());
+                                for (path, size) in &stats.largest {
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            egui::RichText::new(utils::display_path(path))
+                                                .size(11.0)
+                                                .color(TEXT_PRIMARY),
+                                        );
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                            let reveal = egui::Button::new(
+                                                egui::RichText::new("Reveal")
+                                                    .size(10.0)
+                                                    .color(egui::Color32::from_rgb(160, 160, 180)),
+                                            )
+                                            .corner_radius(egui::CornerRadius::same(4));
+                                            if ui.add(reveal).on_hover_text("Show in Finder").clicked() {
+                                                let _ = std::process::Command::new("open")
+                                                    .arg("-R")
+                                                    .arg(path)
+                                                    .spawn();
+                                            }
+                                            ui.label(
+                                                egui::RichText::new(utils::format_size(*size))
+                                                    .size(11.0)
+                                                    .color(GREEN),
+                                            );
+                                        });
+                                    });
+                                }
+                            });
+                        ui.add_space(2.0);
+                    }
+
+                    ui.add_space(1.0);
+                }
+            });
+
+        if let Some(kind) = toggle {
+            self.type_expanded = if self.type_expanded == Some(kind) { None } else { Some(kind) };
+        }
+        ui.add_space(6.0);
+    }
+
+    /// Files offloaded to other volumes, with a way to bring them back.
+    fn render_offloaded(&mut self, ui: &mut egui::Ui) {
+        if self.offloaded.is_empty() || self.phase == AppPhase::Scanning {
+            return;
+        }
+        let idle = self.phase == AppPhase::Idle;
+        let small_btn = |text: &str| {
+            egui::Button::new(
+                egui::RichText::new(text)
+                    .size(10.0)
+                    .color(egui::Color32::from_rgb(160, 160, 180)),
+            )
+            .corner_radius(egui::CornerRadius::same(4))
+        };
+
+        egui::Frame::NONE
+            .fill(CARD_FILL)
+            .corner_radius(egui::CornerRadius::same(10))
+            .stroke(egui::Stroke::new(0.5, BORDER))
+            .inner_margin(egui::Margin::symmetric(12, 10))
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+
+                let total: u64 = self.offloaded.iter().map(|(o, _)| o.size).sum();
+                let available: Vec<(PathBuf, u64)> = self
+                    .offloaded
+                    .iter()
+                    .filter(|(_, connected)| *connected)
+                    .map(|(o, _)| (o.original.clone(), o.size))
+                    .collect();
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new("Offloaded Files")
+                            .size(12.0)
+                            .strong()
+                            .color(TEXT_PRIMARY),
+                    );
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} files, {} kept on other volumes",
+                            self.offloaded.len(),
+                            utils::format_size(total)
+                        ))
+                        .size(11.0)
+                        .color(TEXT_SECONDARY),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let all = ui
+                            .add_enabled(idle && !available.is_empty(), small_btn("Bring All Back"))
+                            .on_hover_text("Copy every file on a connected volume back in place of its link");
+                        if all.clicked() {
+                            self.pending_bring_back = available.clone();
+                        }
+                    });
+                });
+                ui.add_space(6.0);
+
+                for (record, connected) in &self.offloaded {
+                    let connected = *connected;
+                    let volume = record
+                        .copy
+                        .strip_prefix(utils::system_path("/Volumes"))
+                        .ok()
+                        .and_then(|rel| rel.components().next())
+                        .map(|c| c.as_os_str().to_string_lossy().to_string())
+                        .unwrap_or_else(|| utils::display_path(&record.copy));
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(utils::display_path(&record.original))
+                                .size(11.0)
+                                .color(TEXT_PRIMARY),
+                        );
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let back = ui
+                                .add_enabled(idle && connected, small_btn("Bring Back"))
+                                .on_disabled_hover_text(format!("Connect {volume} to bring this file back"));
+                            if back.clicked() {
+                                self.pending_bring_back = vec![(record.original.clone(), record.size)];
+                            }
+                            let (status, color) = if connected {
+                                (format!("on {volume}"), TEXT_SECONDARY)
+                            } else {
+                                (format!("{volume} not connected"), YELLOW)
+                            };
+                            ui.label(egui::RichText::new(status).size(11.0).color(color));
+                            ui.label(
+                                egui::RichText::new(utils::format_size(record.size))
+                                    .size(11.0)
+                                    .color(GREEN),
+                            );
+                        });
+                    });
+                }
+            });
+        ui.add_space(6.0);
+
+        if !self.pending_bring_back.is_empty() && idle {
+            let items = std::mem::take(&mut self.pending_bring_back);
+            self.run_file_action("offloaded".to_string(), items, FileAction::BringBack);
+        }
+    }
+
     fn render_summary(&mut self, ui: &mut egui::Ui) {
         let total: u64 = self
             .categories
@@ -1967,19 +3157,299 @@ impl TidyMacApp {
                     }
                 });
 
-                ui.add_space(4.0);
+                ui.add_space(4.0);
+
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        egui::RichText::new("Built with Rust + egui")
+                            .size(10.0)
+                            .color(egui::Color32::from_rgb(80, 80, 100)),
+                    );
+                });
+            });
+
+        if should_close {
+            self.about_visible = false;
+        }
+    }
+
+    fn render_settings_dialog(&mut self, ctx: &egui::Context) {
+        let mut should_apply = false;
+        let mut should_cancel = false;
+        let mut should_clear_cache = false;
+
+        egui::Area::new(egui::Id::new("settings_overlay"))
+            .fixed_pos(egui::Pos2::ZERO)
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                let screen = ui.ctx().screen_rect();
+                ui.allocate_rect(screen, egui::Sense::click());
+                ui.painter()
+                    .rect_filled(screen, 0.0, egui::Color32::from_black_alpha(180));
+            });
+
+        egui::Window::new("")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .fixed_size([380.0, 0.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.add_space(12.0);
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        egui::RichText::new("Settings")
+                            .size(20.0)
+                            .strong()
+                            .color(TEXT_PRIMARY),
+                    );
+                });
+                ui.add_space(10.0);
+
+                ui.label(
+                    egui::RichText::new("Scan Performance")
+                        .size(14.0)
+                        .strong()
+                        .color(TEXT_PRIMARY),
+                );
+                ui.add_space(4.0);
+
+                let draft = &mut self.settings_draft;
+                let max_scans = std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(4)
+                    .max(draft.max_concurrent_scans);
+
+                egui::Frame::NONE
+                    .fill(INSET_FILL)
+                    .corner_radius(egui::CornerRadius::same(6))
+                    .inner_margin(egui::Margin::symmetric(10, 8))
+                    .show(ui, |ui| {
+                        ui.set_min_width(ui.available_width());
+
+                        ui.label(
+                            egui::RichText::new("Concurrent scans")
+                                .size(13.0)
+                                .color(TEXT_PRIMARY),
+                        );
+                        ui.add(egui::Slider::new(
+                            &mut draft.max_concurrent_scans,
+                            1..=max_scans,
+                        ));
+                        ui.add_space(6.0);
+
+                        ui.checkbox(
+                            &mut draft.background_io,
+                            egui::RichText::new("Low-priority background I/O").size(13.0),
+                        )
+                        .on_hover_text("Run scans, cleaning and shredding at background priority");
+                        ui.add_space(6.0);
+
+                        let mut limited = draft.max_bytes_per_sec.is_some();
+                        let mut mb_per_sec = draft
+                            .max_bytes_per_sec
+                            .map(|b| b / 1_048_576)
+                            .unwrap_or(50);
+                        ui.horizontal(|ui| {
+                            ui.checkbox(
+                                &mut limited,
+                                egui::RichText::new("Limit hashing & shredding to").size(13.0),
+                            );
+                            ui.add_enabled(
+                                limited,
+                                egui::DragValue::new(&mut mb_per_sec)
+                                    .range(1..=4096)
+                                    .suffix(" MB/s"),
+                            );
+                        });
+                        draft.max_bytes_per_sec = limited.then_some(mb_per_sec.max(1) * 1_048_576);
+                    });
+
+                ui.add_space(12.0);
+                ui.label(
+                    egui::RichText::new("Duplicate Files")
+                        .size(14.0)
+                        .strong()
+                        .color(TEXT_PRIMARY),
+                );
+                ui.add_space(4.0);
+
+                egui::Frame::NONE
+                    .fill(INSET_FILL)
+                    .corner_radius(egui::CornerRadius::same(6))
+                    .inner_margin(egui::Margin::symmetric(10, 8))
+                    .show(ui, |ui| {
+                        ui.set_min_width(ui.available_width());
+
+                        let keep = &mut self.keep_draft;
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("Keep").size(13.0).color(TEXT_PRIMARY));
+                            egui::ComboBox::from_id_salt("keep_policy")
+                                .selected_text(keep.policy.label())
+                                .show_ui(ui, |ui| {
+                                    for policy in KeepPolicy::ALL {
+                                        ui.selectable_value(&mut keep.policy, policy, policy.label());
+                                    }
+                                });
+                        });
+
+                        if keep.policy == KeepPolicy::PreferredFolder {
+                            ui.add_space(6.0);
+                            ui.label(
+                                egui::RichText::new("Preferred folders, first wins (one per line)")
+                                    .size(12.0)
+                                    .color(TEXT_SECONDARY),
+                            );
+                            ui.add(
+                                egui::TextEdit::multiline(&mut self.preferred_text)
+                                    .desired_rows(3)
+                                    .desired_width(f32::INFINITY),
+                            );
+                        }
+
+                        ui.add_space(6.0);
+                        ui.label(
+                            egui::RichText::new("Never delete copies in (one per line)")
+                                .size(12.0)
+                                .color(TEXT_SECONDARY),
+                        );
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.protected_text)
+                                .desired_rows(2)
+                                .desired_width(f32::INFINITY)
+                                .hint_text("~/Pictures/Originals"),
+                        );
+
+                        ui.add_space(6.0);
+                        ui.label(
+                            egui::RichText::new("Similar images: how different a match may be")
+                                .size(12.0)
+                                .color(TEXT_SECONDARY),
+                        )
+                        .on_hover_text(
+                            "Bits of the 64-bit perceptual hash that may differ. \
+                             0 matches only visually identical images; higher values \
+                             also match heavier edits, and unrelated images above ~12",
+                        );
+                        ui.add(egui::Slider::new(
+                            &mut self.similar_draft,
+                            0..=categories::MAX_MAX_DISTANCE,
+                        ));
+
+                        ui.add_space(6.0);
+                        ui.label(
+                            egui::RichText::new("Build artifacts: offer projects untouched for")
+                                .size(12.0)
+                                .color(TEXT_SECONDARY),
+                        )
+                        .on_hover_text(
+                            "Days since any source file in the project changed (build output \
+                             and git commits aside) before its target, node_modules, .venv, \
+                             build or Pods folders are offered",
+                        );
+                        ui.add(
+                            egui::Slider::new(
+                                &mut self.stale_days_draft,
+                                categories::MIN_STALE_DAYS..=categories::MAX_STALE_DAYS,
+                            )
+                            .logarithmic(true)
+                            .suffix(" days"),
+                        );
+
+                        ui.add_space(6.0);
+                        ui.label(
+                            egui::RichText::new("Xcode DerivedData: select projects not built for")
+                                .size(12.0)
+                                .color(TEXT_SECONDARY),
+                        )
+                        .on_hover_text(
+                            "DerivedData of projects built more recently is listed but left \
+                             unselected, since removing it forces a full rebuild",
+                        );
+                        ui.add(
+                            egui::Slider::new(
+                                &mut self.derived_days_draft,
+                                categories::MIN_STALE_DAYS..=categories::MAX_STALE_DAYS,
+                            )
+                            .logarithmic(true)
+                            .suffix(" days"),
+                        );
+
+                        ui.add_space(6.0);
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "Hash cache: {}",
+                                    utils::format_size(hash_cache::disk_size())
+                                ))
+                                .size(12.0)
+                                .color(TEXT_SECONDARY),
+                            )
+                            .on_hover_text("Hashes of unchanged files are reused by later scans");
+                            let clear_btn = egui::Button::new(
+                                egui::RichText::new("Clear")
+                                    .size(11.0)
+                                    .color(egui::Color32::from_rgb(160, 160, 180)),
+                            )
+                            .corner_radius(egui::CornerRadius::same(4))
+                            .min_size(egui::vec2(50.0, 20.0));
+                            if ui.add(clear_btn).clicked() {
+                                should_clear_cache = true;
+                            }
+                        });
+                    });
+
+                ui.add_space(14.0);
 
-                ui.vertical_centered(|ui| {
-                    ui.label(
-                        egui::RichText::new("Built with Rust + egui")
-                            .size(10.0)
-                            .color(egui::Color32::from_rgb(80, 80, 100)),
-                    );
+                ui.columns(2, |cols| {
+                    cols[0].vertical_centered(|ui| {
+                        let btn = egui::Button::new(
+                            egui::RichText::new("Cancel")
+                                .size(14.0)
+                                .color(egui::Color32::from_rgb(180, 180, 200)),
+                        )
+                        .corner_radius(egui::CornerRadius::same(8))
+                        .min_size(egui::vec2(150.0, 36.0));
+                        if ui.add(btn).clicked() {
+                            should_cancel = true;
+                        }
+                    });
+                    cols[1].vertical_centered(|ui| {
+                        let btn = egui::Button::new(
+                            egui::RichText::new("Apply")
+                                .size(14.0)
+                                .strong()
+                                .color(egui::Color32::WHITE),
+                        )
+                        .fill(egui::Color32::from_rgb(45, 120, 200))
+                        .corner_radius(egui::CornerRadius::same(8))
+                        .min_size(egui::vec2(150.0, 36.0));
+                        if ui.add(btn).clicked() {
+                            should_apply = true;
+                        }
+                    });
                 });
+                ui.add_space(10.0);
             });
 
-        if should_close {
-            self.about_visible = false;
+        if should_apply {
+            throttle::set_limits(self.settings_draft);
+            self.keep_draft.preferred_folders = Self::text_to_paths(&self.preferred_text);
+            self.keep_draft.protected_roots = Self::text_to_paths(&self.protected_text);
+            categories::set_keep_rule(self.keep_draft.clone());
+            categories::set_similarity_distance(self.similar_draft);
+            categories::set_stale_project_days(self.stale_days_draft);
+            categories::set_derived_data_days(self.derived_days_draft);
+            self.settings_visible = false;
+        }
+        if should_cancel {
+            self.settings_visible = false;
+        }
+        if should_clear_cache {
+            if let Err(e) = hash_cache::clear() {
+                self.errors.push(format!("Cannot clear hash cache: {e}"));
+            }
         }
     }
 
@@ -2524,7 +3994,7 @@ impl TidyMacApp {
 
                                     // Label
                                     ui.label(
-                                        egui::RichText::new(format!("{}", label))
+                                        egui::RichText::new(label.to_string())
                                             .size(11.0)
                                             .color(TEXT_SECONDARY),
                                     );
@@ -2616,6 +4086,403 @@ impl TidyMacApp {
         (self.sys_info.used_memory(), self.sys_info.total_memory())
     }
 
+    /// Measure `dir` in the background. Returns false if the app is busy.
+    fn start_usage_measure(&mut self, dir: PathBuf) -> bool {
+        if self.phase != AppPhase::Idle {
+            return false;
+        }
+        self.phase = AppPhase::Scanning;
+        self.progress_label = format!("Measuring {}...", utils::display_path(&dir));
+        self.usage_measuring = Some(dir.clone());
+        let operation = progress::begin(WorkKind::Scan, 1, 0);
+
+        let (tx, rx) = mpsc::channel::<BgMessage>();
+        self.receiver = Some(rx);
+
+        std::thread::spawn(move || {
+            let _operation = operation;
+            throttle::apply_thread_priority();
+            let listings = disk_usage::measure(&dir);
+            progress::item_done();
+            let _ = tx.send(BgMessage::UsageComplete(dir, listings));
+        });
+        true
+    }
+
+    /// Show `dir` in the explorer, measuring it first if it isn't cached.
+    /// Nothing changes while another operation keeps it from being measured.
+    fn open_usage_dir(&mut self, dir: PathBuf) {
+        if self.usage_cache.get(&dir).is_some() || self.start_usage_measure(dir.clone()) {
+            self.usage_dir = dir;
+        }
+    }
+
+    fn render_disk_usage_view(&mut self, ui: &mut egui::Ui) {
+        let mut open: Option<PathBuf> = None;
+        let mut remeasure = false;
+
+        // ── Header card ──
+        ui.add_space(6.0);
+        egui::Frame::NONE
+            .fill(CARD_FILL)
+            .corner_radius(egui::CornerRadius::same(10))
+            .stroke(egui::Stroke::new(0.5, BORDER))
+            .inner_margin(egui::Margin::symmetric(14, 12))
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+
+                ui.horizontal(|ui| {
+                    let back_btn = egui::Button::new(
+                        egui::RichText::new("<  Back")
+                            .size(12.0)
+                            .color(ACCENT),
+                    )
+                    .corner_radius(egui::CornerRadius::same(6))
+                    .min_size(egui::vec2(70.0, 28.0));
+                    if ui.add(back_btn).clicked() {
+                        self.view_mode = ViewMode::Main;
+                        self.view_alpha = 0.0;
+                    }
+
+                    ui.add_space(10.0);
+
+                    let badge_size = 32.0;
+                    let (badge_rect, _) = ui.allocate_exact_size(
+                        egui::vec2(badge_size, badge_size),
+                        egui::Sense::hover(),
+                    );
+                    let painter = ui.painter();
+                    painter.rect_filled(badge_rect, 8.0, egui::Color32::from_rgb(50, 110, 90));
+                    painter.text(
+                        badge_rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "D",
+                        egui::FontId::proportional(16.0),
+                        egui::Color32::WHITE,
+                    );
+
+                    ui.add_space(8.0);
+
+                    ui.vertical(|ui| {
+                        ui.label(
+                            egui::RichText::new("Disk Usage Explorer")
+                                .size(18.0)
+                                .strong()
+                                .color(TITLE_BLUE),
+                        );
+                        ui.label(
+                            egui::RichText::new("Drill into any folder to see what takes the space")
+                                .size(11.0)
+                                .color(TEXT_SECONDARY),
+                        );
+                    });
+                });
+            });
+
+        ui.add_space(6.0);
+
+        // ── Roots, sorting and view options ──
+        let small_btn = |text: String| {
+            egui::Button::new(
+                egui::RichText::new(text)
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(160, 160, 180)),
+            )
+            .corner_radius(egui::CornerRadius::same(4))
+            .min_size(egui::vec2(50.0, 22.0))
+        };
+        let idle = self.phase == AppPhase::Idle;
+        ui.horizontal(|ui| {
+            let mut roots = vec![
+                ("Home".to_string(), utils::home_dir()),
+                ("Startup Disk".to_string(), PathBuf::from("/")),
+            ];
+            for volume in &self.volumes {
+                let name = volume.file_name().unwrap_or_default().to_string_lossy().to_string();
+                roots.push((name, volume.clone()));
+            }
+            for (name, path) in roots {
+                if ui.add(small_btn(name)).clicked() {
+                    open = Some(path);
+                }
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.checkbox(&mut self.usage_treemap, "Treemap");
+                for mode in SortMode::ALL.iter().rev() {
+                    ui.selectable_value(&mut self.usage_sort, *mode, mode.label());
+                }
+                ui.label(egui::RichText::new("Sort:").size(11.0).color(TEXT_SECONDARY));
+            });
+        });
+
+        // ── Breadcrumbs ──
+        ui.add_space(4.0);
+        ui.horizontal_wrapped(|ui| {
+            let up = self.usage_dir.parent().map(Path::to_path_buf);
+            if ui.add_enabled(up.is_some(), small_btn("\u{2191} Up".to_string())).clicked() {
+                open = up;
+            }
+            ui.add_space(4.0);
+            let crumbs: Vec<&Path> = self.usage_dir.ancestors().collect();
+            for (i, crumb) in crumbs.iter().rev().enumerate() {
+                let name = match crumb.file_name() {
+                    Some(n) => n.to_string_lossy().to_string(),
+                    None => "/".to_string(),
+                };
+                if i > 1 {
+                    ui.label(egui::RichText::new("/").size(12.0).color(TEXT_SECONDARY));
+                }
+                if ui
+                    .selectable_label(false, egui::RichText::new(name).size(12.0).color(TEXT_PRIMARY))
+                    .clicked()
+                {
+                    open = Some(crumb.to_path_buf());
+                }
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .add_enabled(idle, small_btn("Refresh".to_string()))
+                    .on_hover_text("Measure this folder again")
+                    .clicked()
+                {
+                    remeasure = true;
+                }
+            });
+        });
+        ui.add_space(6.0);
+
+        let available = ui.available_height();
+        egui::Frame::NONE
+            .fill(egui::Color32::from_rgb(22, 22, 32))
+            .corner_radius(egui::CornerRadius::same(10))
+            .stroke(egui::Stroke::new(0.5, BORDER))
+            .inner_margin(egui::Margin::symmetric(10, 8))
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+
+                if let Some(ref dir) = self.usage_measuring {
+                    let snap = progress::snapshot();
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "Measuring {} \u{2014} {} items, {:.0}s",
+                                utils::display_path(dir),
+                                snap.files_visited,
+                                snap.elapsed.as_secs_f32(),
+                            ))
+                            .size(12.0)
+                            .color(TEXT_SECONDARY),
+                        );
+                    });
+                    return;
+                }
+
+                let Some(listing) = self.usage_cache.get(&self.usage_dir) else {
+                    ui.label(
+                        egui::RichText::new("Not measured yet. Press Refresh to measure this folder.")
+                            .italics()
+                            .size(12.0)
+                            .color(TEXT_SECONDARY),
+                    );
+                    return;
+                };
+
+                // Summary line
+                let age = listing.measured.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+                let age_text = if age < 60 {
+                    "just now".to_string()
+                } else {
+                    format!("{} min ago", age / 60)
+                };
+                ui.horizontal(|ui| {
+                    let lower_bound = if listing.incomplete { "\u{2265} " } else { "" };
+                    ui.label(
+                        egui::RichText::new(format!("{lower_bound}{}", utils::format_size(listing.size)))
+                            .size(15.0)
+                            .strong()
+                            .color(GREEN),
+                    );
+                    ui.label(
+                        egui::RichText::new(format!("{} items \u{00B7} measured {age_text}", listing.items))
+                            .size(11.0)
+                            .color(TEXT_SECONDARY),
+                    );
+                    if listing.incomplete {
+                        ui.label(
+                            egui::RichText::new("some folders could not be read")
+                                .size(11.0)
+                                .color(YELLOW),
+                        );
+                    }
+                });
+                ui.add_space(4.0);
+
+                // Treemap of the biggest entries; click a folder to open it
+                if self.usage_treemap && listing.size > 0 {
+                    let mut by_size: Vec<&disk_usage::Item> = listing.entries.iter().collect();
+                    by_size.sort_by_key(|e| std::cmp::Reverse(e.size));
+                    let (rect, resp) = ui.allocate_exact_size(
+                        egui::vec2(ui.available_width(), 200.0),
+                        egui::Sense::click(),
+                    );
+                    let sizes: Vec<u64> = by_size.iter().map(|e| e.size).collect();
+                    let tiles = disk_usage::treemap(
+                        &sizes,
+                        disk_usage::Tile { x: rect.min.x, y: rect.min.y, w: rect.width(), h: rect.height() },
+                    );
+                    let painter = ui.painter_at(rect);
+                    let hover = resp.hover_pos();
+                    for (entry, tile) in by_size.iter().zip(&tiles) {
+                        if tile.w < 1.0 || tile.h < 1.0 {
+                            continue;
+                        }
+                        let tile_rect = egui::Rect::from_min_size(
+                            egui::pos2(tile.x, tile.y),
+                            egui::vec2(tile.w, tile.h),
+                        );
+                        let name = entry.name.to_string_lossy();
+                        let hue = (name.bytes().fold(0u32, |acc, b| acc.wrapping_add(b as u32)) % 360) as f32;
+                        let hovered = hover.is_some_and(|p| tile_rect.contains(p));
+                        let fill = if entry.is_dir {
+                            hsv_to_rgb(hue, 0.45, if hovered { 0.6 } else { 0.45 })
+                        } else {
+                            hsv_to_rgb(hue, 0.15, if hovered { 0.45 } else { 0.3 })
+                        };
+                        painter.rect_filled(tile_rect.shrink(1.0), 3.0, fill);
+                        if tile.w > 60.0 && tile.h > 18.0 {
+                            painter.text(
+                                tile_rect.left_top() + egui::vec2(5.0, 4.0),
+                                egui::Align2::LEFT_TOP,
+                                format!("{name} {}", utils::format_size(entry.size)),
+                                egui::FontId::proportional(10.0),
+                                TEXT_PRIMARY,
+                            );
+                        }
+                        if hovered {
+                            if resp.clicked() && entry.is_dir {
+                                open = Some(self.usage_dir.join(&entry.name));
+                            }
+                            resp.clone().on_hover_text(format!("{name} \u{2014} {}", utils::format_size(entry.size)));
+                        }
+                    }
+                    ui.add_space(6.0);
+                }
+
+                // Listing rows
+                let mut entries = listing.entries.clone();
+                disk_usage::sort(&mut entries, self.usage_sort);
+                let max = entries.iter().map(|e| e.size).max().unwrap_or(0).max(1);
+                let total = listing.size.max(1);
+                egui::ScrollArea::vertical()
+                    .max_height((available - 60.0).max(100.0))
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for entry in &entries {
+                            let name = entry.name.to_string_lossy();
+                            ui.horizontal(|ui| {
+                                ui.add_sized(
+                                    [70.0, 18.0],
+                                    egui::Label::new(
+                                        egui::RichText::new(utils::format_size(entry.size))
+                                            .size(12.0)
+                                            .color(GREEN),
+                                    ),
+                                );
+                                ui.add_sized(
+                                    [44.0, 18.0],
+                                    egui::Label::new(
+                                        egui::RichText::new(format!(
+                                            "{:.1}%",
+                                            entry.size as f64 * 100.0 / total as f64
+                                        ))
+                                        .size(11.0)
+                                        .color(TEXT_SECONDARY),
+                                    ),
+                                );
+
+                                // Share bar, relative to the biggest entry
+                                let (bar, _) = ui.allocate_exact_size(egui::vec2(90.0, 8.0), egui::Sense::hover());
+                                ui.painter().rect_filled(bar, 3.0, egui::Color32::from_rgb(35, 35, 50));
+                                let frac = entry.size as f32 / max as f32;
+                                if frac > 0.0 {
+                                    let filled = egui::Rect::from_min_size(
+                                        bar.min,
+                                        egui::vec2((bar.width() * frac).max(2.0), bar.height()),
+                                    );
+                                    ui.painter().rect_filled(filled, 3.0, ACCENT);
+                                }
+                                ui.add_space(6.0);
+
+                                if entry.is_dir {
+                                    if ui
+                                        .selectable_label(
+                                            false,
+                                            egui::RichText::new(format!("{name}/"))
+                                                .size(12.0)
+                                                .strong()
+                                                .color(TEXT_PRIMARY),
+                                        )
+                                        .clicked()
+                                    {
+                                        open = Some(self.usage_dir.join(&entry.name));
+                                    }
+                                } else {
+                                    ui.label(egui::RichText::new(name.as_ref()).size(12.0).color(TEXT_PRIMARY));
+                                }
+
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    if ui.add(small_btn("Reveal".to_string())).on_hover_text("Show in Finder").clicked() {
+                                        let _ = std::process::Command::new("open")
+                                            .arg("-R")
+                                            .arg(self.usage_dir.join(&entry.name))
+                                            .spawn();
+                                    }
+                                    if entry.is_dir {
+                                        ui.label(
+                                            egui::RichText::new(format!("{} items", entry.items))
+                                                .size(11.0)
+                                                .color(TEXT_SECONDARY),
+                                        );
+                                    }
+                                });
+                            });
+                        }
+                        if listing.omitted > 0 {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "\u{2026} and {} smaller items, {}",
+                                    listing.omitted,
+                                    utils::format_size(listing.omitted_size)
+                                ))
+                                .italics()
+                                .size(12.0)
+                                .color(TEXT_SECONDARY),
+                            );
+                        }
+                        if entries.is_empty() {
+                            ui.label(
+                                egui::RichText::new("Empty folder.")
+                                    .italics()
+                                    .size(12.0)
+                                    .color(TEXT_SECONDARY),
+                            );
+                        }
+                    });
+            });
+
+        if remeasure {
+            let dir = self.usage_dir.clone();
+            self.start_usage_measure(dir);
+        } else if let Some(dir) = open {
+            if self.phase == AppPhase::Idle || self.usage_cache.get(&dir).is_some() {
+                self.open_usage_dir(dir);
+            }
+        }
+    }
+
     fn start_ram_optimize(&mut self) {
         let (used, total) = self.get_memory_info();
         self.ram_before = Some((used, total));
@@ -2914,6 +4781,25 @@ impl TidyMacApp {
         ui.add_space(6.0);
     }
 
+    fn paths_to_text(paths: &[PathBuf]) -> String {
+        paths
+            .iter()
+            .map(|p| utils::display_path(p))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn text_to_paths(text: &str) -> Vec<PathBuf> {
+        text.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|l| match l.strip_prefix("~/") {
+                Some(rest) => utils::home_dir().join(rest),
+                None => PathBuf::from(l),
+            })
+            .collect()
+    }
+
     fn format_rate(bytes_per_sec: f64) -> String {
         if bytes_per_sec < 1024.0 {
             format!("{:.0} B/s", bytes_per_sec)
@@ -2962,17 +4848,23 @@ impl TidyMacApp {
         self.cleaned_bytes = 0;
 
         let files = std::mem::take(&mut self.dropped_files);
+        let operation = progress::begin(WorkKind::Transfer, files.len() as u64, 0);
 
         let (tx, rx) = mpsc::channel::<BgMessage>();
         self.receiver = Some(rx);
 
         std::thread::spawn(move || {
+            let _operation = operation;
+            throttle::apply_thread_priority();
+            // Dropped folders are sized here, off the UI thread
+            let total: u64 = files.iter().map(|p| utils::entry_size(p)).sum();
+            progress::expect_bytes(total * shredder::PASSES as u64);
             for path in &files {
                 let tx_ref = &tx;
                 let mut progress_fn = |msg: &str| {
                     let _ = tx_ref.send(BgMessage::Progress(msg.to_string()));
                 };
-                match crate::shredder::shred_file(path, &mut progress_fn) {
+                match shredder::shred_file(path, &mut progress_fn) {
                     Ok(freed) => {
                         let _ = tx.send(BgMessage::DeletedFile(
                             "drop-shred".to_string(),
@@ -2988,6 +4880,7 @@ impl TidyMacApp {
                         ));
                     }
                 }
+                progress::item_done();
             }
             let _ = tx.send(BgMessage::AllShredsComplete);
         });
@@ -3226,6 +5119,10 @@ impl eframe::App for TidyMacApp {
             self.render_about_dialog(ctx);
         }
 
+        if self.settings_visible {
+            self.render_settings_dialog(ctx);
+        }
+
         egui::CentralPanel::default()
             .frame(
                 egui::Frame::central_panel(&ctx.style())
@@ -3260,6 +5157,8 @@ impl eframe::App for TidyMacApp {
                                         .auto_shrink([false, false])
                                         .show(ui, |ui| {
                                             self.render_scan_dashboard(ui);
+                                            self.render_type_breakdown(ui);
+                                            self.render_offloaded(ui);
                                             self.render_category_list(ui);
                                             self.render_summary(ui);
                                             self.render_errors(ui);
@@ -3270,6 +5169,9 @@ impl eframe::App for TidyMacApp {
                     ViewMode::Analyzer => {
                         self.render_analyzer_view(ui);
                     }
+                    ViewMode::DiskUsage => {
+                        self.render_disk_usage_view(ui);
+                    }
                 }
             });
     }
@@ -0,0 +1,394 @@
+use crate::hash_cache::FileKey;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const BLOCK: usize = 512;
+const CHUNK_SIZE: usize = 1_048_576;
+
+/// zstd level: good ratio while still streaming at disk speed.
+const ZSTD_LEVEL: i32 = 9;
+
+/// Largest size an ustar header can hold (11 octal digits).
+const USTAR_MAX_SIZE: u64 = 0o77777777777;
+
+/// A file stored in an archive.
+pub struct Archived {
+    pub path: PathBuf,
+    pub size: u64,
+    mtime: i64,
+    hash: blake3::Hash,
+    /// The file as packed; any later write or replacement changes it.
+    key: FileKey,
+}
+
+/// Result of [`archive_files`]: what was stored, and where.
+pub struct ArchiveOutcome {
+    pub archive: PathBuf,
+    pub files: Vec<Archived>,
+}
+
+/// Pack `files` into a new `.tar.zst` in `dest_dir` with a manifest beside
+/// it, then read the archive back and check every file against the hash
+/// taken while packing. Nothing is removed here; on any error no archive is
+/// left behind.
+pub fn archive_files(files: &[PathBuf], dest_dir: &Path, title: &str) -> io::Result<ArchiveOutcome> {
+    fs::create_dir_all(dest_dir)?;
+    let base = unique_base(dest_dir, &format!("{title} {}", crate::utils::format_date(SystemTime::now())));
+    let archive = base.with_extension("tar.zst");
+    let manifest = manifest_for(&archive);
+    let partial = base.with_extension("tar.zst.partial");
+
+    let result = write_archive(files, &partial).and_then(|stored| {
+        verify_archive(&partial, &stored)?;
+        write_manifest(&manifest, &archive, &stored)?;
+        fs::rename(&partial, &archive)?;
+        Ok(stored)
+    });
+    match result {
+        Ok(stored) => Ok(ArchiveOutcome {
+            archive,
+            files: stored,
+        }),
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            let _ = fs::remove_file(&manifest);
+            Err(e)
+        }
+    }
+}
+
+/// "X.manifest.txt" beside "X.tar.zst".
+fn manifest_for(archive: &Path) -> PathBuf {
+    archive.with_extension("").with_extension("manifest.txt")
+}
+
+/// Remove an archived original, unless it changed after it was packed.
+pub fn remove_original(file: &Archived) -> io::Result<u64> {
+    let meta = fs::symlink_metadata(&file.path)?;
+    if FileKey::from_metadata(&meta) != file.key {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "changed after it was archived, kept",
+        ));
+    }
+    fs::remove_file(&file.path)?;
+    Ok(file.size)
+}
+
+/// Name inside the archive: relative to home when possible.
+fn entry_name(path: &Path) -> String {
+    let home = crate::utils::home_dir();
+    let rel = path.strip_prefix(&home).unwrap_or(path);
+    rel.to_string_lossy().trim_start_matches('/').to_string()
+}
+
+fn write_archive(files: &[PathBuf], dest: &Path) -> io::Result<Vec<Archived>> {
+    let out = BufWriter::new(File::create(dest)?);
+    let mut zst = zstd::Encoder::new(out, ZSTD_LEVEL)?;
+    let mut stored = Vec::with_capacity(files.len());
+    let mut buf = vec![0u8; CHUNK_SIZE];
+
+    for path in files {
+        let mut file = File::open(path)?;
+        let meta = file.metadata()?;
+        if !meta.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a regular file", path.display()),
+            ));
+        }
+        let name = entry_name(path);
+        write_header(&mut zst, &name, &meta)?;
+
+        let mut hasher = blake3::Hasher::new();
+        let mut written = 0u64;
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            let n = n.min((meta.len() - written) as usize);
+            if n == 0 {
+                break;
+            }
+            zst.write_all(&buf[..n])?;
+            hasher.update(&buf[..n]);
+            written += n as u64;
+            crate::throttle::consume_io(n as u64);
+            crate::progress::bytes_done(n as u64);
+        }
+        if written != meta.len() {
+            return Err(io::Error::other(format!(
+                "{} changed while it was being archived",
+                path.display()
+            )));
+        }
+        zst.write_all(&[0u8; BLOCK][..padding(written)])?;
+
+        stored.push(Archived {
+            path: path.clone(),
+            size: written,
+            mtime: meta.mtime(),
+            hash: hasher.finalize(),
+            key: FileKey::from_metadata(&meta),
+        });
+    }
+
+    // End of archive: two empty blocks
+    zst.write_all(&[0u8; BLOCK * 2])?;
+    let out = zst.finish()?;
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    Ok(stored)
+}
+
+fn padding(size: u64) -> usize {
+    (BLOCK - (size % BLOCK as u64) as usize) % BLOCK
+}
+
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+}
+
+fn header_block(name: &[u8], size: u64, mode: u32, mtime: i64, kind: u8) -> [u8; BLOCK] {
+    let mut h = [0u8; BLOCK];
+    let name = &name[..name.len().min(100)];
+    h[..name.len()].copy_from_slice(name);
+    octal(&mut h[100..108], (mode & 0o7777) as u64);
+    octal(&mut h[108..116], 0);
+    octal(&mut h[116..124], 0);
+    octal(&mut h[124..136], size.min(USTAR_MAX_SIZE));
+    octal(&mut h[136..148], mtime.max(0) as u64);
+    h[156] = kind;
+    h[257..263].copy_from_slice(b"ustar\0");
+    h[263..265].copy_from_slice(b"00");
+
+    // Checksum is computed with its own field as spaces
+    h[148..156].copy_from_slice(b"        ");
+    let sum: u32 = h.iter().map(|&b| b as u32).sum();
+    let digits = format!("{sum:06o}\0 ");
+    h[148..156].copy_from_slice(digits.as_bytes());
+    h
+}
+
+/// One pax record: "<len> key=value\n", where len counts itself.
+fn pax_record(key: &str, value: &str) -> String {
+    let body = format!(" {key}={value}\n");
+    let mut len = body.len() + 1;
+    while len.to_string().len() + body.len() != len {
+        len += 1;
+    }
+    format!("{len}{body}")
+}
+
+fn write_header(out: &mut impl Write, name: &str, meta: &fs::Metadata) -> io::Result<()> {
+    // Long or non-ASCII names and huge files need a pax extended header
+    let mut pax = String::new();
+    if name.len() >= 100 || !name.is_ascii() {
+        pax.push_str(&pax_record("path", name));
+    }
+    if meta.len() > USTAR_MAX_SIZE {
+        pax.push_str(&pax_record("size", &meta.len().to_string()));
+    }
+    if !pax.is_empty() {
+        out.write_all(&header_block(b"PaxHeader", pax.len() as u64, 0o644, meta.mtime(), b'x'))?;
+        out.write_all(pax.as_bytes())?;
+        out.write_all(&[0u8; BLOCK][..padding(pax.len() as u64)])?;
+    }
+    out.write_all(&header_block(name.as_bytes(), meta.len(), meta.mode(), meta.mtime(), b'0'))
+}
+
+fn parse_octal(field: &[u8]) -> u64 {
+    field
+        .iter()
+        .skip_while(|&&b| b == b' ')
+        .take_while(|&&b| (b'0'..=b'7').contains(&b))
+        .fold(0, |acc, &b| acc * 8 + (b - b'0') as u64)
+}
+
+fn parse_pax(data: &[u8]) -> HashMap<String, String> {
+    let text = String::from_utf8_lossy(data);
+    text.lines()
+        .filter_map(|line| {
+            let (_, record) = line.split_once(' ')?;
+            let (key, value) = record.split_once('=')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Decompress the archive and check it holds exactly `stored`, byte for byte.
+fn verify_archive(path: &Path, stored: &[Archived]) -> io::Result<()> {
+    let mut expected: HashMap<String, (u64, blake3::Hash)> = stored
+        .iter()
+        .map(|f| (entry_name(&f.path), (f.size, f.hash)))
+        .collect();
+    let mut input = zstd::Decoder::new(BufReader::new(File::open(path)?))?;
+    let mut block = [0u8; BLOCK];
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut pax: HashMap<String, String> = HashMap::new();
+    let corrupt = |what: &str| io::Error::new(io::ErrorKind::InvalidData, format!("archive check failed: {what}"));
+
+    loop {
+        input.read_exact(&mut block)?;
+        if block.iter().all(|&b| b == 0) {
+            break;
+        }
+        let mut size = parse_octal(&block[124..136]);
+        if let Some(s) = pax.get("size").and_then(|s| s.parse().ok()) {
+            size = s;
+        }
+        match block[156] {
+            b'x' => {
+                let mut data = vec![0u8; size as usize];
+                input.read_exact(&mut data)?;
+                io::copy(&mut (&mut input).take(padding(size) as u64), &mut io::sink())?;
+                pax = parse_pax(&data);
+            }
+            b'0' | 0 => {
+                let name = match pax.remove("path") {
+                    Some(p) => p,
+                    None => {
+                        let end = block[..100].iter().position(|&b| b == 0).unwrap_or(100);
+                        String::from_utf8_lossy(&block[..end]).to_string()
+                    }
+                };
+                pax.clear();
+
+                let mut hasher = blake3::Hasher::new();
+                let mut left = size;
+                while left > 0 {
+                    let n = (left as usize).min(CHUNK_SIZE);
+                    input.read_exact(&mut buf[..n])?;
+                    hasher.update(&buf[..n]);
+                    crate::throttle::consume_io(n as u64);
+                    crate::progress::bytes_done(n as u64);
+                    left -= n as u64;
+                }
+                io::copy(&mut (&mut input).take(padding(size) as u64), &mut io::sink())?;
+
+                match expected.remove(&name) {
+                    Some((s, h)) if s == size && h == hasher.finalize() => {}
+                    Some(_) => return Err(corrupt(&format!("{name} differs"))),
+                    None => return Err(corrupt(&format!("unexpected entry {name}"))),
+                }
+            }
+            _ => return Err(corrupt("unknown entry type")),
+        }
+    }
+
+    match expected.keys().next() {
+        Some(name) => Err(corrupt(&format!("{name} is missing"))),
+        None => Ok(()),
+    }
+}
+
+/// What's in the archive and where each file came from, one per line.
+fn write_manifest(path: &Path, archive: &Path, stored: &[Archived]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "# TidyMac archive manifest")?;
+    writeln!(out, "# archive: {}", archive.display())?;
+    writeln!(out, "# blake3\tsize\tmtime\toriginal path")?;
+    for f in stored {
+        writeln!(out, "{}\t{}\t{}\t{}", f.hash.to_hex(), f.size, f.mtime, f.path.display())?;
+    }
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()
+}
+
+/// "<dir>/<name>", or "<dir>/<name> 2" etc. if an archive by that name exists.
+fn unique_base(dir: &Path, name: &str) -> PathBuf {
+    let taken = |base: &Path| {
+        base.with_extension("tar.zst").exists() || base.with_extension("manifest.txt").exists()
+    };
+    // A placeholder extension for `with_extension` to replace
+    let mut base = dir.join(format!("{name}.archive"));
+    let mut n = 2;
+    while taken(&base) {
+        base = dir.join(format!("{name} {n}.archive"));
+        n += 1;
+    }
+    base
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, Fixture};
+    use std::time::Duration;
+
+    #[test]
+    fn archives_verifies_and_only_then_allows_removal() {
+        let fx = Fixture::new();
+        let a = fx.file_with("Documents/old report.pdf", &test_support::content(1, 300_000));
+        let long = format!("Documents/{}/clip.mov", "nested folder ".repeat(9));
+        let b = fx.file_with(&long, &test_support::content(2, 5000));
+        let dest = fx.path("Archives");
+
+        let outcome = archive_files(&[a.clone(), b.clone()], &dest, "Old Files").unwrap();
+
+        assert!(outcome.archive.exists());
+        assert!(outcome.archive.to_string_lossy().ends_with(".tar.zst"));
+        assert!(!outcome.archive.with_extension("zst.partial").exists());
+        let manifest = fs::read_to_string(manifest_for(&outcome.archive)).unwrap();
+        assert!(manifest.contains(&a.display().to_string()));
+        assert!(manifest.contains(&b.display().to_string()));
+        // Re-reading the finished archive still checks out
+        verify_archive(&outcome.archive, &outcome.files).unwrap();
+
+        assert_eq!(remove_original(&outcome.files[0]).unwrap(), 300_000);
+        assert!(!a.exists());
+
+        // A file edited after packing is kept
+        fs::write(&b, b"edited").unwrap();
+        assert!(remove_original(&outcome.files[1]).is_err());
+        assert!(b.exists());
+
+        // A second archive the same day gets its own name
+        let again = archive_files(&[b], &dest, "Old Files").unwrap();
+        assert_ne!(again.archive, outcome.archive);
+    }
+
+    #[test]
+    fn a_same_size_rewrite_within_the_second_is_kept() {
+        let fx = Fixture::new();
+        let a = fx.file_with("Documents/a.bin", &test_support::content(3, 4096));
+        let second = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let set_mtime = |when| {
+            File::options()
+                .write(true)
+                .open(&a)
+                .and_then(|f| f.set_times(fs::FileTimes::new().set_modified(when)))
+                .unwrap();
+        };
+        set_mtime(second + Duration::from_millis(200));
+        let outcome = archive_files(std::slice::from_ref(&a), &fx.path("Archives"), "Old Files").unwrap();
+
+        fs::write(&a, test_support::content(4, 4096)).unwrap();
+        set_mtime(second + Duration::from_millis(700));
+
+        assert!(remove_original(&outcome.files[0]).is_err());
+        assert!(a.exists());
+    }
+
+    #[test]
+    fn a_failed_archive_leaves_nothing_behind() {
+        let fx = Fixture::new();
+        let a = fx.file("Documents/a.bin", 1000);
+        let dest = fx.path("Archives");
+
+        assert!(archive_files(&[a.clone(), fx.path("Documents/missing.bin")], &dest, "Old Files").is_err());
+
+        assert!(a.exists());
+        assert_eq!(fs::read_dir(&dest).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn pax_records_count_their_own_length() {
+        assert_eq!(pax_record("path", "abc"), "12 path=abc\n");
+        assert_eq!(pax_record("path", &"x".repeat(95)).len(), 105);
+    }
+}
@@ -1,6 +1,5 @@
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
 use crate::utils;
-use std::path::PathBuf;
 
 pub struct AppLogs;
 
@@ -16,7 +15,7 @@ impl Cleaner for AppLogs {
     fn scan(&self) -> ScanResult {
         let log_dirs = vec![
             utils::home_dir().join("Library/Logs"),
-            PathBuf::from("/Library/Logs"),
+            utils::system_path("/Library/Logs"),
         ];
 
         let mut entries = Vec::new();
@@ -34,10 +33,7 @@ impl Cleaner for AppLogs {
                         let path = entry.path();
                         let size = utils::entry_size(&path);
                         total_bytes += size;
-                        entries.push(ScanEntry {
-                            path,
-                            size_bytes: size,
-                        });
+                        entries.push(ScanEntry::new(path, size));
                     }
                 }
                 Err(e) => {
@@ -46,41 +42,14 @@ impl Cleaner for AppLogs {
             }
         }
 
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
 
         ScanResult {
 
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match utils::safe_remove(&entry.path) {
-                Ok(freed) => {
-                    total_freed += freed;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
-    }
 }
@@ -1,4 +1,6 @@
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::offload;
+use crate::progress;
 use crate::utils;
 use walkdir::WalkDir;
 
@@ -15,7 +17,7 @@ const SKIP_DIRS: &[&str] = &[
 pub struct BrokenSymlinks;
 
 fn should_skip(name: &str) -> bool {
-    SKIP_DIRS.iter().any(|&s| name == s)
+    SKIP_DIRS.contains(&name)
 }
 
 impl Cleaner for BrokenSymlinks {
@@ -33,11 +35,13 @@ impl Cleaner for BrokenSymlinks {
         let errors = Vec::new();
 
         let home = utils::home_dir();
+        // Links to offloaded files dangle whenever their volume is unplugged
+        let offloaded = offload::offloaded_paths();
 
         let dirs_to_scan = [
             home.join("Library"),
-            std::path::PathBuf::from("/usr/local/bin"),
-            std::path::PathBuf::from("/usr/local/lib"),
+            utils::system_path("/usr/local/bin"),
+            utils::system_path("/usr/local/lib"),
             home.join("bin"),
         ];
 
@@ -46,7 +50,7 @@ impl Cleaner for BrokenSymlinks {
                 continue;
             }
 
-            let max_depth = if dir.starts_with("/usr/local") { 1 } else { 5 };
+            let max_depth = if dir.starts_with(utils::system_path("/usr/local")) { 1 } else { 5 };
 
             for entry in WalkDir::new(dir)
                 .max_depth(max_depth)
@@ -60,6 +64,7 @@ impl Cleaner for BrokenSymlinks {
                     true
                 })
                 .filter_map(|e| e.ok())
+                .inspect(|_| progress::file_visited())
             {
                 let path = entry.path();
 
@@ -70,7 +75,7 @@ impl Cleaner for BrokenSymlinks {
                     .map(|m| m.file_type().is_symlink())
                     .unwrap_or(false);
 
-                if !is_symlink {
+                if !is_symlink || offloaded.contains(path) {
                     continue;
                 }
 
@@ -91,10 +96,7 @@ impl Cleaner for BrokenSymlinks {
                         .unwrap_or(0);
                     total_bytes += size;
 
-                    entries.push(ScanEntry {
-                        path: path.to_path_buf(),
-                        size_bytes: size,
-                    });
+                    entries.push(ScanEntry::new(path.to_path_buf(), size));
 
                     let _ = target; // target info available if needed for display
                 }
@@ -107,34 +109,7 @@ impl Cleaner for BrokenSymlinks {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match std::fs::remove_file(&entry.path) {
-                Ok(()) => {
-                    total_freed += entry.size_bytes;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
-    }
 }
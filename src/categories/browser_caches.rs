@@ -1,4 +1,5 @@
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::in_use::InUseSnapshot;
 use crate::utils;
 use std::path::PathBuf;
 
@@ -74,6 +75,10 @@ impl Cleaner for BrowserCaches {
         "Browser Caches"
     }
 
+    fn skips_in_use(&self) -> bool {
+        true
+    }
+
     fn scan(&self) -> ScanResult {
         let mut entries = Vec::new();
         let mut total_bytes = 0u64;
@@ -92,48 +97,20 @@ impl Cleaner for BrowserCaches {
             let size = utils::entry_size(&dir);
             if size > 0 {
                 total_bytes += size;
-                entries.push(ScanEntry {
-                    path: dir,
-                    size_bytes: size,
-                });
+                entries.push(ScanEntry::new(dir, size));
             }
         }
 
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        InUseSnapshot::capture().mark_entries(&mut entries);
+
+        entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
 
         ScanResult {
 
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match utils::safe_remove(&entry.path) {
-                Ok(freed) => {
-                    total_freed += freed;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
-    }
 }
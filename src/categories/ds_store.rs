@@ -1,4 +1,5 @@
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::progress;
 use crate::utils;
 use std::ffi::OsStr;
 use std::path::PathBuf;
@@ -70,6 +71,7 @@ impl Cleaner for DsStore {
                 entries,
                 total_bytes,
                 errors,
+                groups: Vec::new(),
             };
         }
 
@@ -85,15 +87,15 @@ impl Cleaner for DsStore {
                 true
             });
 
-        for entry in walker.filter_map(|e| e.ok()) {
+        for entry in walker
+            .filter_map(|e| e.ok())
+            .inspect(|_| progress::file_visited())
+        {
             if entry.file_type().is_file() && entry.file_name() == OsStr::new(".DS_Store") {
                 let path = entry.path().to_path_buf();
                 let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
                 total_bytes += size;
-                entries.push(ScanEntry {
-                    path,
-                    size_bytes: size,
-                });
+                entries.push(ScanEntry::new(path, size));
             }
         }
 
@@ -101,34 +103,7 @@ impl Cleaner for DsStore {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match std::fs::remove_file(&entry.path) {
-                Ok(()) => {
-                    total_freed += entry.size_bytes;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
-    }
 }
@@ -0,0 +1,266 @@
+use super::duplicates::full_hash;
+use crate::hash_cache::HashCache;
+use crate::progress;
+use crate::throttle::HashingPool;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Folders smaller than this aren't worth reporting as a whole.
+const MIN_FOLDER_SIZE: u64 = 1_048_576;
+
+/// A single file is a file duplicate, not a folder duplicate.
+const MIN_FOLDER_FILES: u64 = 2;
+
+/// Identical directory trees: same names, same layout, same file contents.
+pub struct FolderGroup {
+    pub hash: blake3::Hash,
+    /// Total size of one tree.
+    pub size_bytes: u64,
+    pub members: Vec<PathBuf>,
+}
+
+#[derive(Default)]
+struct DirNode {
+    files: Vec<(OsString, u64)>,
+    subdirs: Vec<OsString>,
+    /// False when part of the tree wasn't seen (skipped, unreadable, too
+    /// deep, symlinks): such a folder can't be proven identical to another.
+    complete: bool,
+}
+
+/// Layout signature of a complete folder, with its totals.
+#[derive(Clone, Copy)]
+struct Shape {
+    sig: blake3::Hash,
+    size: u64,
+    files: u64,
+}
+
+/// Directory listing gathered during the duplicate finder's walk.
+pub struct FolderIndex {
+    roots: Vec<PathBuf>,
+    dirs: HashMap<PathBuf, DirNode>,
+}
+
+impl FolderIndex {
+    pub fn new(roots: &[PathBuf]) -> Self {
+        Self {
+            roots: roots.to_vec(),
+            dirs: HashMap::new(),
+        }
+    }
+
+    pub fn add_dir(&mut self, path: &Path) {
+        self.dirs.insert(
+            path.to_path_buf(),
+            DirNode {
+                complete: true,
+                ..DirNode::default()
+            },
+        );
+        if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+            if let Some(node) = self.dirs.get_mut(parent) {
+                node.subdirs.push(name.to_os_string());
+            }
+        }
+    }
+
+    pub fn add_file(&mut self, path: &Path, size: u64) {
+        if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+            if let Some(node) = self.dirs.get_mut(parent) {
+                node.files.push((name.to_os_string(), size));
+            }
+        }
+    }
+
+    /// Record that the listing of `path` (a directory), or of the folder
+    /// containing `path` (anything else), is incomplete.
+    pub fn mark_incomplete(&mut self, path: &Path) {
+        let dir = if self.dirs.contains_key(path) {
+            Some(path)
+        } else {
+            path.parent()
+        };
+        if let Some(node) = dir.and_then(|d| self.dirs.get_mut(d)) {
+            node.complete = false;
+        }
+    }
+
+    /// Deepest folders first, so children are always summarised before parents.
+    fn bottom_up(&self) -> Vec<&PathBuf> {
+        let mut order: Vec<&PathBuf> = self.dirs.keys().collect();
+        order.sort_by_key(|p| std::cmp::Reverse(p.components().count()));
+        order
+    }
+
+    /// Find the outermost identical trees. Candidates are narrowed by layout
+    /// and file sizes first, so only their files are read and hashed.
+    pub fn find_groups(&self, cache: &HashCache, pool: &HashingPool) -> Vec<FolderGroup> {
+        // Layout signature, total size and file count per complete folder
+        let mut shapes: HashMap<&Path, Shape> = HashMap::new();
+        for dir in self.bottom_up() {
+            let node = &self.dirs[dir];
+            if !node.complete {
+                continue;
+            }
+            let mut files: Vec<&(OsString, u64)> = node.files.iter().collect();
+            files.sort();
+            let mut hasher = blake3::Hasher::new();
+            let (mut size, mut count) = (0u64, 0u64);
+            for (name, len) in files {
+                hasher.update(b"f\0");
+                hasher.update(name.as_encoded_bytes());
+                hasher.update(&len.to_le_bytes());
+                size += len;
+                count += 1;
+            }
+            let Some(children) = self.child_summaries(dir, &node.subdirs, &shapes) else {
+                continue;
+            };
+            for (name, child) in children {
+                hasher.update(b"d\0");
+                hasher.update(name.as_encoded_bytes());
+                hasher.update(child.sig.as_bytes());
+                size += child.size;
+                count += child.files;
+            }
+            shapes.insert(
+                dir,
+                Shape {
+                    sig: hasher.finalize(),
+                    size,
+                    files: count,
+                },
+            );
+        }
+
+        let mut by_shape: HashMap<blake3::Hash, Vec<&Path>> = HashMap::new();
+        for (dir, shape) in &shapes {
+            if shape.size >= MIN_FOLDER_SIZE
+                && shape.files >= MIN_FOLDER_FILES
+                && !self.roots.iter().any(|r| r == dir)
+            {
+                by_shape.entry(shape.sig).or_default().push(dir);
+            }
+        }
+        let candidates: HashSet<&Path> = by_shape
+            .into_values()
+            .filter(|dirs| dirs.len() >= 2)
+            .flatten()
+            .collect();
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+
+        // Hash every file under a candidate
+        let in_candidate = |dir: &Path| dir.ancestors().any(|a| candidates.contains(a));
+        let files: Vec<PathBuf> = self
+            .dirs
+            .iter()
+            .filter(|(dir, _)| in_candidate(dir))
+            .flat_map(|(dir, node)| node.files.iter().map(move |(name, _)| dir.join(name)))
+            .collect();
+        progress::expect_bytes(
+            files
+                .iter()
+                .filter_map(|p| p.metadata().ok())
+                .map(|m| m.len())
+                .sum(),
+        );
+        let file_hashes: HashMap<PathBuf, blake3::Hash> = pool.install(|| {
+            files
+                .into_par_iter()
+                .filter_map(|p| full_hash(&p, cache).map(|h| (p, h)))
+                .collect()
+        });
+
+        // Content hash of each folder under a candidate, from its files' hashes
+        let mut contents: HashMap<&Path, blake3::Hash> = HashMap::new();
+        for dir in self.bottom_up() {
+            if !in_candidate(dir) || !shapes.contains_key(dir.as_path()) {
+                continue;
+            }
+            let node = &self.dirs[dir];
+            let mut files: Vec<&OsString> = node.files.iter().map(|(n, _)| n).collect();
+            files.sort();
+            let mut subdirs: Vec<&OsString> = node.subdirs.iter().collect();
+            subdirs.sort();
+
+            let mut hasher = blake3::Hasher::new();
+            let mut readable = true;
+            for name in files {
+                let Some(hash) = file_hashes.get(&dir.join(name)) else {
+                    readable = false;
+                    break;
+                };
+                hasher.update(b"f\0");
+                hasher.update(name.as_encoded_bytes());
+                hasher.update(hash.as_bytes());
+            }
+            for name in subdirs {
+                let Some(hash) = contents.get(dir.join(name).as_path()) else {
+                    readable = false;
+                    break;
+                };
+                hasher.update(b"d\0");
+                hasher.update(name.as_encoded_bytes());
+                hasher.update(hash.as_bytes());
+            }
+            if readable {
+                contents.insert(dir, hasher.finalize());
+            }
+        }
+
+        let mut by_content: HashMap<blake3::Hash, Vec<&Path>> = HashMap::new();
+        for dir in &candidates {
+            if let Some(hash) = contents.get(dir) {
+                by_content.entry(*hash).or_default().push(dir);
+            }
+        }
+        let matched: Vec<(blake3::Hash, Vec<&Path>)> = by_content
+            .into_iter()
+            .filter(|(_, dirs)| dirs.len() >= 2)
+            .collect();
+
+        // Report only the outermost trees: anything inside a matched folder
+        // goes (or stays) with it, and must never be a group's last copy
+        let matched_dirs: HashSet<&Path> = matched.iter().flat_map(|(_, d)| d.iter().copied()).collect();
+        let nested = |dir: &Path| dir.ancestors().skip(1).any(|a| matched_dirs.contains(a));
+        matched
+            .into_iter()
+            .filter_map(|(hash, dirs)| {
+                let mut members: Vec<PathBuf> = dirs
+                    .into_iter()
+                    .filter(|d| !nested(d))
+                    .map(Path::to_path_buf)
+                    .collect();
+                if members.len() < 2 {
+                    return None;
+                }
+                members.sort();
+                Some(FolderGroup {
+                    hash,
+                    size_bytes: shapes[members[0].as_path()].size,
+                    members,
+                })
+            })
+            .collect()
+    }
+
+    /// Layout summaries of `dir`'s subfolders, or `None` if any is incomplete.
+    fn child_summaries<'a>(
+        &self,
+        dir: &Path,
+        subdirs: &'a [OsString],
+        shapes: &HashMap<&Path, Shape>,
+    ) -> Option<Vec<(&'a OsString, Shape)>> {
+        let mut children: Vec<&OsString> = subdirs.iter().collect();
+        children.sort();
+        children
+            .into_iter()
+            .map(|name| Some((name, *shapes.get(dir.join(name).as_path())?)))
+            .collect()
+    }
+}
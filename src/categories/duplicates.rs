@@ -1,20 +1,34 @@
-use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use super::duplicate_folders::FolderIndex;
+use crate::cleaner::{Cleaner, DuplicateGroup, ScanEntry, ScanResult};
+use crate::hash_cache::{FileKey, HashCache, HashKind};
+use crate::progress;
+use crate::throttle;
 use crate::utils;
 use rayon::prelude::*;
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::io::Read;
-use std::path::PathBuf;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::SystemTime;
 use walkdir::WalkDir;
 
 /// Minimum file size: 1 MB
 const MIN_SIZE: u64 = 1_048_576;
 
-/// Maximum file size for hashing: 500 MB (skip very large files)
-const MAX_SIZE: u64 = 500_000_000;
+/// Above 500 MB, files are pre-screened by sampling instead of a head-only
+/// hash: disk images, videos and VM files often share their first blocks.
+const SAMPLE_THRESHOLD: u64 = 500_000_000;
 
 /// Bytes to read for partial hash (first 4 KB)
 const PARTIAL_READ: usize = 4096;
 
+/// Size of each sampled block (head, middle and tail).
+const SAMPLE_BLOCK: usize = 65536;
+
+/// Read buffer for full hashes.
+const FULL_READ: usize = 1_048_576;
+
 /// Maximum walk depth.
 const MAX_DEPTH: usize = 8;
 
@@ -47,36 +61,274 @@ const SKIP_DIRS: &[&str] = &[
     "Pods",
 ];
 
-pub struct DuplicateFinder;
+/// How the surviving copy of each duplicate group is chosen.
+#[derive(Clone, Copy, PartialEq)]
+pub enum KeepPolicy {
+    Oldest,
+    Newest,
+    ShortestPath,
+    PreferredFolder,
+}
+
+impl KeepPolicy {
+    pub const ALL: [KeepPolicy; 4] = [
+        KeepPolicy::Oldest,
+        KeepPolicy::Newest,
+        KeepPolicy::ShortestPath,
+        KeepPolicy::PreferredFolder,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeepPolicy::Oldest => "Oldest (modification time)",
+            KeepPolicy::Newest => "Newest (modification time)",
+            KeepPolicy::ShortestPath => "Shortest path",
+            KeepPolicy::PreferredFolder => "Preferred folder",
+        }
+    }
+}
+
+/// Which copy of a duplicate group survives, and which copies never go.
+#[derive(Clone, PartialEq)]
+pub struct KeepRule {
+    pub policy: KeepPolicy,
+    /// Folders in order of preference for `PreferredFolder`; earlier wins.
+    pub preferred_folders: Vec<PathBuf>,
+    /// Copies under these folders are never offered for deletion.
+    pub protected_roots: Vec<PathBuf>,
+}
+
+impl Default for KeepRule {
+    fn default() -> Self {
+        let home = utils::home_dir();
+        Self {
+            policy: KeepPolicy::Oldest,
+            preferred_folders: vec![
+                home.join("Pictures"),
+                home.join("Documents"),
+                home.join("Desktop"),
+                home.join("Downloads"),
+            ],
+            protected_roots: Vec::new(),
+        }
+    }
+}
+
+impl KeepRule {
+    fn folder_rank(&self, path: &Path) -> usize {
+        self.preferred_folders
+            .iter()
+            .position(|f| path.starts_with(f))
+            .unwrap_or(self.preferred_folders.len())
+    }
+
+    /// Whether deleting `path` would touch a protected folder: it lies inside
+    /// one, or is a folder containing one.
+    pub fn is_protected(&self, path: &Path) -> bool {
+        self.protected_roots
+            .iter()
+            .any(|root| path.starts_with(root) || root.starts_with(path))
+    }
+
+    /// Pick the copy to keep. Ties fall back to the shortest, then the
+    /// alphabetically first path, so the choice is stable across scans.
+    pub fn choose_original<'a>(&self, group: &[&'a PathBuf]) -> &'a PathBuf {
+        let mtime = |p: &Path| {
+            p.metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+        };
+        group
+            .iter()
+            .copied()
+            .min_by(|a, b| {
+                let primary = match self.policy {
+                    KeepPolicy::Oldest => mtime(a).cmp(&mtime(b)),
+                    KeepPolicy::Newest => mtime(b).cmp(&mtime(a)),
+                    KeepPolicy::ShortestPath => Ordering::Equal,
+                    KeepPolicy::PreferredFolder => self.folder_rank(a).cmp(&self.folder_rank(b)),
+                };
+                primary
+                    .then_with(|| a.as_os_str().len().cmp(&b.as_os_str().len()))
+                    .then_with(|| a.cmp(b))
+            })
+            .expect("duplicate group is never empty")
+    }
+}
+
+/// Rule used by scans started from the UI; `None` means the default.
+static KEEP_RULE: RwLock<Option<KeepRule>> = RwLock::new(None);
 
-fn should_skip_dir(name: &str) -> bool {
+pub fn keep_rule() -> KeepRule {
+    KEEP_RULE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+pub fn set_keep_rule(rule: KeepRule) {
+    *KEEP_RULE.write().unwrap_or_else(|e| e.into_inner()) = Some(rule);
+}
+
+pub struct DuplicateFinder {
+    rule: KeepRule,
+    /// Files larger than this are pre-screened by sampling.
+    sample_threshold: u64,
+}
+
+impl DuplicateFinder {
+    pub fn with_rule(rule: KeepRule) -> Self {
+        Self {
+            rule,
+            sample_threshold: SAMPLE_THRESHOLD,
+        }
+    }
+
+    /// Lower the sampling threshold so tests can use small files.
+    #[cfg(test)]
+    pub fn with_sample_threshold(mut self, bytes: u64) -> Self {
+        self.sample_threshold = bytes;
+        self
+    }
+
+    /// Record identical copies (files or folders of `size` bytes each) as a
+    /// group, keeping the original chosen by the rule. Protected copies are
+    /// left out entirely since they can never be deleted.
+    /// Returns the bytes deleting the other copies would free.
+    fn add_group(
+        &self,
+        hash: blake3::Hash,
+        size: u64,
+        dupes: &[&PathBuf],
+        entries: &mut Vec<ScanEntry>,
+        groups: &mut Vec<DuplicateGroup>,
+    ) -> u64 {
+        let original = self.rule.choose_original(dupes);
+        let members: Vec<PathBuf> = dupes
+            .iter()
+            .filter(|p| **p == original || !self.rule.is_protected(p))
+            .map(|p| p.to_path_buf())
+            .collect();
+        if members.len() < 2 {
+            return 0;
+        }
+
+        let mut freed = 0;
+        for path in &members {
+            let mut entry = ScanEntry::new(path.clone(), size);
+            if path != original {
+                freed += size;
+                entry.duplicate_of = Some(original.clone());
+            }
+            entries.push(entry);
+        }
+        groups.push(DuplicateGroup {
+            hash: hash.to_hex().to_string(),
+            size_bytes: size,
+            members,
+            original: original.clone(),
+        });
+        freed
+    }
+}
+
+pub(super) fn should_skip_dir(name: &str) -> bool {
     let lower = name.to_lowercase();
-    SKIP_DIRS.iter().any(|&skip| name == skip)
+    SKIP_DIRS.contains(&name)
         || SKIP_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
 }
 
+/// Bytes read to pre-screen a file of `size` bytes.
+fn prescreen_bytes(size: u64, sample_threshold: u64) -> u64 {
+    if size > sample_threshold {
+        3 * SAMPLE_BLOCK as u64
+    } else {
+        size.min(PARTIAL_READ as u64)
+    }
+}
+
+/// Cheap first comparison: a head-only hash, or for very large files a
+/// hash of blocks at the head, middle and tail.
+fn prescreen_hash(
+    path: &Path,
+    size: u64,
+    sample_threshold: u64,
+    cache: &HashCache,
+) -> Option<blake3::Hash> {
+    if size > sample_threshold {
+        sampled_hash(path, cache)
+    } else {
+        partial_hash(path, cache)
+    }
+}
+
+/// Compute blake3 hash of the head, middle and tail blocks of a file.
+pub(super) fn sampled_hash(path: &Path, cache: &HashCache) -> Option<blake3::Hash> {
+    let key = FileKey::of(path)?;
+    if let Some(hash) = cache.get(key, HashKind::Sampled) {
+        progress::bytes_done(3 * SAMPLE_BLOCK as u64);
+        return Some(hash);
+    }
+    let size = key.size();
+    let block = (SAMPLE_BLOCK as u64).min(size);
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; block as usize];
+    for offset in [0, (size - block) / 2, size - block] {
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        file.read_exact(&mut buf).ok()?;
+        throttle::consume_io(block);
+        progress::bytes_done(block);
+        hasher.update(&buf);
+    }
+    let hash = hasher.finalize();
+    cache.insert(key, HashKind::Sampled, hash);
+    Some(hash)
+}
+
 /// Compute blake3 hash of the first `n` bytes of a file.
-fn partial_hash(path: &std::path::Path) -> Option<blake3::Hash> {
+fn partial_hash(path: &std::path::Path, cache: &HashCache) -> Option<blake3::Hash> {
+    // Key taken before reading: a write during hashing changes the mtime,
+    // so the result can never be served for the newer contents
+    let key = FileKey::of(path)?;
+    if let Some(hash) = cache.get(key, HashKind::Partial) {
+        progress::bytes_done(key.size().min(PARTIAL_READ as u64));
+        return Some(hash);
+    }
     let mut file = std::fs::File::open(path).ok()?;
     let mut buf = vec![0u8; PARTIAL_READ];
     let bytes_read = file.read(&mut buf).ok()?;
+    throttle::consume_io(bytes_read as u64);
+    progress::bytes_done(bytes_read as u64);
     buf.truncate(bytes_read);
-    Some(blake3::hash(&buf))
+    let hash = blake3::hash(&buf);
+    cache.insert(key, HashKind::Partial, hash);
+    Some(hash)
 }
 
 /// Compute blake3 hash of an entire file.
-fn full_hash(path: &std::path::Path) -> Option<blake3::Hash> {
+pub(super) fn full_hash(path: &std::path::Path, cache: &HashCache) -> Option<blake3::Hash> {
+    let key = FileKey::of(path)?;
+    if let Some(hash) = cache.get(key, HashKind::Full) {
+        progress::bytes_done(key.size());
+        return Some(hash);
+    }
     let mut file = std::fs::File::open(path).ok()?;
     let mut hasher = blake3::Hasher::new();
-    let mut buf = vec![0u8; 65536];
+    let mut buf = vec![0u8; FULL_READ];
     loop {
         let n = file.read(&mut buf).ok()?;
         if n == 0 {
             break;
         }
+        throttle::consume_io(n as u64);
+        progress::bytes_done(n as u64);
         hasher.update(&buf[..n]);
     }
-    Some(hasher.finalize())
+    let hash = hasher.finalize();
+    cache.insert(key, HashKind::Full, hash);
+    Some(hash)
 }
 
 impl Cleaner for DuplicateFinder {
@@ -91,7 +343,7 @@ impl Cleaner for DuplicateFinder {
     fn scan(&self) -> ScanResult {
         let mut entries = Vec::new();
         let mut total_bytes = 0u64;
-        let errors = Vec::new();
+        let mut errors = Vec::new();
 
         let home = utils::home_dir();
         let dirs_to_scan = [
@@ -101,8 +353,11 @@ impl Cleaner for DuplicateFinder {
             home.join("Pictures"),
         ];
 
-        // Pass 1: Group all files by size (single consolidated walk)
+        // Pass 1: Group all files by size (single consolidated walk), and
+        // record the folder layout for whole-folder duplicates
         let mut size_groups: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        let mut folders = FolderIndex::new(&dirs_to_scan);
+        let mut skipped: Vec<PathBuf> = Vec::new();
 
         for dir in &dirs_to_scan {
             if !dir.exists() {
@@ -115,20 +370,44 @@ impl Cleaner for DuplicateFinder {
                 .filter_entry(|e| {
                     if e.file_type().is_dir() {
                         let name = e.file_name().to_string_lossy();
-                        return !should_skip_dir(&name);
+                        if should_skip_dir(&name) {
+                            skipped.push(e.path().to_path_buf());
+                            return false;
+                        }
                     }
                     true
                 })
-                .filter_map(|e| e.ok())
             {
+                let entry = match entry {
+                    Ok(e) => e,
+                    Err(e) => {
+                        if let Some(path) = e.path() {
+                            folders.mark_incomplete(path);
+                        }
+                        continue;
+                    }
+                };
+                if entry.file_type().is_dir() {
+                    folders.add_dir(entry.path());
+                    if entry.depth() == MAX_DEPTH {
+                        folders.mark_incomplete(entry.path());
+                    }
+                    continue;
+                }
                 if !entry.file_type().is_file() {
+                    folders.mark_incomplete(entry.path());
                     continue;
                 }
+                progress::file_visited();
                 let size = match entry.metadata() {
                     Ok(m) => m.len(),
-                    Err(_) => continue,
+                    Err(_) => {
+                        folders.mark_incomplete(entry.path());
+                        continue;
+                    }
                 };
-                if size < MIN_SIZE || size > MAX_SIZE {
+                folders.add_file(entry.path(), size);
+                if size < MIN_SIZE {
                     continue;
                 }
                 size_groups
@@ -137,6 +416,26 @@ impl Cleaner for DuplicateFinder {
                     .push(entry.into_path());
             }
         }
+        for path in &skipped {
+            folders.mark_incomplete(path);
+        }
+
+        // Hash on a bounded pool so a scan doesn't take every core; files
+        // unchanged since an earlier scan reuse their cached hashes
+        let pool = throttle::hashing_pool();
+        let cache = HashCache::open();
+
+        // Whole identical folders come first; their files are not offered
+        // again one by one
+        let folder_groups = folders.find_groups(&cache, &pool);
+        let in_folder_group = |path: &Path| {
+            folder_groups
+                .iter()
+                .any(|g| g.members.iter().any(|m| path.starts_with(m)))
+        };
+        for paths in size_groups.values_mut() {
+            paths.retain(|p| !in_folder_group(p));
+        }
 
         // Only keep groups with 2+ files (potential duplicates)
         let candidate_groups: Vec<(u64, Vec<PathBuf>)> = size_groups
@@ -144,100 +443,101 @@ impl Cleaner for DuplicateFinder {
             .filter(|(_, paths)| paths.len() >= 2)
             .collect();
 
-        // Pass 2: Parallel partial hashing for size-matched groups
-        let partial_results: Vec<(u64, HashMap<blake3::Hash, Vec<PathBuf>>)> = candidate_groups
-            .into_par_iter()
-            .map(|(size, paths)| {
-                let mut partial_groups: HashMap<blake3::Hash, Vec<PathBuf>> = HashMap::new();
-                for path in paths {
-                    if let Some(hash) = partial_hash(&path) {
-                        partial_groups.entry(hash).or_default().push(path);
+        // Announce the hashing work up front so progress tracks bytes, not categories
+        progress::expect_bytes(
+            candidate_groups
+                .iter()
+                .map(|(size, paths)| prescreen_bytes(*size, self.sample_threshold) * paths.len() as u64)
+                .sum(),
+        );
+
+        // Pass 2: Parallel partial (or sampled) hashing for size-matched groups
+        let partial_results: Vec<(u64, HashMap<blake3::Hash, Vec<PathBuf>>)> = pool.install(|| {
+            candidate_groups
+                .into_par_iter()
+                .map(|(size, paths)| {
+                    let mut partial_groups: HashMap<blake3::Hash, Vec<PathBuf>> = HashMap::new();
+                    for path in paths {
+                        if let Some(hash) = prescreen_hash(&path, size, self.sample_threshold, &cache) {
+                            partial_groups.entry(hash).or_default().push(path);
+                        }
                     }
-                }
-                (size, partial_groups)
-            })
-            .collect();
+                    (size, partial_groups)
+                })
+                .collect()
+        });
 
         // Pass 3: Parallel full hashing for partial-hash matches
         let mut full_hash_tasks: Vec<Vec<PathBuf>> = Vec::new();
         for (_size, partial_groups) in &partial_results {
-            for (_phash, partial_matches) in partial_groups {
+            for partial_matches in partial_groups.values() {
                 if partial_matches.len() >= 2 {
                     full_hash_tasks.push(partial_matches.clone());
                 }
             }
         }
 
-        let dup_groups: Vec<Vec<(PathBuf, blake3::Hash)>> = full_hash_tasks
-            .into_par_iter()
-            .map(|paths| {
-                paths
-                    .into_iter()
-                    .filter_map(|p| {
-                        let hash = full_hash(&p)?;
-                        Some((p, hash))
-                    })
-                    .collect()
-            })
-            .collect();
+        progress::expect_bytes(
+            full_hash_tasks
+                .iter()
+                .flatten()
+                .filter_map(|p| p.metadata().ok())
+                .map(|m| m.len())
+                .sum(),
+        );
 
-        // Collect true duplicates from full hash groups
-        for group in &dup_groups {
-            let mut full_groups: HashMap<blake3::Hash, Vec<&PathBuf>> = HashMap::new();
-            for (path, hash) in group {
-                full_groups.entry(*hash).or_default().push(path);
-            }
+        // One job per file, so two multi-GB candidates stream side by side
+        let hashed: Vec<(PathBuf, blake3::Hash)> = pool.install(|| {
+            full_hash_tasks
+                .into_par_iter()
+                .flatten()
+                .filter_map(|p| {
+                    let hash = full_hash(&p, &cache)?;
+                    Some((p, hash))
+                })
+                .collect()
+        });
 
-            for (_fhash, dupes) in &full_groups {
-                if dupes.len() < 2 {
-                    continue;
-                }
-                // Skip the first file (the "original"), mark the rest
-                for dup_path in &dupes[1..] {
-                    let size = dup_path.metadata().map(|m| m.len()).unwrap_or(0);
-                    total_bytes += size;
-                    entries.push(ScanEntry {
-                        path: dup_path.to_path_buf(),
-                        size_bytes: size,
-                    });
-                }
+        if let Err(e) = cache.save() {
+            errors.push(format!("Cannot save hash cache: {e}"));
+        }
+
+        // Collect true duplicates from full hash groups
+        let mut groups = Vec::new();
+        let mut full_groups: HashMap<blake3::Hash, Vec<&PathBuf>> = HashMap::new();
+        for (path, hash) in &hashed {
+            full_groups.entry(*hash).or_default().push(path);
+        }
+        for (fhash, dupes) in &full_groups {
+            if dupes.len() < 2 {
+                continue;
             }
+            let size = dupes[0].metadata().map(|m| m.len()).unwrap_or(0);
+            total_bytes += self.add_group(*fhash, size, dupes, &mut entries, &mut groups);
+        }
+        for folder in &folder_groups {
+            let dupes: Vec<&PathBuf> = folder.members.iter().collect();
+            total_bytes +=
+                self.add_group(folder.hash, folder.size_bytes, &dupes, &mut entries, &mut groups);
         }
 
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        // Biggest savings first; members of a group stay together
+        groups.sort_by(|a, b| {
+            let saved = |g: &DuplicateGroup| g.size_bytes * (g.members.len() as u64 - 1);
+            saved(b).cmp(&saved(a)).then_with(|| a.original.cmp(&b.original))
+        });
+        let order: HashMap<&Path, usize> = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(i, g)| g.members.iter().map(move |m| (m.as_path(), i)))
+            .collect();
+        entries.sort_by_key(|e| (order[e.path.as_path()], e.duplicate_of.is_some()));
 
         ScanResult {
             entries,
             total_bytes,
             errors,
+            groups,
         }
     }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match utils::safe_remove(&entry.path) {
-                Ok(freed) => {
-                    total_freed += freed;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
-    }
 }
@@ -1,6 +1,7 @@
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::progress;
 use crate::utils;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 /// Top-level user directories that should never be removed even if empty.
@@ -33,7 +34,7 @@ fn is_protected(path: &std::path::Path, home: &std::path::Path) -> bool {
 }
 
 fn should_skip(name: &str) -> bool {
-    SKIP_DIRS.iter().any(|&s| name == s) || name.starts_with('.')
+    SKIP_DIRS.contains(&name) || name.starts_with('.')
 }
 
 /// Check if a directory is empty or only contains .DS_Store files.
@@ -94,6 +95,7 @@ impl Cleaner for EmptyFolders {
                     true
                 })
                 .filter_map(|e| e.ok())
+                .inspect(|_| progress::file_visited())
                 .filter(|e| e.file_type().is_dir())
                 .map(|e| e.path().to_path_buf())
                 .collect();
@@ -107,10 +109,7 @@ impl Cleaner for EmptyFolders {
                     continue;
                 }
                 if is_effectively_empty(&path) {
-                    entries.push(ScanEntry {
-                        path,
-                        size_bytes: 0,
-                    });
+                    entries.push(ScanEntry::new(path, 0));
                 }
             }
         }
@@ -121,37 +120,22 @@ impl Cleaner for EmptyFolders {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
 
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-
-        for entry in result.entries.drain(..) {
-            // Remove .DS_Store inside first if present
-            let ds = entry.path.join(".DS_Store");
-            if ds.exists() {
-                let _ = std::fs::remove_file(&ds);
-            }
-            // Now remove the empty directory
-            match std::fs::remove_dir(&entry.path) {
-                Ok(()) => {
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
+    /// Removes only the folder and a `.DS_Store` in it, so anything saved
+    /// into it since the scan makes the removal fail instead of vanishing.
+    fn remove(&self, path: &Path) -> std::io::Result<u64> {
+        let ds = path.join(".DS_Store");
+        let freed = match ds.symlink_metadata() {
+            Ok(meta) => {
+                std::fs::remove_file(&ds)?;
+                meta.len()
             }
-        }
-
-        result.entries = cleaned_entries;
-        result
+            Err(_) => 0,
+        };
+        std::fs::remove_dir(path)?;
+        Ok(freed)
     }
 }
@@ -0,0 +1,244 @@
+use super::large_files::{should_skip_dir, MAX_DEPTH};
+use crate::progress;
+use crate::utils;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Largest files remembered per kind, for drilling in.
+const LARGEST_PER_KIND: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    Video,
+    Images,
+    Audio,
+    Archives,
+    DiskImages,
+    Documents,
+    SourceCode,
+    Apps,
+    Other,
+}
+
+impl FileKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            FileKind::Video => "Video",
+            FileKind::Images => "Images",
+            FileKind::Audio => "Audio",
+            FileKind::Archives => "Archives",
+            FileKind::DiskImages => "Disk Images",
+            FileKind::Documents => "Documents",
+            FileKind::SourceCode => "Source Code",
+            FileKind::Apps => "Apps",
+            FileKind::Other => "Other",
+        }
+    }
+}
+
+const EXTENSIONS: &[(FileKind, &[&str])] = &[
+    (
+        FileKind::Video,
+        &["mp4", "m4v", "mov", "avi", "mkv", "webm", "wmv", "flv", "mpg", "mpeg", "3gp", "mts", "m2ts"],
+    ),
+    (
+        FileKind::Images,
+        &[
+            "jpg", "jpeg", "png", "gif", "heic", "heif", "webp", "tif", "tiff", "bmp", "raw", "cr2", "cr3",
+            "nef", "arw", "dng", "orf", "raf", "psd", "svg", "ico", "icns",
+        ],
+    ),
+    (
+        FileKind::Audio,
+        &["mp3", "m4a", "aac", "wav", "aif", "aiff", "flac", "ogg", "opus", "wma", "alac", "caf"],
+    ),
+    (
+        FileKind::Archives,
+        &["zip", "tar", "gz", "tgz", "bz2", "tbz", "xz", "txz", "7z", "rar", "zst", "lz4", "xip", "pkg", "jar"],
+    ),
+    (
+        FileKind::DiskImages,
+        &["dmg", "iso", "img", "sparseimage", "vmdk", "vdi", "qcow2", "vhd", "vhdx"],
+    ),
+    (
+        FileKind::Documents,
+        &[
+            "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "pages", "numbers", "key", "txt", "rtf", "md",
+            "odt", "ods", "odp", "epub", "csv",
+        ],
+    ),
+    (
+        FileKind::SourceCode,
+        &[
+            "rs", "c", "h", "cc", "cpp", "hpp", "m", "mm", "swift", "go", "java", "kt", "py", "rb", "js", "ts",
+            "jsx", "tsx", "php", "cs", "sh", "zsh", "html", "css", "scss", "json", "yaml", "yml", "toml",
+            "xml", "sql", "lua", "dart",
+        ],
+    ),
+    (FileKind::Apps, &["dylib", "so", "exe", "msi"]),
+];
+
+/// Folders that macOS shows as a single file, counted whole.
+fn bundle_kind(name: &str) -> Option<FileKind> {
+    let lower = name.to_lowercase();
+    let ext = lower.rsplit_once('.')?.1;
+    match ext {
+        "app" => Some(FileKind::Apps),
+        "photoslibrary" => Some(FileKind::Images),
+        "musiclibrary" => Some(FileKind::Audio),
+        "tvlibrary" | "fcpbundle" | "imovielibrary" => Some(FileKind::Video),
+        "vmwarevm" | "parallels" | "pvm" | "utm" | "sparsebundle" => Some(FileKind::DiskImages),
+        _ => None,
+    }
+}
+
+fn by_extension(path: &Path) -> Option<FileKind> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(_, exts)| exts.contains(&ext.as_str()))
+        .map(|(kind, _)| *kind)
+}
+
+/// Identify a file from its first bytes, for names without a known extension.
+fn by_magic(path: &Path) -> Option<FileKind> {
+    let mut head = [0u8; 16];
+    let mut file = File::open(path).ok()?;
+    let n = file.read(&mut head).ok()?;
+    let head = &head[..n];
+    if n < 4 {
+        return None;
+    }
+
+    let starts = |sig: &[u8]| head.starts_with(sig);
+    let kind = if starts(b"\xFF\xD8\xFF") || starts(b"\x89PNG") || starts(b"GIF8") || starts(b"II*\0") || starts(b"MM\0*") {
+        FileKind::Images
+    } else if n >= 12 && &head[4..8] == b"ftyp" {
+        match &head[8..12] {
+            b"heic" | b"heix" | b"mif1" | b"msf1" | b"avif" => FileKind::Images,
+            b"M4A " | b"M4B " => FileKind::Audio,
+            _ => FileKind::Video,
+        }
+    } else if n >= 12 && starts(b"RIFF") {
+        match &head[8..12] {
+            b"WEBP" => FileKind::Images,
+            b"WAVE" => FileKind::Audio,
+            _ => FileKind::Video,
+        }
+    } else if starts(b"\x1A\x45\xDF\xA3") {
+        FileKind::Video
+    } else if starts(b"ID3") || starts(b"fLaC") || starts(b"OggS") || starts(b"FORM") {
+        FileKind::Audio
+    } else if starts(b"PK\x03\x04")
+        || starts(b"\x1F\x8B")
+        || starts(b"BZh")
+        || starts(b"\xFD7zXZ")
+        || starts(b"7z\xBC\xAF")
+        || starts(b"Rar!")
+        || starts(b"\x28\xB5\x2F\xFD")
+        || starts(b"xar!")
+    {
+        FileKind::Archives
+    } else if starts(b"%PDF") || starts(b"{\\rtf") {
+        FileKind::Documents
+    } else if starts(b"\xCF\xFA\xED\xFE") || starts(b"\xCE\xFA\xED\xFE") || starts(b"\xCA\xFE\xBA\xBE") {
+        // Mach-O executables and universal binaries
+        FileKind::Apps
+    } else if starts(b"#!") {
+        FileKind::SourceCode
+    } else {
+        return None;
+    };
+    Some(kind)
+}
+
+pub fn classify(path: &Path) -> FileKind {
+    by_extension(path)
+        .or_else(|| by_magic(path))
+        .unwrap_or(FileKind::Other)
+}
+
+pub struct KindStats {
+    pub kind: FileKind,
+    pub bytes: u64,
+    pub count: u64,
+    /// Biggest first.
+    pub largest: Vec<(PathBuf, u64)>,
+}
+
+/// Bytes and counts per kind of file, biggest kind first.
+pub struct TypeReport {
+    pub kinds: Vec<KindStats>,
+    pub total_bytes: u64,
+}
+
+fn record(stats: &mut HashMap<FileKind, KindStats>, kind: FileKind, path: PathBuf, size: u64) {
+    let entry = stats.entry(kind).or_insert_with(|| KindStats {
+        kind,
+        bytes: 0,
+        count: 0,
+        largest: Vec::new(),
+    });
+    entry.bytes += size;
+    entry.count += 1;
+    entry.largest.push((path, size));
+    if entry.largest.len() >= LARGEST_PER_KIND * 4 {
+        keep_largest(&mut entry.largest);
+    }
+}
+
+fn keep_largest(files: &mut Vec<(PathBuf, u64)>) {
+    files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    files.truncate(LARGEST_PER_KIND);
+}
+
+/// Classify the files the large file finder looks at (the home folder, or
+/// `scan_path`) by kind. App bundles and media libraries count as one file.
+pub fn storage_by_type(scan_path: Option<&str>) -> TypeReport {
+    let root = scan_path.map(PathBuf::from).unwrap_or_else(utils::home_dir);
+    let mut stats: HashMap<FileKind, KindStats> = HashMap::new();
+
+    let mut walker = WalkDir::new(&root).max_depth(MAX_DEPTH).follow_links(false).into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        progress::file_visited();
+
+        if entry.file_type().is_dir() {
+            if entry.depth() == 0 {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy();
+            if let Some(kind) = bundle_kind(&name) {
+                let size = utils::dir_size(entry.path());
+                record(&mut stats, kind, entry.path().to_path_buf(), size);
+                walker.skip_current_dir();
+            } else if should_skip_dir(&name) {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let kind = classify(entry.path());
+        record(&mut stats, kind, entry.into_path(), meta.len());
+    }
+
+    let mut kinds: Vec<KindStats> = stats.into_values().collect();
+    for k in &mut kinds {
+        keep_largest(&mut k.largest);
+    }
+    kinds.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.kind.label().cmp(b.kind.label())));
+    TypeReport {
+        total_bytes: kinds.iter().map(|k| k.bytes).sum(),
+        kinds,
+    }
+}
@@ -24,6 +24,7 @@ impl Cleaner for HomebrewCache {
                 entries,
                 total_bytes,
                 errors,
+                groups: Vec::new(),
             };
         }
 
@@ -33,10 +34,7 @@ impl Cleaner for HomebrewCache {
                     let path = entry.path();
                     let size = utils::entry_size(&path);
                     total_bytes += size;
-                    entries.push(ScanEntry {
-                        path,
-                        size_bytes: size,
-                    });
+                    entries.push(ScanEntry::new(path, size));
                 }
             }
             Err(e) => {
@@ -49,34 +47,7 @@ impl Cleaner for HomebrewCache {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match utils::safe_remove(&entry.path) {
-                Ok(freed) => {
-                    total_freed += freed;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
-    }
 }
@@ -0,0 +1,173 @@
+use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::in_use::InUseSnapshot;
+use crate::progress;
+use crate::utils;
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// A download not written to for this long is no longer in progress.
+const QUIET_MINUTES: u64 = 30;
+
+/// Pause before re-checking candidates, to catch any still growing.
+const SETTLE: Duration = Duration::from_millis(500);
+
+/// Browsers that download into subfolders rarely nest deeper than this.
+const MAX_DEPTH: usize = 3;
+
+/// Extensions browsers give a download until it completes, and who uses them.
+const PARTIAL_EXTENSIONS: &[(&str, &str)] = &[
+    ("crdownload", "Chrome"),
+    ("part", "Firefox"),
+    ("partial", "Edge"),
+    ("opdownload", "Opera"),
+    // Safari keeps the partial file inside a folder with this extension
+    ("download", "Safari"),
+];
+
+/// Which browser left `name` behind, if it is an unfinished download.
+pub(super) fn partial_download_source(name: &str) -> Option<&'static str> {
+    let ext = name.rsplit_once('.')?.1.to_lowercase();
+    PARTIAL_EXTENSIONS
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, browser)| *browser)
+}
+
+/// The empty file Firefox creates under the final name while the `.part`
+/// beside it downloads.
+fn firefox_placeholder(part: &Path) -> Option<PathBuf> {
+    let placeholder = part.with_extension("");
+    let meta = fs::symlink_metadata(&placeholder).ok()?;
+    (meta.is_file() && meta.len() == 0).then_some(placeholder)
+}
+
+/// Latest modification time of `path`, looking inside Safari's bundles.
+fn last_written(path: &Path) -> Option<SystemTime> {
+    WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .max()
+}
+
+/// Size and last write, compared before and after [`SETTLE`].
+type Fingerprint = (u64, Option<SystemTime>);
+
+fn fingerprint(path: &Path) -> Fingerprint {
+    (utils::entry_size(path), last_written(path))
+}
+
+pub struct IncompleteDownloads;
+
+impl Cleaner for IncompleteDownloads {
+    fn name(&self) -> &'static str {
+        "incomplete-downloads"
+    }
+
+    fn label(&self) -> &'static str {
+        "Incomplete Downloads"
+    }
+
+    fn skips_in_use(&self) -> bool {
+        true
+    }
+
+    /// Partial downloads in ~/Downloads that nothing is writing any more:
+    /// quiet for [`QUIET_MINUTES`], unchanged across a short re-check, and
+    /// not held open by any process.
+    fn scan(&self) -> ScanResult {
+        let mut entries = Vec::new();
+        let mut total_bytes = 0u64;
+        let errors = Vec::new();
+
+        let downloads = utils::home_dir().join("Downloads");
+        let quiet_since = SystemTime::now()
+            .checked_sub(Duration::from_secs(QUIET_MINUTES * 60))
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let mut candidates: Vec<(PathBuf, &str, Fingerprint)> = Vec::new();
+        let mut walker = WalkDir::new(&downloads)
+            .min_depth(1)
+            .max_depth(MAX_DEPTH)
+            .follow_links(false)
+            .into_iter();
+        while let Some(entry) = walker.next() {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            progress::file_visited();
+            let name = entry.file_name().to_string_lossy();
+
+            let source = if entry.file_type().is_dir() {
+                let safari = partial_download_source(&name) == Some("Safari");
+                if safari || name.starts_with('.') {
+                    walker.skip_current_dir();
+                }
+                safari.then_some("Safari")
+            } else if entry.file_type().is_file() {
+                match partial_download_source(&name) {
+                    // Only Safari's partial downloads are folders
+                    Some("Safari") => None,
+                    Some(source) => Some(source),
+                    // Zero-length temporary files are left by interrupted downloads
+                    None if name.to_lowercase().ends_with(".tmp")
+                        && entry.metadata().is_ok_and(|m| m.len() == 0) =>
+                    {
+                        Some("empty temporary file")
+                    }
+                    None => None,
+                }
+            } else {
+                None
+            };
+            let Some(source) = source else {
+                continue;
+            };
+
+            let print = fingerprint(entry.path());
+            if print.1.is_none_or(|written| written > quiet_since) {
+                continue;
+            }
+            candidates.push((entry.into_path(), source, print));
+        }
+
+        if !candidates.is_empty() {
+            std::thread::sleep(SETTLE);
+        }
+        for (path, source, print) in candidates {
+            // Still growing: leave it to the browser
+            if fingerprint(&path) != print {
+                continue;
+            }
+            let (size, written) = print;
+            total_bytes += size;
+            let written = written.map(utils::format_date).unwrap_or_default();
+            if source == "Firefox" {
+                if let Some(placeholder) = firefox_placeholder(&path) {
+                    let mut entry = ScanEntry::new(placeholder, 0);
+                    entry.detail = Some("empty Firefox placeholder".to_string());
+                    entries.push(entry);
+                }
+            }
+            let mut entry = ScanEntry::new(path, size);
+            entry.detail = Some(format!("{source}, last written {written}"));
+            entries.push(entry);
+        }
+
+        // A paused download may still be held open by its browser
+        InUseSnapshot::capture().mark_entries(&mut entries);
+
+        entries.sort_by_key(|e| Reverse(e.size_bytes));
+
+        ScanResult {
+            entries,
+            total_bytes,
+            errors,
+            groups: Vec::new(),
+        }
+    }
+}
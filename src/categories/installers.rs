@@ -0,0 +1,321 @@
+use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::in_use::InUseSnapshot;
+use crate::progress;
+use crate::utils;
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Installers for apps that aren't installed are kept this long.
+const MAX_AGE_DAYS: u64 = 30;
+
+/// File extensions of app installers. Zips only count when they hold an app.
+const INSTALLER_EXTENSIONS: &[&str] = &["dmg", "pkg", "mpkg", "xip", "zip"];
+
+/// Filename words that describe the download rather than the app.
+const NOISE_WORDS: &[&str] = &[
+    "mac", "macos", "osx", "darwin", "universal", "arm64", "aarch64", "x64", "x86", "intel",
+    "apple", "silicon", "installer", "install", "setup", "latest", "release", "full", "stable",
+    "desktop",
+];
+
+/// A shorter key than this only matches exactly.
+const MIN_PREFIX_KEY: usize = 4;
+
+/// Whether `rest` is made up of noise words only ("installerfull").
+fn is_noise(rest: &str) -> bool {
+    rest.is_empty()
+        || NOISE_WORDS
+            .iter()
+            .any(|w| rest.strip_prefix(w).is_some_and(is_noise))
+}
+
+/// An app bundle found in /Applications or ~/Applications.
+struct InstalledApp {
+    name: String,
+    key: String,
+    bundle_id: Option<String>,
+    version: Option<String>,
+}
+
+/// Lowercase letters and digits only, so "Google Chrome" matches "googlechrome".
+fn match_key(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn installed_apps() -> Vec<InstalledApp> {
+    let dirs = [
+        utils::system_path("/Applications"),
+        utils::home_dir().join("Applications"),
+    ];
+    let mut apps = Vec::new();
+    for dir in &dirs {
+        // Vendor folders such as /Applications/Utilities hold apps one level down
+        for entry in walkdir::WalkDir::new(dir)
+            .min_depth(1)
+            .max_depth(2)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| {
+                let parent_is_app = e
+                    .path()
+                    .parent()
+                    .is_some_and(|p| p.extension().is_some_and(|ext| ext == "app"));
+                e.file_type().is_dir() && !parent_is_app
+            })
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "app") {
+                continue;
+            }
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            let info = plist::Value::from_file(path.join("Contents/Info.plist")).ok();
+            let field = |key: &str| {
+                info.as_ref()?
+                    .as_dictionary()?
+                    .get(key)?
+                    .as_string()
+                    .map(str::to_string)
+            };
+            apps.push(InstalledApp {
+                key: match_key(&name),
+                bundle_id: field("CFBundleIdentifier"),
+                version: field("CFBundleShortVersionString"),
+                name,
+            });
+        }
+    }
+    apps
+}
+
+/// Names of the `.app` bundles in a zip, read from its central directory.
+fn zip_app_names(path: &Path) -> Vec<String> {
+    const EOCD_SIG: &[u8] = b"PK\x05\x06";
+    const ENTRY_SIG: &[u8] = b"PK\x01\x02";
+
+    let read = || -> Option<Vec<u8>> {
+        let mut file = File::open(path).ok()?;
+        let len = file.metadata().ok()?.len();
+        // The end record is 22 bytes plus a comment of up to 64 KB
+        let tail_len = len.min(22 + 65_535);
+        file.seek(SeekFrom::Start(len - tail_len)).ok()?;
+        let mut tail = vec![0u8; tail_len as usize];
+        file.read_exact(&mut tail).ok()?;
+        let eocd = tail.windows(4).rposition(|w| w == EOCD_SIG)?;
+        let field = |at: usize| -> Option<u32> {
+            Some(u32::from_le_bytes(tail.get(eocd + at..eocd + at + 4)?.try_into().ok()?))
+        };
+        let (size, offset) = (field(12)?, field(16)?);
+        // Zip64 archives are not worth the trouble here
+        if size == u32::MAX || offset == u32::MAX || size > 64 * 1_048_576 {
+            return None;
+        }
+        file.seek(SeekFrom::Start(offset as u64)).ok()?;
+        let mut dir = vec![0u8; size as usize];
+        file.read_exact(&mut dir).ok()?;
+        Some(dir)
+    };
+    let Some(dir) = read() else {
+        return Vec::new();
+    };
+
+    let u16_at = |at: usize| dir.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+    let mut names: Vec<String> = Vec::new();
+    let mut pos = 0;
+    while dir.get(pos..pos + 4) == Some(ENTRY_SIG) {
+        let (Some(name_len), Some(extra_len), Some(comment_len)) = (u16_at(pos + 28), u16_at(pos + 30), u16_at(pos + 32))
+        else {
+            break;
+        };
+        let Some(name) = dir.get(pos + 46..pos + 46 + name_len) else {
+            break;
+        };
+        let name = String::from_utf8_lossy(name);
+        let app = name
+            .split('/')
+            .take_while(|c| *c != "__MACOSX")
+            .find(|c| c.ends_with(".app"))
+            .map(|c| c.trim_end_matches(".app").to_string());
+        if let Some(app) = app {
+            if !names.contains(&app) {
+                names.push(app);
+            }
+        }
+        pos += 46 + name_len + extra_len + comment_len;
+    }
+    names
+}
+
+/// The app name part of an installer's file name: "Slack-4.36.140-macOS.dmg"
+/// gives "slack", "googlechrome.dmg" gives "googlechrome".
+fn name_key_from_filename(stem: &str) -> String {
+    let words: Vec<&str> = stem
+        .split(['-', '_', ' ', '.', '(', ')'])
+        .filter(|w| !w.is_empty())
+        .take_while(|w| {
+            let lower = w.to_lowercase();
+            let versionish = lower.starts_with(|c: char| c.is_ascii_digit())
+                || (lower.starts_with('v') && lower[1..].starts_with(|c: char| c.is_ascii_digit()));
+            !versionish && !NOISE_WORDS.contains(&lower.as_str())
+        })
+        .collect();
+    match_key(&words.concat())
+}
+
+/// The installed app an installer is for, if it can be told.
+fn installed_match<'a>(apps: &'a [InstalledApp], stem: &str, bundled: &[String]) -> Option<&'a InstalledApp> {
+    // App names inside the archive are exact
+    for name in bundled {
+        let key = match_key(name);
+        if let Some(app) = apps.iter().find(|a| a.key == key) {
+            return Some(app);
+        }
+    }
+    // Some installers are named after the bundle ID
+    let lower = stem.to_lowercase();
+    if let Some(app) = apps
+        .iter()
+        .find(|a| a.bundle_id.as_ref().is_some_and(|id| lower.contains(&id.to_lowercase())))
+    {
+        return Some(app);
+    }
+
+    let key = name_key_from_filename(stem);
+    if key.is_empty() {
+        return None;
+    }
+    apps.iter().find(|a| a.key == key).or_else(|| {
+        // "zoomusInstallerFull" for zoom.us, but never "Firefox Developer
+        // Edition" for Firefox or "Python" for Python Launcher
+        apps.iter()
+            .filter(|a| a.key.len() >= MIN_PREFIX_KEY && key.len() >= MIN_PREFIX_KEY)
+            .filter(|a| {
+                let rest = key.strip_prefix(&a.key).or_else(|| a.key.strip_prefix(&key));
+                rest.is_some_and(is_noise)
+            })
+            .max_by_key(|a| a.key.len())
+    })
+}
+
+/// Whether `path` is an installer this category considers, so that other
+/// categories can leave it alone.
+pub(super) fn is_installer(path: &Path) -> bool {
+    if path.parent() != Some(utils::home_dir().join("Downloads").as_path()) {
+        return false;
+    }
+    match path.extension().map(|e| e.to_string_lossy().to_lowercase()) {
+        Some(ext) if ext == "zip" => !zip_app_names(path).is_empty(),
+        Some(ext) => INSTALLER_EXTENSIONS.contains(&ext.as_str()),
+        None => false,
+    }
+}
+
+pub struct InstallerLeftovers;
+
+impl Cleaner for InstallerLeftovers {
+    fn name(&self) -> &'static str {
+        "installers"
+    }
+
+    fn label(&self) -> &'static str {
+        "Installer Leftovers"
+    }
+
+    fn skips_in_use(&self) -> bool {
+        true
+    }
+
+    /// Disk images, packages and zipped apps in ~/Downloads whose app is
+    /// already installed, or which are older than [`MAX_AGE_DAYS`].
+    fn scan(&self) -> ScanResult {
+        let mut entries = Vec::new();
+        let mut total_bytes = 0u64;
+        let mut errors = Vec::new();
+
+        let downloads = utils::home_dir().join("Downloads");
+        let read_dir = match fs::read_dir(&downloads) {
+            Ok(rd) => rd,
+            Err(e) => {
+                if downloads.exists() {
+                    errors.push(format!("Cannot read {}: {e}", downloads.display()));
+                }
+                return ScanResult {
+                    entries,
+                    total_bytes,
+                    errors,
+                    groups: Vec::new(),
+                };
+            }
+        };
+
+        let apps = installed_apps();
+        let max_age = Duration::from_secs(MAX_AGE_DAYS * 86400);
+
+        for entry in read_dir.filter_map(|e| e.ok()) {
+            progress::file_visited();
+            let path: PathBuf = entry.path();
+            let Some(ext) = path.extension().map(|e| e.to_string_lossy().to_lowercase()) else {
+                continue;
+            };
+            if !INSTALLER_EXTENSIONS.contains(&ext.as_str()) {
+                continue;
+            }
+            // Flat packages are files, bundle packages are folders
+            let Ok(meta) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            let bundle_pkg = meta.is_dir() && ext.ends_with("pkg");
+            if !meta.is_file() && !bundle_pkg {
+                continue;
+            }
+
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            let bundled = if ext == "zip" { zip_app_names(&path) } else { Vec::new() };
+            let matched = installed_match(&apps, &stem, &bundled);
+            if ext == "zip" && bundled.is_empty() {
+                continue;
+            }
+
+            let age = meta
+                .modified()
+                .ok()
+                .and_then(|t| SystemTime::now().duration_since(t).ok())
+                .unwrap_or_default();
+            let detail = match matched {
+                Some(app) => {
+                    let version = app.version.as_deref().map(|v| format!(" {v}")).unwrap_or_default();
+                    format!("{}{version} is installed", app.name)
+                }
+                None if age > max_age => {
+                    let what = bundled.first().map_or(stem.as_str(), String::as_str);
+                    format!("{what} not installed, downloaded {} days ago", age.as_secs() / 86400)
+                }
+                None => continue,
+            };
+
+            let size = utils::entry_size(&path);
+            total_bytes += size;
+            let mut entry = ScanEntry::new(path, size);
+            entry.detail = Some(detail);
+            entries.push(entry);
+        }
+
+        // A mounted disk image or a running installer holds its file open
+        InUseSnapshot::capture().mark_entries(&mut entries);
+
+        entries.sort_by_key(|e| Reverse(e.size_bytes));
+
+        ScanResult {
+            entries,
+            total_bytes,
+            errors,
+            groups: Vec::new(),
+        }
+    }
+}
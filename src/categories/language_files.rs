@@ -1,4 +1,5 @@
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::progress;
 use crate::utils;
 use std::collections::HashSet;
 use std::sync::OnceLock;
@@ -61,21 +62,23 @@ impl Cleaner for LanguageFiles {
 
         let keep_langs = system_languages();
 
-        let apps_dir = std::path::Path::new("/Applications");
+        let apps_dir = utils::system_path("/Applications");
         if !apps_dir.exists() {
             return ScanResult {
                 entries,
                 total_bytes,
                 errors,
+                groups: Vec::new(),
             };
         }
 
         // Walk /Applications with depth limit
-        for entry in WalkDir::new(apps_dir)
+        for entry in WalkDir::new(&apps_dir)
             .max_depth(MAX_DEPTH)
             .follow_links(false)
             .into_iter()
             .filter_map(|e| e.ok())
+            .inspect(|_| progress::file_visited())
         {
             let path = entry.path();
 
@@ -98,7 +101,7 @@ impl Cleaner for LanguageFiles {
             }
 
             // Skip if it's a language we want to keep
-            if KEEP_LPROJ.iter().any(|&k| k == name) {
+            if KEEP_LPROJ.contains(&name) {
                 continue;
             }
 
@@ -113,54 +116,23 @@ impl Cleaner for LanguageFiles {
             match utils::entry_size(path) {
                 size if size > 0 => {
                     total_bytes += size;
-                    entries.push(ScanEntry {
-                        path: path.to_path_buf(),
-                        size_bytes: size,
-                    });
+                    entries.push(ScanEntry::new(path.to_path_buf(), size));
                 }
                 _ => {}
             }
         }
 
-        if let Err(e) = std::fs::read_dir(apps_dir) {
+        if let Err(e) = std::fs::read_dir(&apps_dir) {
             errors.push(format!("Cannot read /Applications: {e}"));
         }
 
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
 
         ScanResult {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match utils::safe_remove(&entry.path) {
-                Ok(freed) => {
-                    total_freed += freed;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result.errors.push(format!(
-                        "Failed to remove {}: {e}",
-                        entry.path.display()
-                    ));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
-    }
 }
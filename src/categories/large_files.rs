@@ -1,10 +1,11 @@
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::progress;
 use crate::utils;
 use std::path::PathBuf;
 use walkdir::WalkDir;
 
 /// Maximum depth to traverse.
-const MAX_DEPTH: usize = 10;
+pub(super) const MAX_DEPTH: usize = 10;
 
 /// Directories to skip during large file scan.
 const SKIP_DIRS: &[&str] = &[
@@ -46,9 +47,9 @@ const SKIP_EXTENSIONS: &[&str] = &[
     ".parallels",
 ];
 
-fn should_skip_dir(name: &str) -> bool {
+pub(super) fn should_skip_dir(name: &str) -> bool {
     let lower = name.to_lowercase();
-    SKIP_DIRS.iter().any(|&skip| name == skip)
+    SKIP_DIRS.contains(&name)
         || SKIP_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
 }
 
@@ -86,6 +87,7 @@ impl Cleaner for LargeFiles {
                 entries,
                 total_bytes,
                 errors,
+                groups: Vec::new(),
             };
         }
 
@@ -101,32 +103,28 @@ impl Cleaner for LargeFiles {
                 true
             });
 
-        for entry in walker.filter_map(|e| e.ok()) {
+        for entry in walker
+            .filter_map(|e| e.ok())
+            .inspect(|_| progress::file_visited())
+        {
             if !entry.file_type().is_file() {
                 continue;
             }
             if let Ok(metadata) = entry.metadata() {
                 if metadata.len() >= self.min_bytes {
                     total_bytes += metadata.len();
-                    entries.push(ScanEntry {
-                        path: entry.path().to_path_buf(),
-                        size_bytes: metadata.len(),
-                    });
+                    entries.push(ScanEntry::new(entry.path().to_path_buf(), metadata.len()));
                 }
             }
         }
 
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
 
         ScanResult {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
-
-    fn clean(&self, _dry_run: bool) -> ScanResult {
-        // Large files are report-only — never auto-delete
-        self.scan()
-    }
 }
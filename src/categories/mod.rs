@@ -2,37 +2,58 @@ mod app_logs;
 mod broken_symlinks;
 mod browser_caches;
 mod ds_store;
+mod duplicate_folders;
 mod duplicates;
 mod empty_folders;
+mod file_types;
 mod homebrew;
+mod incomplete_downloads;
+mod installers;
 mod language_files;
 mod large_files;
 mod old_files;
-mod privacy;
 mod package_managers;
+mod privacy;
+mod project_artifacts;
+mod runtime_versions;
 mod screenshots;
+mod similar_images;
 mod system_caches;
 mod trash;
 mod xcode;
 
+#[cfg(test)]
+mod tests;
+
 use crate::cleaner::Cleaner;
 
+pub use duplicates::{keep_rule, set_keep_rule, KeepPolicy, KeepRule};
+pub use file_types::{storage_by_type, FileKind, TypeReport};
+pub use project_artifacts::{set_stale_project_days, stale_project_days, MAX_STALE_DAYS, MIN_STALE_DAYS};
+pub use similar_images::{set_similarity_distance, similarity_distance, MAX_MAX_DISTANCE};
+pub use xcode::{derived_data_days, set_derived_data_days};
+
 pub fn all_cleaners(min_size_bytes: u64, scan_path: Option<&str>) -> Vec<Box<dyn Cleaner>> {
     vec![
         Box::new(system_caches::SystemCaches),
         Box::new(app_logs::AppLogs),
         Box::new(browser_caches::BrowserCaches),
-        Box::new(xcode::XcodeDerivedData),
+        Box::new(xcode::XcodeDerivedData::new(derived_data_days())),
         Box::new(xcode::XcodeDeviceSupport),
         Box::new(xcode::XcodeArchives),
         Box::new(xcode::CoreSimulator),
         Box::new(homebrew::HomebrewCache),
         Box::new(package_managers::PackageManagerCaches),
+        Box::new(project_artifacts::ProjectArtifacts::new(stale_project_days(), scan_path)),
+        Box::new(runtime_versions::RuntimeVersions),
         Box::new(trash::Trash),
         Box::new(ds_store::DsStore::new(scan_path)),
         Box::new(language_files::LanguageFiles),
-        Box::new(duplicates::DuplicateFinder),
+        Box::new(duplicates::DuplicateFinder::with_rule(keep_rule())),
+        Box::new(similar_images::SimilarImages::new(similarity_distance(), keep_rule())),
         Box::new(privacy::PrivacyCleaner),
+        Box::new(incomplete_downloads::IncompleteDownloads),
+        Box::new(installers::InstallerLeftovers),
         Box::new(old_files::OldFiles),
         Box::new(broken_symlinks::BrokenSymlinks),
         Box::new(empty_folders::EmptyFolders),
@@ -51,6 +72,7 @@ pub fn find_cleaner(
         .find(|c| c.name() == name)
 }
 
+#[allow(dead_code)] // public helper, unused by the GUI
 pub fn all_cleaner_names() -> Vec<&'static str> {
     vec![
         "system-caches",
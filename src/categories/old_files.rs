@@ -1,5 +1,14 @@
+use super::incomplete_downloads::partial_download_source;
+use super::installers::is_installer;
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::progress;
 use crate::utils;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::mem::MaybeUninit;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 use walkdir::WalkDir;
 
@@ -35,12 +44,128 @@ const SKIP_EXTENSIONS: &[&str] = &[
     ".parallels",
 ];
 
+/// Files per volume whose timestamps are sampled to judge its access times.
+const ATIME_SAMPLES: usize = 200;
+
+/// With fewer samples, access times are taken at face value.
+const MIN_ATIME_SAMPLES: usize = 20;
+
+/// A backup or indexing tool that reads everything leaves access times
+/// bunched within about this many seconds.
+const BULK_WINDOW_SECS: i64 = 3600;
+
 fn should_skip_dir(name: &str) -> bool {
     let lower = name.to_lowercase();
-    SKIP_DIRS.iter().any(|&skip| name == skip)
+    SKIP_DIRS.contains(&name)
         || SKIP_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
 }
 
+/// Whether the volume holding `path` is mounted without access time updates.
+/// `relatime`-style mounts still refresh them daily, which is plenty here.
+#[cfg(target_os = "macos")]
+fn mounted_noatime(path: &Path) -> bool {
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat = MaybeUninit::<libc::statfs>::uninit();
+    let ret = unsafe { libc::statfs(c_path.as_ptr(), stat.as_mut_ptr()) };
+    ret == 0 && unsafe { stat.assume_init() }.f_flags & libc::MNT_NOATIME as u32 != 0
+}
+
+#[cfg(not(target_os = "macos"))]
+fn mounted_noatime(path: &Path) -> bool {
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+    let ret = unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) };
+    ret == 0 && unsafe { stat.assume_init() }.f_flag & libc::ST_NOATIME != 0
+}
+
+/// Most of `times` (unix seconds) that fit in one `window`.
+fn largest_cluster(times: &mut [i64], window: i64) -> usize {
+    times.sort_unstable();
+    let mut best = 0;
+    let mut start = 0;
+    for end in 0..times.len() {
+        while times[end] - times[start] > window {
+            start += 1;
+        }
+        best = best.max(end - start + 1);
+    }
+    best
+}
+
+/// Access and modification times of files seen on one volume.
+#[derive(Default)]
+struct VolumeSample {
+    noatime: bool,
+    atimes: Vec<i64>,
+    mtimes: Vec<i64>,
+}
+
+impl VolumeSample {
+    /// Why access times on this volume can't be trusted, if they can't.
+    fn atime_problem(&mut self) -> Option<&'static str> {
+        if self.noatime {
+            return Some("volume mounted noatime");
+        }
+        let n = self.atimes.len();
+        if n < MIN_ATIME_SAMPLES {
+            return None;
+        }
+        // Never read since written, on practically every file: not updated
+        let unread = self.atimes.iter().zip(&self.mtimes).filter(|(a, m)| a <= m).count();
+        if unread * 100 >= n * 95 {
+            return Some("never updated on this volume");
+        }
+        // Read all at once although written at different times: a bulk reader
+        let atime_cluster = largest_cluster(&mut self.atimes, BULK_WINDOW_SECS);
+        let mtime_cluster = largest_cluster(&mut self.mtimes, BULK_WINDOW_SECS);
+        if atime_cluster * 10 >= n * 9 && mtime_cluster * 2 < n {
+            return Some("reset in bulk on this volume");
+        }
+        None
+    }
+}
+
+/// A file big enough to report, pending the verdict on its volume.
+struct Candidate {
+    path: PathBuf,
+    size: u64,
+    dev: u64,
+    accessed: Option<SystemTime>,
+    modified: Option<SystemTime>,
+    created: Option<SystemTime>,
+}
+
+impl Candidate {
+    /// When the file was last used, and how that was decided, for the entry.
+    /// Without usable access times, a file copied in recently (birth time)
+    /// counts as used even if it keeps an old modification date.
+    fn last_used(&self, atime_problem: Option<&str>) -> (SystemTime, String) {
+        let trusted = atime_problem.is_none() && self.accessed.is_some();
+        let dates = if trusted {
+            [("last accessed", self.accessed), ("modified", self.modified)]
+        } else {
+            [("modified", self.modified), ("added", self.created)]
+        };
+        // The first label wins a tie
+        let (what, when) = dates
+            .into_iter()
+            .filter_map(|(what, t)| Some((what, t?)))
+            .reduce(|a, b| if b.1 > a.1 { b } else { a })
+            .unwrap_or(("modified", SystemTime::UNIX_EPOCH));
+
+        let mut detail = format!("{what} {}", utils::format_date(when));
+        if !trusted {
+            let why = atime_problem.unwrap_or("unavailable");
+            detail.push_str(&format!(" (access times {why})"));
+        }
+        (when, detail)
+    }
+}
+
 pub struct OldFiles;
 
 impl Cleaner for OldFiles {
@@ -52,22 +177,30 @@ impl Cleaner for OldFiles {
         "Old & Unused Files"
     }
 
+    /// Large files not used for [`MIN_AGE_DAYS`]. "Used" means accessed where
+    /// the volume's access times look trustworthy, otherwise the later of the
+    /// modification and creation dates; each entry says which it was.
     fn scan(&self) -> ScanResult {
         let mut entries = Vec::new();
         let mut total_bytes = 0u64;
         let errors = Vec::new();
 
         let home = utils::home_dir();
-        let dirs_to_scan = [
-            home.join("Downloads"),
-            home.join("Documents"),
-            home.join("Desktop"),
-        ];
+        let downloads = home.join("Downloads");
+        let dirs_to_scan = [downloads.clone(), home.join("Documents"), home.join("Desktop")];
+
+        // Unfinished downloads in ~/Downloads (Safari's are folders) have
+        // their own category; elsewhere they are just files
+        let partial =
+            |path: &Path, name: &str| path.starts_with(&downloads) && partial_download_source(name).is_some();
 
         let threshold = SystemTime::now()
             .checked_sub(Duration::from_secs(MIN_AGE_DAYS * 86400))
             .unwrap_or(SystemTime::UNIX_EPOCH);
 
+        let mut volumes: HashMap<u64, VolumeSample> = HashMap::new();
+        let mut candidates = Vec::new();
+
         for dir in &dirs_to_scan {
             if !dir.exists() {
                 continue;
@@ -80,15 +213,21 @@ impl Cleaner for OldFiles {
                 .filter_entry(|e| {
                     if e.file_type().is_dir() {
                         let name = e.file_name().to_string_lossy();
-                        return !should_skip_dir(&name);
+                        return !should_skip_dir(&name) && !partial(e.path(), &name);
                     }
                     true
                 })
                 .filter_map(|e| e.ok())
+                .inspect(|_| progress::file_visited())
             {
                 if !entry.file_type().is_file() {
                     continue;
                 }
+                // Installers have their own category too
+                let name = entry.file_name().to_string_lossy();
+                if partial(entry.path(), &name) || is_installer(entry.path()) {
+                    continue;
+                }
 
                 // Single metadata call — get size + timestamps at once
                 let meta = match entry.metadata() {
@@ -96,63 +235,131 @@ impl Cleaner for OldFiles {
                     Err(_) => continue,
                 };
 
+                let volume = volumes.entry(meta.dev()).or_insert_with(|| VolumeSample {
+                    noatime: mounted_noatime(entry.path()),
+                    ..Default::default()
+                });
+                if volume.atimes.len() < ATIME_SAMPLES {
+                    volume.atimes.push(meta.atime());
+                    volume.mtimes.push(meta.mtime());
+                }
+
                 let size = meta.len();
                 if size < MIN_SIZE {
                     continue;
                 }
+                candidates.push(Candidate {
+                    path: entry.into_path(),
+                    size,
+                    dev: meta.dev(),
+                    accessed: meta.accessed().ok(),
+                    modified: meta.modified().ok(),
+                    created: meta.created().ok(),
+                });
+            }
+        }
 
-                // Check last accessed time, fall back to modified time
-                let last_used = meta
-                    .accessed()
-                    .or_else(|_| meta.modified())
-                    .unwrap_or(SystemTime::UNIX_EPOCH);
+        let problems: HashMap<u64, Option<&str>> = volumes
+            .iter_mut()
+            .map(|(dev, sample)| (*dev, sample.atime_problem()))
+            .collect();
 
-                if last_used > threshold {
-                    continue;
-                }
-
-                total_bytes += size;
-                entries.push(ScanEntry {
-                    path: entry.path().to_path_buf(),
-                    size_bytes: size,
-                });
+        for candidate in candidates {
+            let problem = problems.get(&candidate.dev).copied().flatten();
+            let (last_used, detail) = candidate.last_used(problem);
+            if last_used > threshold {
+                continue;
             }
+
+            total_bytes += candidate.size;
+            let mut entry = ScanEntry::new(candidate.path, candidate.size);
+            entry.detail = Some(detail);
+            entries.push(entry);
         }
 
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
 
         ScanResult {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
+}
 
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
+    const DAY: u64 = 86400;
 
-        for entry in result.entries.drain(..) {
-            match utils::safe_remove(&entry.path) {
-                Ok(freed) => {
-                    total_freed += freed;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
+    fn days_ago(days: u64) -> SystemTime {
+        SystemTime::now() - Duration::from_secs(days * DAY)
+    }
+
+    fn sample(files: impl Iterator<Item = (SystemTime, SystemTime)>) -> VolumeSample {
+        let secs = |t: SystemTime| t.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs() as i64;
+        let mut volume = VolumeSample::default();
+        for (atime, mtime) in files {
+            volume.atimes.push(secs(atime));
+            volume.mtimes.push(secs(mtime));
         }
+        volume
+    }
+
+    #[test]
+    fn spots_untrustworthy_access_times() {
+        // Written over months, read at varied times since
+        let mut normal = sample((0..40).map(|d| (days_ago(d), days_ago(d * 3 + 1))));
+        assert_eq!(normal.atime_problem(), None);
+
+        // Written over months, then all read by a backup within minutes
+        let now = SystemTime::now();
+        let mut backed_up = sample((0..40).map(|d| (now, days_ago(d * 3 + 1))));
+        assert_eq!(backed_up.atime_problem(), Some("reset in bulk on this volume"));
+
+        // Access times stuck at creation
+        let mut frozen = sample((0..40).map(|d| (days_ago(d * 3), days_ago(d * 3))));
+        assert_eq!(frozen.atime_problem(), Some("never updated on this volume"));
+
+        let mut few = sample((0..5).map(|d| (now, days_ago(d * 3 + 1))));
+        assert_eq!(few.atime_problem(), None);
+
+        let mut noatime = VolumeSample {
+            noatime: true,
+            ..Default::default()
+        };
+        assert_eq!(noatime.atime_problem(), Some("volume mounted noatime"));
+    }
+
+    #[test]
+    fn labels_the_date_it_used() {
+        let file = Candidate {
+            path: PathBuf::from("/tmp/x"),
+            size: MIN_SIZE,
+            dev: 1,
+            accessed: Some(days_ago(1)),
+            modified: Some(days_ago(400)),
+            created: Some(days_ago(300)),
+        };
+
+        let (when, detail) = file.last_used(None);
+        assert_eq!(when, file.accessed.unwrap());
+        assert!(detail.starts_with("last accessed "));
+
+        let (when, detail) = file.last_used(Some("reset in bulk on this volume"));
+        assert_eq!(when, file.created.unwrap());
+        assert_eq!(
+            detail,
+            format!(
+                "added {} (access times reset in bulk on this volume)",
+                utils::format_date(days_ago(300))
+            )
+        );
 
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
+        let unknown = Candidate { accessed: None, created: None, ..file };
+        let (_, detail) = unknown.last_used(None);
+        assert!(detail.starts_with("modified ") && detail.ends_with("(access times unavailable)"));
     }
 }
@@ -1,8 +1,13 @@
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
 use crate::utils;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
 struct PmCache {
-    path: Vec<&'static str>, // path components relative to home
+    tool: &'static str,
+    path: Vec<&'static str>, // path components relative to home; "*" matches any folder
 }
 
 pub struct PackageManagerCaches;
@@ -11,19 +16,141 @@ impl PackageManagerCaches {
     fn cache_dirs() -> Vec<PmCache> {
         vec![
             PmCache {
+                tool: "npm",
                 path: vec![".npm", "_cacache"],
             },
             PmCache {
+                tool: "Yarn",
                 path: vec!["Library", "Caches", "Yarn"],
             },
             PmCache {
+                tool: "pnpm",
+                path: vec!["Library", "pnpm", "store"],
+            },
+            PmCache {
+                tool: "Bun",
+                path: vec![".bun", "install", "cache"],
+            },
+            PmCache {
+                tool: "Deno",
+                path: vec!["Library", "Caches", "deno"],
+            },
+            PmCache {
+                tool: "pip",
                 path: vec!["Library", "Caches", "pip"],
             },
             PmCache {
+                tool: "Poetry",
+                path: vec!["Library", "Caches", "pypoetry"],
+            },
+            PmCache {
+                tool: "conda",
+                path: vec!["miniconda3", "pkgs"],
+            },
+            PmCache {
+                tool: "conda",
+                path: vec!["anaconda3", "pkgs"],
+            },
+            PmCache {
+                tool: "conda",
+                path: vec!["miniforge3", "pkgs"],
+            },
+            PmCache {
+                tool: "conda",
+                path: vec!["opt", "anaconda3", "pkgs"],
+            },
+            PmCache {
+                tool: "cargo",
                 path: vec![".cargo", "registry", "cache"],
             },
+            PmCache {
+                tool: "cargo git",
+                path: vec![".cargo", "git", "checkouts"],
+            },
+            PmCache {
+                tool: "Gradle",
+                path: vec![".gradle", "caches"],
+            },
+            PmCache {
+                tool: "Maven",
+                path: vec![".m2", "repository"],
+            },
+            PmCache {
+                tool: "CocoaPods",
+                path: vec!["Library", "Caches", "CocoaPods"],
+            },
+            PmCache {
+                tool: "SwiftPM",
+                path: vec!["Library", "Caches", "org.swift.swiftpm"],
+            },
+            PmCache {
+                tool: "Go modules",
+                path: vec!["go", "pkg", "mod"],
+            },
+            PmCache {
+                tool: "Go build",
+                path: vec!["Library", "Caches", "go-build"],
+            },
+            PmCache {
+                tool: "Composer",
+                path: vec!["Library", "Caches", "composer"],
+            },
+            PmCache {
+                tool: "Composer",
+                path: vec![".composer", "cache"],
+            },
+            PmCache {
+                tool: "RubyGems",
+                path: vec![".gem", "ruby", "*", "cache"],
+            },
+            PmCache {
+                tool: "Bundler",
+                path: vec![".bundle", "cache"],
+            },
         ]
     }
+
+    /// Existing paths for a cache, expanding "*" components.
+    fn expand(home: &Path, components: &[&str]) -> Vec<PathBuf> {
+        let mut paths = vec![home.to_path_buf()];
+        for component in components {
+            paths = paths
+                .iter()
+                .flat_map(|base| {
+                    if *component != "*" {
+                        return vec![base.join(component)];
+                    }
+                    let mut dirs: Vec<PathBuf> = std::fs::read_dir(base)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|e| e.ok())
+                        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                        .map(|e| e.path())
+                        .collect();
+                    dirs.sort();
+                    dirs
+                })
+                .collect();
+        }
+        paths.retain(|p| p.exists());
+        paths
+    }
+}
+
+/// Give the owner write access to every folder under `path`.
+fn make_dirs_writable(path: &Path) {
+    for entry in WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+    {
+        if let Ok(meta) = entry.metadata() {
+            let mut perms = meta.permissions();
+            perms.set_mode(perms.mode() | 0o200);
+            let _ = std::fs::set_permissions(entry.path(), perms);
+        }
+    }
 }
 
 impl Cleaner for PackageManagerCaches {
@@ -42,22 +169,14 @@ impl Cleaner for PackageManagerCaches {
         let errors = Vec::new();
 
         for pm in Self::cache_dirs() {
-            let mut cache_path = home.clone();
-            for component in &pm.path {
-                cache_path = cache_path.join(component);
-            }
-
-            if !cache_path.exists() {
-                continue;
-            }
-
-            let size = utils::entry_size(&cache_path);
-            if size > 0 {
-                total_bytes += size;
-                entries.push(ScanEntry {
-                    path: cache_path,
-                    size_bytes: size,
-                });
+            for cache_path in Self::expand(&home, &pm.path) {
+                let size = utils::entry_size(&cache_path);
+                if size > 0 {
+                    total_bytes += size;
+                    let mut entry = ScanEntry::new(cache_path, size);
+                    entry.detail = Some(pm.tool.to_string());
+                    entries.push(entry);
+                }
             }
         }
 
@@ -66,34 +185,15 @@ impl Cleaner for PackageManagerCaches {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
 
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
+    /// Go writes its module cache read-only, so it is unlocked first.
+    fn remove(&self, path: &Path) -> io::Result<u64> {
+        if path == utils::home_dir().join("go/pkg/mod") {
+            make_dirs_writable(path);
         }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match utils::safe_remove(&entry.path) {
-                Ok(freed) => {
-                    total_freed += freed;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
+        utils::safe_remove(path)
     }
 }
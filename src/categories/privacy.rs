@@ -1,4 +1,5 @@
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::in_use::InUseSnapshot;
 use crate::utils;
 use std::path::PathBuf;
 
@@ -128,14 +129,15 @@ impl Cleaner for PrivacyCleaner {
         "Privacy Data"
     }
 
+    fn skips_in_use(&self) -> bool {
+        true
+    }
+
     fn scan(&self) -> ScanResult {
         let mut entries = Vec::new();
         let mut total_bytes = 0u64;
-        let mut errors = Vec::new();
-
-        errors.push(
-            "Clearing cookies and history will log you out of websites.".to_string(),
-        );
+        let errors =
+            vec!["Clearing cookies and history will log you out of websites.".to_string()];
 
         let all_files: Vec<PathBuf> = [
             Self::safari_privacy_files(),
@@ -151,47 +153,19 @@ impl Cleaner for PrivacyCleaner {
             let size = utils::entry_size(&path);
             if size > 0 {
                 total_bytes += size;
-                entries.push(ScanEntry {
-                    path,
-                    size_bytes: size,
-                });
+                entries.push(ScanEntry::new(path, size));
             }
         }
 
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        InUseSnapshot::capture().mark_entries(&mut entries);
+
+        entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
 
         ScanResult {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match utils::safe_remove(&entry.path) {
-                Ok(freed) => {
-                    total_freed += freed;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
-    }
 }
@@ -0,0 +1,249 @@
+use super::large_files::should_skip_dir;
+use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::progress;
+use crate::utils;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// Projects untouched for this many days have their artifacts offered.
+pub const DEFAULT_STALE_DAYS: u32 = 90;
+
+/// Range offered in settings.
+pub const MIN_STALE_DAYS: u32 = 7;
+pub const MAX_STALE_DAYS: u32 = 730;
+
+/// Staleness used by scans started from the UI.
+static STALE_DAYS: AtomicU32 = AtomicU32::new(DEFAULT_STALE_DAYS);
+
+pub fn stale_project_days() -> u32 {
+    STALE_DAYS.load(Ordering::Relaxed)
+}
+
+pub fn set_stale_project_days(days: u32) {
+    STALE_DAYS.store(days.clamp(MIN_STALE_DAYS, MAX_STALE_DAYS), Ordering::Relaxed);
+}
+
+/// Deepest project root looked for below the scan root.
+const MAX_DEPTH: usize = 8;
+
+/// A kind of project, recognised by a marker file in its root, and the
+/// folders its tools regenerate there.
+struct Ecosystem {
+    label: &'static str,
+    markers: &'static [&'static str],
+    artifacts: &'static [&'static str],
+}
+
+const ECOSYSTEMS: &[Ecosystem] = &[
+    Ecosystem {
+        label: "Rust",
+        markers: &["Cargo.toml"],
+        artifacts: &["target"],
+    },
+    Ecosystem {
+        label: "Node",
+        markers: &["package.json"],
+        artifacts: &["node_modules", ".next", ".nuxt", ".parcel-cache"],
+    },
+    Ecosystem {
+        label: "Python",
+        markers: &["pyproject.toml"],
+        artifacts: &[".venv", "venv", ".tox", ".pytest_cache", ".mypy_cache"],
+    },
+    Ecosystem {
+        label: "Gradle",
+        markers: &["build.gradle", "build.gradle.kts"],
+        artifacts: &["build", ".gradle"],
+    },
+    Ecosystem {
+        label: "CocoaPods",
+        markers: &["Podfile"],
+        artifacts: &["Pods"],
+    },
+];
+
+/// Python leaves these in every package folder, not just the project root.
+const NESTED_ARTIFACT: &str = "__pycache__";
+
+pub(super) fn is_artifact_name(name: &str) -> bool {
+    name == NESTED_ARTIFACT || ECOSYSTEMS.iter().any(|e| e.artifacts.contains(&name))
+}
+
+/// Guard against user folders that merely share an artifact's name.
+fn looks_generated(name: &str, path: &Path) -> bool {
+    match name {
+        "target" => ["CACHEDIR.TAG", ".rustc_info.json", "debug", "release"]
+            .iter()
+            .any(|f| path.join(f).exists()),
+        ".venv" | "venv" => path.join("pyvenv.cfg").is_file(),
+        "Pods" => path.join("Manifest.lock").is_file(),
+        _ => true,
+    }
+}
+
+/// Newest modification time in a project, ignoring its artifacts. Git's
+/// index and HEAD count, so a checkout or commit keeps a project fresh.
+fn last_modified(root: &Path) -> Option<SystemTime> {
+    let git = ["index", "HEAD"]
+        .iter()
+        .filter_map(|f| root.join(".git").join(f).metadata().ok()?.modified().ok());
+    WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            !(e.file_type().is_dir() && e.depth() > 0 && (name == ".git" || is_artifact_name(&name)))
+        })
+        .filter_map(|e| e.ok())
+        .inspect(|_| progress::file_visited())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .chain(git)
+        .max()
+}
+
+/// `__pycache__` folders anywhere in a Python project.
+fn nested_caches(root: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut walker = WalkDir::new(root).min_depth(1).follow_links(false).into_iter();
+    while let Some(Ok(entry)) = walker.next() {
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy();
+        if name == NESTED_ARTIFACT {
+            found.push(entry.path().to_path_buf());
+            walker.skip_current_dir();
+        } else if name == ".git" || is_artifact_name(&name) {
+            walker.skip_current_dir();
+        }
+    }
+    found
+}
+
+/// Regenerable build output (`target`, `node_modules`, `.venv`, `build`,
+/// `Pods`, ...) of projects that haven't been touched in a while.
+pub struct ProjectArtifacts {
+    stale_days: u32,
+    root: PathBuf,
+}
+
+impl ProjectArtifacts {
+    pub fn new(stale_days: u32, path: Option<&str>) -> Self {
+        let root = path.map(PathBuf::from).unwrap_or_else(utils::home_dir);
+        Self { stale_days, root }
+    }
+
+    /// Artifacts of the project at `dir`, if it is one, with a label.
+    fn artifacts_of(dir: &Path) -> Option<(Vec<PathBuf>, String)> {
+        let found: Vec<&Ecosystem> = ECOSYSTEMS
+            .iter()
+            .filter(|e| e.markers.iter().any(|m| dir.join(m).is_file()))
+            .collect();
+        if found.is_empty() {
+            return None;
+        }
+
+        let mut artifacts: Vec<PathBuf> = found
+            .iter()
+            .flat_map(|e| e.artifacts.iter())
+            .map(|name| (name, dir.join(name)))
+            .filter(|(name, path)| {
+                // Never through a symlink, which could point anywhere
+                path.symlink_metadata().is_ok_and(|m| m.is_dir()) && looks_generated(name, path)
+            })
+            .map(|(_, path)| path)
+            .collect();
+        if found.iter().any(|e| e.label == "Python") {
+            artifacts.extend(nested_caches(dir));
+        }
+        let label = found.iter().map(|e| e.label).collect::<Vec<_>>().join("/");
+        Some((artifacts, label))
+    }
+}
+
+impl Cleaner for ProjectArtifacts {
+    fn name(&self) -> &'static str {
+        "project-artifacts"
+    }
+
+    fn label(&self) -> &'static str {
+        "Stale Build Artifacts"
+    }
+
+    fn scan(&self) -> ScanResult {
+        let mut entries = Vec::new();
+        let mut total_bytes = 0u64;
+        let errors = Vec::new();
+
+        let stale_since = SystemTime::now()
+            .checked_sub(Duration::from_secs(self.stale_days as u64 * 86400))
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        // A Python project's caches are also found from any project around it
+        let mut reported: HashSet<PathBuf> = HashSet::new();
+        let mut walker = WalkDir::new(&self.root)
+            .max_depth(MAX_DEPTH)
+            .follow_links(false)
+            .into_iter();
+        while let Some(entry) = walker.next() {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+            progress::file_visited();
+            let name = entry.file_name().to_string_lossy();
+            if entry.depth() > 0 && (should_skip_dir(&name) || is_artifact_name(&name)) {
+                walker.skip_current_dir();
+                continue;
+            }
+
+            let Some((artifacts, label)) = Self::artifacts_of(entry.path()) else {
+                continue;
+            };
+            if artifacts.is_empty() {
+                continue;
+            }
+            // Nested projects are judged on their own, so keep walking
+            let Some(modified) = last_modified(entry.path()) else {
+                continue;
+            };
+            if modified > stale_since {
+                continue;
+            }
+
+            let days = SystemTime::now()
+                .duration_since(modified)
+                .map(|d| d.as_secs() / 86400)
+                .unwrap_or(0);
+            for path in artifacts {
+                if !reported.insert(path.clone()) {
+                    continue;
+                }
+                let size = utils::dir_size(&path);
+                if size == 0 {
+                    continue;
+                }
+                total_bytes += size;
+                let mut scan_entry = ScanEntry::new(path, size);
+                scan_entry.detail = Some(format!("{label} project {name}, untouched {days} days"));
+                entries.push(scan_entry);
+            }
+        }
+
+        entries.sort_by_key(|e| Reverse(e.size_bytes));
+
+        ScanResult {
+            entries,
+            total_bytes,
+            errors,
+            groups: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,544 @@
+use super::large_files::should_skip_dir;
+use super::project_artifacts::is_artifact_name;
+use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::in_use::InUseSnapshot;
+use crate::progress;
+use crate::utils;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Deepest version file looked for below the home folder.
+const PROJECT_DEPTH: usize = 8;
+
+/// Longest chain of nvm aliases followed before giving up.
+const MAX_ALIAS_DEPTH: usize = 8;
+
+/// Version managers that keep one folder per installed version:
+/// (manager, folder relative to home, tool, or `None` for one folder per tool).
+const MANAGERS: &[(&str, &str, Option<&str>)] = &[
+    ("nvm", ".nvm/versions/node", Some("node")),
+    ("pyenv", ".pyenv/versions", Some("python")),
+    ("rbenv", ".rbenv/versions", Some("ruby")),
+    ("asdf", ".asdf/installs", None),
+    ("mise", ".local/share/mise/installs", None),
+    ("rustup", ".rustup/toolchains", Some("rust")),
+];
+
+/// Files in a project that pin a single tool's version.
+const VERSION_FILES: &[(&str, &str)] = &[
+    (".nvmrc", "node"),
+    (".node-version", "node"),
+    (".python-version", "python"),
+    (".ruby-version", "ruby"),
+    ("rust-toolchain", "rust"),
+    ("rust-toolchain.toml", "rust"),
+];
+
+/// Files in a project that pin versions of several tools.
+const MULTI_VERSION_FILES: &[&str] = &[".tool-versions", "mise.toml", ".mise.toml"];
+
+/// One installed version of a tool.
+struct Install {
+    manager: &'static str,
+    tool: String,
+    version: String,
+    path: PathBuf,
+}
+
+/// A version asked for by a default or a project. A default only speaks for
+/// its own managers; a project's version file counts for all of them.
+struct Reference {
+    tool: String,
+    spec: String,
+    managers: &'static [&'static str],
+}
+
+/// asdf says "nodejs" where everyone else says "node".
+fn normalize_tool(tool: &str) -> String {
+    match tool {
+        "nodejs" => "node".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// rustup names toolchains "<channel>-<host>"; projects name only the channel.
+fn toolchain_channel(name: &str) -> &str {
+    const ARCHES: &[&str] = &["x86_64", "aarch64", "arm64", "i686", "armv7"];
+    name.match_indices('-')
+        .find(|(at, _)| ARCHES.iter().any(|a| name[at + 1..].starts_with(a)))
+        .map_or(name, |(at, _)| &name[..at])
+}
+
+impl Install {
+    /// What a version spec is compared against.
+    fn id(&self) -> &str {
+        if self.tool == "rust" {
+            toolchain_channel(&self.version)
+        } else {
+            &self.version
+        }
+    }
+}
+
+/// Text between double quotes: `["3.11", "3.10"]` gives both versions.
+fn quoted(text: &str) -> Vec<String> {
+    text.split('"').skip(1).step_by(2).map(str::to_string).collect()
+}
+
+/// What a version spec refers to among a tool's installs.
+enum Resolved<'a> {
+    Install(&'a Install),
+    /// A version that isn't installed, or no version at all ("ref:", "path:").
+    Missing,
+    /// A name that can't be mapped to a version, like "system" or "lts/*":
+    /// any install could be the one in use.
+    Unknown,
+}
+
+/// The install a spec means: an exact match, else the newest install with
+/// that prefix ("18" is the newest 18.x). "latest" and nvm's "node" and
+/// "stable" mean the newest install; other names are [`Resolved::Unknown`].
+fn resolve<'a>(spec: &str, installs: &[&'a Install]) -> Resolved<'a> {
+    let spec = spec.trim();
+    let spec = match spec.strip_prefix('v') {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => rest,
+        _ => spec,
+    };
+    let spec = if installs.first().is_some_and(|i| i.tool == "rust") {
+        toolchain_channel(spec)
+    } else {
+        spec
+    };
+    if spec.is_empty() || spec.starts_with("ref:") || spec.starts_with("path:") {
+        return Resolved::Missing;
+    }
+    let newest = |found: Vec<&'a Install>| {
+        found
+            .into_iter()
+            .max_by_key(|i| utils::version_key(i.id()))
+            .map_or(Resolved::Missing, Resolved::Install)
+    };
+
+    if let Some(exact) = installs.iter().find(|i| i.id() == spec) {
+        return Resolved::Install(exact);
+    }
+    if spec.starts_with(|c: char| c.is_ascii_digit()) {
+        let prefix = format!("{spec}.");
+        return newest(installs.iter().copied().filter(|i| i.id().starts_with(&prefix)).collect());
+    }
+    if ["latest", "node", "stable"].contains(&spec) {
+        return newest(installs.to_vec());
+    }
+    Resolved::Unknown
+}
+
+/// Follow nvm's alias files (`~/.nvm/alias/<name>`, `lts/<codename>`) from
+/// `spec` to a version. "node" and "stable" mean the newest install and are
+/// returned as is. `None` when the chain breaks or loops.
+fn nvm_alias(home: &Path, spec: &str) -> Option<String> {
+    let mut spec = spec.trim().to_string();
+    for _ in 0..MAX_ALIAS_DEPTH {
+        let versionish = spec.trim_start_matches('v').starts_with(|c: char| c.is_ascii_digit());
+        if versionish || ["node", "stable", "system"].contains(&spec.as_str()) {
+            return Some(spec);
+        }
+        if spec.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+            return None;
+        }
+        spec = fs::read_to_string(home.join(".nvm/alias").join(&spec)).ok()?.trim().to_string();
+    }
+    None
+}
+
+/// Virtualenvs pyenv-virtualenv made from a Python version, kept inside it.
+fn pyenv_envs(install: &Install) -> Vec<String> {
+    if install.manager != "pyenv" {
+        return Vec::new();
+    }
+    let mut envs: Vec<String> = fs::read_dir(install.path.join("envs"))
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    envs.sort();
+    envs
+}
+
+/// Every version folder of every manager, symlinked aliases included.
+fn installs(home: &Path) -> Vec<Install> {
+    let list = |dir: &Path| -> Vec<(String, PathBuf)> {
+        let mut found: Vec<(String, PathBuf)> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+            .filter(|(name, _)| !name.starts_with('.'))
+            .collect();
+        found.sort();
+        found
+    };
+
+    let mut all = Vec::new();
+    for &(manager, rel, tool) in MANAGERS {
+        let root = home.join(rel);
+        let per_tool = match tool {
+            Some(tool) => vec![(tool.to_string(), root)],
+            None => list(&root),
+        };
+        for (tool, dir) in per_tool {
+            for (name, path) in list(&dir) {
+                let version = match manager {
+                    "nvm" => name.trim_start_matches('v').to_string(),
+                    _ => name,
+                };
+                all.push(Install {
+                    manager,
+                    tool: normalize_tool(&tool),
+                    version,
+                    path,
+                });
+            }
+        }
+    }
+    all
+}
+
+/// `tool version...` lines of an asdf `.tool-versions` file.
+fn parse_tool_versions(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            let tool = normalize_tool(words.next()?);
+            Some(words.map(move |v| (tool.clone(), v.to_string())).collect::<Vec<_>>())
+        })
+        .flatten()
+        .collect()
+}
+
+/// The `[tools]` table of a mise config.
+fn parse_mise_toml(text: &str) -> Vec<(String, String)> {
+    let mut in_tools = false;
+    let mut found = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_tools = line == "[tools]";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if in_tools {
+            let tool = normalize_tool(key.trim().trim_matches('"'));
+            found.extend(quoted(value).into_iter().map(|v| (tool.clone(), v)));
+        }
+    }
+    found
+}
+
+/// The versions a version file at `path` asks for.
+fn parse_version_file(path: &Path) -> Vec<(String, String)> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let Ok(text) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    match name.as_ref() {
+        ".tool-versions" => parse_tool_versions(&text),
+        "mise.toml" | ".mise.toml" | "config.toml" => parse_mise_toml(&text),
+        _ => {
+            let Some(&(_, tool)) = VERSION_FILES.iter().find(|(f, _)| *f == name) else {
+                return Vec::new();
+            };
+            // rust-toolchain may be a bare channel or the TOML form
+            if text.contains("channel") {
+                let channel = text.lines().find(|l| l.trim_start().starts_with("channel"));
+                return channel
+                    .map(|l| quoted(l).into_iter().map(|v| (tool.to_string(), v)).collect())
+                    .unwrap_or_default();
+            }
+            text.lines()
+                .map(|l| l.split('#').next().unwrap_or_default().trim())
+                .filter(|l| !l.is_empty())
+                .map(|l| (tool.to_string(), l.to_string()))
+                .collect()
+        }
+    }
+}
+
+/// Versions set as the default by each manager's own global setting.
+fn global_references(home: &Path) -> Vec<Reference> {
+    let mut refs = Vec::new();
+    let mut add = |managers: &'static [&'static str], found: Vec<(String, String)>| {
+        refs.extend(found.into_iter().map(|(tool, spec)| Reference { tool, spec, managers }));
+    };
+    let read = |rel: &str| fs::read_to_string(home.join(rel)).unwrap_or_default();
+    let lines = |tool: &str, text: String| -> Vec<(String, String)> {
+        text.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|l| (tool.to_string(), l.to_string()))
+            .collect()
+    };
+
+    add(&["nvm"], lines("node", read(".nvm/alias/default")));
+    add(&["pyenv"], lines("python", read(".pyenv/version")));
+    add(&["rbenv"], lines("ruby", read(".rbenv/version")));
+    add(&["asdf", "mise"], parse_tool_versions(&read(".tool-versions")));
+    add(&["mise"], parse_mise_toml(&read(".config/mise/config.toml")));
+
+    // rustup's default and its per-folder overrides
+    let settings = read(".rustup/settings.toml");
+    let mut in_overrides = false;
+    let mut rust = Vec::new();
+    for line in settings.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_overrides = line == "[overrides]";
+        } else if let Some((key, value)) = line.split_once('=') {
+            if in_overrides || key.trim() == "default_toolchain" {
+                rust.extend(quoted(value).into_iter().map(|v| ("rust".to_string(), v)));
+            }
+        }
+    }
+    add(&["rustup"], rust);
+    refs
+}
+
+/// Versions pinned by version files in project folders under the home
+/// folder. Hidden folders, `~/Library` and build output are not searched.
+fn project_references(home: &Path) -> Vec<Reference> {
+    let go_modules = home.join("go/pkg");
+    let mut refs = Vec::new();
+    let mut walker = WalkDir::new(home)
+        .min_depth(1)
+        .max_depth(PROJECT_DEPTH)
+        .follow_links(false)
+        .into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy();
+        if entry.file_type().is_dir() {
+            progress::file_visited();
+            if name.starts_with('.')
+                || should_skip_dir(&name)
+                || is_artifact_name(&name)
+                || entry.path() == go_modules
+            {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+        // The home folder's own .tool-versions is a default, read above
+        let is_version_file =
+            VERSION_FILES.iter().any(|(f, _)| *f == name) || MULTI_VERSION_FILES.contains(&name.as_ref());
+        if !is_version_file || entry.depth() == 1 && name == ".tool-versions" {
+            continue;
+        }
+        refs.extend(
+            parse_version_file(entry.path())
+                .into_iter()
+                .map(|(tool, spec)| Reference { tool, spec, managers: &[] }),
+        );
+    }
+    refs
+}
+
+/// Installed runtime versions from nvm, pyenv, rbenv, asdf, mise and rustup
+/// that are neither a manager's default nor pinned by any project.
+pub struct RuntimeVersions;
+
+impl Cleaner for RuntimeVersions {
+    fn name(&self) -> &'static str {
+        "runtime-versions"
+    }
+
+    fn label(&self) -> &'static str {
+        "Old Runtime Versions"
+    }
+
+    fn skips_in_use(&self) -> bool {
+        true
+    }
+
+    fn scan(&self) -> ScanResult {
+        let mut entries = Vec::new();
+        let mut total_bytes = 0u64;
+        let errors = Vec::new();
+
+        let home = utils::home_dir();
+        let installs = installs(&home);
+        if installs.is_empty() {
+            return ScanResult {
+                entries,
+                total_bytes,
+                errors,
+                groups: Vec::new(),
+            };
+        }
+        let globals = global_references(&home);
+        let projects = project_references(&home);
+
+        // Kept installs, by resolved path so aliases keep what they point at
+        let mut kept: HashSet<PathBuf> = HashSet::new();
+        let mut keep = |install: &Install| {
+            kept.insert(fs::canonicalize(&install.path).unwrap_or_else(|_| install.path.clone()));
+        };
+        let mut groups: Vec<(&str, &str)> = installs.iter().map(|i| (i.manager, i.tool.as_str())).collect();
+        groups.dedup();
+        for (manager, tool) in groups {
+            let group: Vec<&Install> = installs
+                .iter()
+                .filter(|i| i.manager == manager && i.tool == tool)
+                .collect();
+            // nvm specs may name aliases; a spec that leads nowhere could
+            // mean any version, so the tool's versions all stay
+            let resolve_spec = |spec: &str| match manager {
+                "nvm" => nvm_alias(&home, spec).map_or(Resolved::Unknown, |spec| resolve(&spec, &group)),
+                _ => resolve(spec, &group),
+            };
+            let mut unresolved = false;
+            let mut has_default = false;
+            for r in globals.iter().filter(|r| r.tool == tool && r.managers.contains(&manager)) {
+                match resolve_spec(&r.spec) {
+                    Resolved::Install(install) => {
+                        keep(install);
+                        has_default = true;
+                    }
+                    Resolved::Missing => {}
+                    Resolved::Unknown => unresolved = true,
+                }
+            }
+            // Without a readable default, the newest is most likely in use
+            if !has_default {
+                if let Some(install) = group.iter().max_by_key(|i| utils::version_key(i.id())) {
+                    keep(install);
+                }
+            }
+            for r in projects.iter().filter(|r| r.tool == tool) {
+                match resolve_spec(&r.spec) {
+                    Resolved::Install(install) => keep(install),
+                    Resolved::Missing => {}
+                    Resolved::Unknown => unresolved = true,
+                }
+            }
+            if unresolved {
+                group.iter().for_each(|install| keep(install));
+            }
+        }
+
+        for install in &installs {
+            // Aliases are links into real installs, removed with them
+            if fs::symlink_metadata(&install.path).is_ok_and(|m| m.file_type().is_symlink()) {
+                continue;
+            }
+            let real = fs::canonicalize(&install.path).unwrap_or_else(|_| install.path.clone());
+            // A kept virtualenv lives inside the version it was made from
+            if kept.iter().any(|k| k.starts_with(&real)) {
+                continue;
+            }
+            let size = utils::dir_size(&install.path);
+            if size == 0 {
+                continue;
+            }
+            total_bytes += size;
+            let mut entry = ScanEntry::new(install.path.clone(), size);
+            let what = format!(
+                "{} {} {}, not a default or pinned by any project",
+                install.manager, install.tool, install.version
+            );
+            // Unpinned virtualenvs would go with it, so it's left unselected
+            let envs = pyenv_envs(install);
+            entry.keep_by_default = !envs.is_empty();
+            entry.detail = Some(if envs.is_empty() {
+                what
+            } else {
+                format!("{what}; holds virtualenvs {}", envs.join(", "))
+            });
+            entries.push(entry);
+        }
+
+        // A running interpreter holds its own binary open
+        InUseSnapshot::capture().mark_entries(&mut entries);
+
+        entries.sort_by_key(|e| Reverse(e.size_bytes));
+
+        ScanResult {
+            entries,
+            total_bytes,
+            errors,
+            groups: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn install(tool: &str, version: &str) -> Install {
+        Install {
+            manager: "test",
+            tool: tool.to_string(),
+            version: version.to_string(),
+            path: PathBuf::from(version),
+        }
+    }
+
+    #[test]
+    fn specs_resolve_to_the_newest_matching_install() {
+        let node = [install("node", "18.2.0"), install("node", "18.17.0"), install("node", "20.11.0")];
+        let node: Vec<&Install> = node.iter().collect();
+        let resolved = |spec: &str| match resolve(spec, &node) {
+            Resolved::Install(i) => Some(i.version.as_str()),
+            Resolved::Missing => None,
+            Resolved::Unknown => Some("?"),
+        };
+        assert_eq!(resolved("v18.2.0"), Some("18.2.0"));
+        assert_eq!(resolved("18"), Some("18.17.0"));
+        assert_eq!(resolved("latest"), Some("20.11.0"));
+        assert_eq!(resolved("16"), None);
+        assert_eq!(resolved("lts/*"), Some("?"));
+        assert_eq!(resolved("system"), Some("?"));
+
+        let rust = [
+            install("rust", "stable-aarch64-apple-darwin"),
+            install("rust", "1.75.0-x86_64-apple-darwin"),
+            install("rust", "nightly-2024-01-01-aarch64-apple-darwin"),
+        ];
+        let rust: Vec<&Install> = rust.iter().collect();
+        let resolved = |spec: &str| match resolve(spec, &rust) {
+            Resolved::Install(i) => Some(i.version.as_str()),
+            _ => None,
+        };
+        assert_eq!(resolved("stable"), Some("stable-aarch64-apple-darwin"));
+        assert_eq!(resolved("1.75"), Some("1.75.0-x86_64-apple-darwin"));
+        assert_eq!(resolved("nightly-2024-01-01"), Some("nightly-2024-01-01-aarch64-apple-darwin"));
+    }
+
+    #[test]
+    fn version_files_are_parsed() {
+        assert_eq!(
+            parse_tool_versions("nodejs 18.17.0 16.20.0 # two\npython 3.11.4\n"),
+            [
+                ("node".to_string(), "18.17.0".to_string()),
+                ("node".to_string(), "16.20.0".to_string()),
+                ("python".to_string(), "3.11.4".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_mise_toml("[env]\nA = \"1\"\n[tools]\nnode = \"20\"\npython = [\"3.12\", \"3.11\"]\n"),
+            [
+                ("node".to_string(), "20".to_string()),
+                ("python".to_string(), "3.12".to_string()),
+                ("python".to_string(), "3.11".to_string()),
+            ]
+        );
+    }
+}
@@ -15,29 +15,35 @@ const VALID_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tiff", "gif", "mov",
 
 pub struct Screenshots;
 
-/// Cached screenshot directory — only runs `defaults read` once per process.
-static SCREENSHOT_DIR: OnceLock<PathBuf> = OnceLock::new();
-
-fn get_screenshot_dir() -> &'static PathBuf {
-    SCREENSHOT_DIR.get_or_init(|| {
-        // Check if user has a custom screenshot location
-        if let Ok(output) = std::process::Command::new("defaults")
-            .args(["read", "com.apple.screencapture", "location"])
-            .output()
-        {
-            if output.status.success() {
-                let location = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                if !location.is_empty() {
-                    let path = PathBuf::from(&location);
-                    if path.exists() {
-                        return path;
-                    }
-                }
+/// Cached custom screenshot location — only runs `defaults read` once per process.
+static CUSTOM_LOCATION: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+fn custom_location() -> Option<&'static PathBuf> {
+    CUSTOM_LOCATION
+        .get_or_init(|| {
+            let output = std::process::Command::new("defaults")
+                .args(["read", "com.apple.screencapture", "location"])
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                return None;
             }
+            let location = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            let path = PathBuf::from(&location);
+            (!location.is_empty() && path.exists()).then_some(path)
+        })
+        .as_ref()
+}
+
+fn get_screenshot_dir() -> PathBuf {
+    // A fixture root stands in for the whole system, user defaults included
+    if utils::root_override().is_none() {
+        if let Some(custom) = custom_location() {
+            return custom.clone();
         }
-        // Default: ~/Desktop
-        utils::home_dir().join("Desktop")
-    })
+    }
+    // Default: ~/Desktop
+    utils::home_dir().join("Desktop")
 }
 
 fn is_screenshot(name: &str) -> bool {
@@ -80,18 +86,20 @@ impl Cleaner for Screenshots {
                 entries,
                 total_bytes,
                 errors,
+                groups: Vec::new(),
             };
         }
 
         let max_age = Duration::from_secs(MAX_AGE_DAYS * 24 * 60 * 60);
 
-        let dir_entries = match std::fs::read_dir(screenshot_dir) {
+        let dir_entries = match std::fs::read_dir(&screenshot_dir) {
             Ok(rd) => rd,
             Err(_) => {
                 return ScanResult {
                     entries,
                     total_bytes,
                     errors,
+                    groups: Vec::new(),
                 }
             }
         };
@@ -120,46 +128,16 @@ impl Cleaner for Screenshots {
 
             let size = metadata.len();
             total_bytes += size;
-            entries.push(ScanEntry {
-                path,
-                size_bytes: size,
-            });
+            entries.push(ScanEntry::new(path, size));
         }
 
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
 
         ScanResult {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match std::fs::remove_file(&entry.path) {
-                Ok(()) => {
-                    total_freed += entry.size_bytes;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
-    }
 }
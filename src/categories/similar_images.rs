@@ -0,0 +1,336 @@
+use super::duplicates::{full_hash, should_skip_dir, KeepRule};
+use crate::cleaner::{Cleaner, DuplicateGroup, ImageInfo, ScanEntry, ScanResult};
+use crate::hash_cache::HashCache;
+use crate::progress;
+use crate::throttle;
+use crate::utils;
+use image::imageops::FilterType;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use walkdir::WalkDir;
+
+/// Skip icons and tiny web assets.
+const MIN_SIZE: u64 = 4096;
+
+/// Maximum walk depth.
+const MAX_DEPTH: usize = 8;
+
+/// Decodable formats (HEIC is not supported by the decoder).
+const EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "tif", "tiff", "bmp"];
+
+/// Preview size on the long side, in pixels.
+const THUMB_SIZE: u32 = 64;
+
+/// Aspect ratios further apart than this never match (crops, rotations).
+const MAX_ASPECT_DIFF: f64 = 0.1;
+
+/// Hamming distance between 64-bit hashes at or below which two images are
+/// considered the same picture. Re-encodes and resizes land around 0–5.
+pub const DEFAULT_MAX_DISTANCE: u32 = 6;
+
+/// Largest distance offered in settings; beyond it unrelated images match.
+pub const MAX_MAX_DISTANCE: u32 = 16;
+
+/// Distance used by scans started from the UI.
+static MAX_DISTANCE: AtomicU32 = AtomicU32::new(DEFAULT_MAX_DISTANCE);
+
+pub fn similarity_distance() -> u32 {
+    MAX_DISTANCE.load(Ordering::Relaxed)
+}
+
+pub fn set_similarity_distance(distance: u32) {
+    MAX_DISTANCE.store(distance.min(MAX_MAX_DISTANCE), Ordering::Relaxed);
+}
+
+/// Near-duplicate photos: resized, re-encoded or re-exported versions of the
+/// same picture, found by perceptual hash.
+pub struct SimilarImages {
+    max_distance: u32,
+    rule: KeepRule,
+}
+
+impl SimilarImages {
+    pub fn new(max_distance: u32, rule: KeepRule) -> Self {
+        Self { max_distance, rule }
+    }
+}
+
+struct Candidate {
+    path: PathBuf,
+    size: u64,
+    width: u32,
+    height: u32,
+    dhash: u64,
+}
+
+impl Candidate {
+    fn aspect(&self) -> f64 {
+        self.width as f64 / self.height.max(1) as f64
+    }
+
+    fn matches(&self, other: &Candidate, max_distance: u32) -> bool {
+        let (a, b) = (self.aspect(), other.aspect());
+        (self.dhash ^ other.dhash).count_ones() <= max_distance
+            && (a - b).abs() / a.max(b) <= MAX_ASPECT_DIFF
+    }
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| EXTENSIONS.contains(&e.as_str()))
+}
+
+fn decode(path: &Path) -> Option<image::DynamicImage> {
+    image::ImageReader::open(path)
+        .ok()?
+        .with_guessed_format()
+        .ok()?
+        .decode()
+        .ok()
+}
+
+/// Difference hash: shrink to 9x8 greyscale and record whether each pixel is
+/// brighter than its right neighbour. Survives scaling and recompression.
+fn dhash(img: &image::DynamicImage) -> u64 {
+    let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+fn analyze(path: PathBuf, size: u64) -> Option<Candidate> {
+    throttle::consume_io(size);
+    let img = decode(&path);
+    progress::bytes_done(size);
+    let img = img?;
+    Some(Candidate {
+        dhash: dhash(&img),
+        width: img.width(),
+        height: img.height(),
+        path,
+        size,
+    })
+}
+
+/// Decodes the image again for its preview, so only reported images ever
+/// hold one in memory.
+fn image_info(candidate: &Candidate) -> ImageInfo {
+    let thumb = decode(&candidate.path)
+        .map(|img| img.thumbnail(THUMB_SIZE, THUMB_SIZE).to_rgba8());
+    ImageInfo {
+        width: candidate.width,
+        height: candidate.height,
+        thumb_size: thumb
+            .as_ref()
+            .map_or([0, 0], |t| [t.width() as usize, t.height() as usize]),
+        thumb_rgba: thumb.map(|t| t.into_raw()).unwrap_or_default(),
+    }
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Union-find clusters of candidates within `max_distance` of each other.
+/// Pairwise, which is fine for the few thousand photos in home folders.
+fn cluster(candidates: &[Candidate], max_distance: u32) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..candidates.len()).collect();
+    for i in 0..candidates.len() {
+        for j in i + 1..candidates.len() {
+            if candidates[i].matches(&candidates[j], max_distance) {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                parent[a] = b;
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..candidates.len() {
+        let root = find(&mut parent, i);
+        clusters.entry(root).or_default().push(i);
+    }
+    clusters.into_values().filter(|c| c.len() >= 2).collect()
+}
+
+/// Byte-identical members are the duplicate finder's business: keep one of
+/// each so the two categories can never delete every copy between them.
+fn drop_exact_copies(members: &mut Vec<usize>, candidates: &[Candidate], cache: &HashCache) {
+    members.sort_by(|&a, &b| candidates[a].path.cmp(&candidates[b].path));
+    let mut by_size: HashMap<u64, usize> = HashMap::new();
+    for &i in members.iter() {
+        *by_size.entry(candidates[i].size).or_default() += 1;
+    }
+    let mut seen = Vec::new();
+    members.retain(|&i| {
+        if by_size[&candidates[i].size] < 2 {
+            return true;
+        }
+        let Some(hash) = full_hash(&candidates[i].path, cache) else {
+            return false;
+        };
+        if seen.contains(&hash) {
+            return false;
+        }
+        seen.push(hash);
+        true
+    });
+}
+
+impl Cleaner for SimilarImages {
+    fn name(&self) -> &'static str {
+        "similar-images"
+    }
+
+    fn label(&self) -> &'static str {
+        "Similar Images"
+    }
+
+    fn scan(&self) -> ScanResult {
+        let mut entries = Vec::new();
+        let mut total_bytes = 0u64;
+        let mut errors = Vec::new();
+
+        let home = utils::home_dir();
+        let dirs_to_scan = [
+            home.join("Pictures"),
+            home.join("Downloads"),
+            home.join("Desktop"),
+            home.join("Documents"),
+        ];
+
+        let mut files: Vec<(PathBuf, u64)> = Vec::new();
+        for dir in &dirs_to_scan {
+            if !dir.exists() {
+                continue;
+            }
+            for entry in WalkDir::new(dir)
+                .max_depth(MAX_DEPTH)
+                .follow_links(false)
+                .into_iter()
+                .filter_entry(|e| {
+                    if e.file_type().is_dir() {
+                        let name = e.file_name().to_string_lossy();
+                        return !should_skip_dir(&name);
+                    }
+                    true
+                })
+                .filter_map(|e| e.ok())
+                .inspect(|_| progress::file_visited())
+            {
+                if !entry.file_type().is_file() || !is_image(entry.path()) {
+                    continue;
+                }
+                let size = match entry.metadata() {
+                    Ok(m) => m.len(),
+                    Err(_) => continue,
+                };
+                if size >= MIN_SIZE {
+                    files.push((entry.into_path(), size));
+                }
+            }
+        }
+
+        // Every image is decoded once; files that fail to decode are skipped
+        progress::expect_bytes(files.iter().map(|(_, size)| size).sum());
+        let pool = throttle::hashing_pool();
+        let candidates: Vec<Candidate> = pool.install(|| {
+            files
+                .into_par_iter()
+                .filter_map(|(path, size)| analyze(path, size))
+                .collect()
+        });
+
+        let cache = HashCache::open();
+        let mut groups = Vec::new();
+        // Candidate behind each entry, for its preview
+        let mut shown = Vec::new();
+        for mut members in cluster(&candidates, self.max_distance) {
+            drop_exact_copies(&mut members, &candidates, &cache);
+
+            // Keep the sharpest copy: most pixels, then the least compressed
+            let Some(&best) = members.iter().max_by(|&&a, &&b| {
+                let (a, b) = (&candidates[a], &candidates[b]);
+                (a.width as u64 * a.height as u64, a.size)
+                    .cmp(&(b.width as u64 * b.height as u64, b.size))
+                    .then_with(|| b.path.cmp(&a.path))
+            }) else {
+                continue;
+            };
+            members.retain(|&i| i == best || !self.rule.is_protected(&candidates[i].path));
+            if members.len() < 2 {
+                continue;
+            }
+            // Original first, then largest
+            members.sort_by_key(|&i| (i != best, std::cmp::Reverse(candidates[i].size)));
+
+            let original = candidates[best].path.clone();
+            for &i in &members {
+                let c = &candidates[i];
+                let mut entry = ScanEntry::new(c.path.clone(), c.size);
+                if i != best {
+                    total_bytes += c.size;
+                    entry.duplicate_of = Some(original.clone());
+                }
+                entries.push(entry);
+                shown.push(i);
+            }
+            groups.push(DuplicateGroup {
+                hash: format!("{:016x}", candidates[best].dhash),
+                size_bytes: candidates[best].size,
+                members: members.iter().map(|&i| candidates[i].path.clone()).collect(),
+                original,
+            });
+        }
+
+        let previews: Vec<ImageInfo> =
+            pool.install(|| shown.par_iter().map(|&i| image_info(&candidates[i])).collect());
+        for (entry, preview) in entries.iter_mut().zip(previews) {
+            entry.image = Some(preview);
+        }
+
+        if let Err(e) = cache.save() {
+            errors.push(format!("Cannot save hash cache: {e}"));
+        }
+
+        // Biggest savings first; members of a group stay together
+        let savings = |g: &DuplicateGroup| -> u64 {
+            entries
+                .iter()
+                .filter(|e| g.members.contains(&e.path) && e.path != g.original)
+                .map(|e| e.size_bytes)
+                .sum()
+        };
+        let mut keyed: Vec<(u64, DuplicateGroup)> =
+            groups.into_iter().map(|g| (savings(&g), g)).collect();
+        keyed.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.original.cmp(&b.1.original)));
+        let groups: Vec<DuplicateGroup> = keyed.into_iter().map(|(_, g)| g).collect();
+
+        let order: HashMap<&Path, usize> = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(i, g)| g.members.iter().map(move |m| (m.as_path(), i)))
+            .collect();
+        entries.sort_by_key(|e| order[e.path.as_path()]);
+
+        ScanResult {
+            entries,
+            total_bytes,
+            errors,
+            groups,
+        }
+    }
+}
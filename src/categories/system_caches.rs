@@ -1,4 +1,5 @@
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
+use crate::in_use::InUseSnapshot;
 use crate::utils;
 
 /// Directories handled by other cleaners — excluded to avoid double-counting.
@@ -9,6 +10,12 @@ const EXCLUDED_SUBDIRS: &[&str] = &[
     "com.apple.Safari",
     "Yarn",
     "pip",
+    "deno",
+    "pypoetry",
+    "CocoaPods",
+    "org.swift.swiftpm",
+    "go-build",
+    "composer",
 ];
 
 pub struct SystemCaches;
@@ -22,6 +29,10 @@ impl Cleaner for SystemCaches {
         "System Caches"
     }
 
+    fn skips_in_use(&self) -> bool {
+        true
+    }
+
     fn scan(&self) -> ScanResult {
         let cache_dir = utils::home_dir().join("Library/Caches");
         let mut entries = Vec::new();
@@ -34,6 +45,7 @@ impl Cleaner for SystemCaches {
                 entries,
                 total_bytes,
                 errors,
+                groups: Vec::new(),
             };
         }
 
@@ -51,10 +63,7 @@ impl Cleaner for SystemCaches {
 
                     let size = utils::entry_size(&path);
                     total_bytes += size;
-                    entries.push(ScanEntry {
-                        path,
-                        size_bytes: size,
-                    });
+                    entries.push(ScanEntry::new(path, size));
                 }
             }
             Err(e) => {
@@ -62,42 +71,17 @@ impl Cleaner for SystemCaches {
             }
         }
 
+        InUseSnapshot::capture().mark_entries(&mut entries);
+
         // Sort by size descending for readability
-        entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.size_bytes));
 
         ScanResult {
 
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         }
     }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        let mut result = self.scan();
-        if dry_run {
-            return result;
-        }
-
-        let mut cleaned_entries = Vec::new();
-        let mut total_freed = 0u64;
-
-        for entry in result.entries.drain(..) {
-            match utils::safe_remove(&entry.path) {
-                Ok(freed) => {
-                    total_freed += freed;
-                    cleaned_entries.push(entry);
-                }
-                Err(e) => {
-                    result
-                        .errors
-                        .push(format!("Failed to remove {}: {e}", entry.path.display()));
-                }
-            }
-        }
-
-        result.entries = cleaned_entries;
-        result.total_bytes = total_freed;
-        result
-    }
 }
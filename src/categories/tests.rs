@@ -0,0 +1,1345 @@
+use super::*;
+use crate::test_support::{self, Fixture, MB};
+
+fn cleaner(name: &str) -> Box<dyn Cleaner> {
+    find_cleaner(name, 10 * MB, None).unwrap_or_else(|| panic!("no cleaner named {name}"))
+}
+
+/// Scan, check entries and total, clean, then check that exactly the
+/// reported entries are gone.
+fn assert_scan_and_clean(fx: &Fixture, name: &str, expected: &[&str], total: u64) {
+    let c = cleaner(name);
+    let scanned = c.scan();
+    assert_eq!(fx.rel_paths(&scanned), expected, "{name} entries");
+    assert_eq!(scanned.total_bytes, total, "{name} total");
+
+    let cleaned = clean(&*c);
+    assert_eq!(fx.rel_paths(&cleaned), expected, "{name} cleaned entries");
+    for rel in expected {
+        let path = fx.root().join(rel);
+        assert!(path.symlink_metadata().is_err(), "{name} left {rel} behind");
+    }
+    assert!(c.scan().entries.is_empty(), "{name} finds entries after clean");
+}
+
+/// Remove what a scan selects by default, the way the app cleans a
+/// category, returning the removed entries and any errors.
+fn clean(c: &dyn Cleaner) -> crate::cleaner::ScanResult {
+    let mut result = c.scan();
+    let selected = result.default_selection();
+    let mut removed = Vec::new();
+    let mut total_freed = 0u64;
+    for (entry, sel) in result.entries.drain(..).zip(selected) {
+        if !sel {
+            continue;
+        }
+        match c.remove(&entry.path) {
+            Ok(freed) => {
+                total_freed += freed;
+                removed.push(entry);
+            }
+            Err(e) => result
+                .errors
+                .push(format!("Failed to remove {}: {e}", entry.path.display())),
+        }
+    }
+    result.entries = removed;
+    result.total_bytes = total_freed;
+    result
+}
+
+/// The entry for `path` in a scan result.
+fn entry_at<'a>(result: &'a crate::cleaner::ScanResult, path: &std::path::Path) -> &'a crate::cleaner::ScanEntry {
+    result
+        .entries
+        .iter()
+        .find(|e| e.path == path)
+        .unwrap_or_else(|| panic!("no entry for {}", path.display()))
+}
+
+/// The detail line of the entry for `path`, empty if it has none.
+fn detail_of(result: &crate::cleaner::ScanResult, path: &std::path::Path) -> String {
+    entry_at(result, path).detail.clone().unwrap_or_default()
+}
+
+#[test]
+fn every_cleaner_stays_inside_the_fixture() {
+    let fx = test_support::populated();
+    let before = fs_listing(fx.root());
+    // Scans may only write TidyMac's own state (the hash cache)
+    let app_data = crate::utils::app_data_dir();
+
+    for c in all_cleaners(10 * MB, None) {
+        let result = c.scan();
+        // rel_paths panics on any entry outside the fixture root
+        fx.rel_paths(&result);
+    }
+
+    let mut after = fs_listing(fx.root());
+    after.retain(|p| !p.starts_with(&app_data) && !app_data.starts_with(p) || before.contains(p));
+    assert_eq!(after, before, "a scan modified the tree");
+}
+
+fn fs_listing(root: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut paths: Vec<_> = walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn system_caches_skip_folders_owned_by_other_cleaners() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "system-caches",
+        &[
+            "home/Library/Caches/com.example.editor",
+            "home/Library/Caches/com.example.player",
+        ],
+        4096 + 8192 + 2048,
+    );
+    assert!(fx.path("Library/Caches/Homebrew").exists());
+    assert!(fx.path("Library/Caches/Google/Chrome").exists());
+}
+
+#[test]
+fn system_caches_leave_package_manager_caches_to_their_category() {
+    let fx = Fixture::new();
+    fx.file("Library/Caches/com.example.app/cache.db", 100);
+    for tool in ["deno", "pypoetry", "CocoaPods", "org.swift.swiftpm", "go-build", "composer"] {
+        fx.file(&format!("Library/Caches/{tool}/blob"), 200);
+    }
+
+    assert_scan_and_clean(&fx, "system-caches", &["home/Library/Caches/com.example.app"], 100);
+    let claimed = cleaner("package-managers").scan();
+    assert_eq!(claimed.entries.len(), 6);
+    assert_eq!(claimed.total_bytes, 6 * 200);
+}
+
+#[test]
+fn app_logs() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "app-logs",
+        &[
+            "Library/Logs/install.log",
+            "home/Library/Logs/DiagnosticReports",
+            "home/Library/Logs/com.example.editor",
+        ],
+        1500 + 2500 + 900,
+    );
+}
+
+#[test]
+fn browser_caches() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "browser-caches",
+        &[
+            "home/Library/Caches/Firefox/Profiles/abcd.default/cache2",
+            "home/Library/Caches/Google/Chrome/Default/Cache",
+            "home/Library/Caches/Google/Chrome/Profile 1/Code Cache",
+            "home/Library/Caches/com.apple.Safari",
+        ],
+        5000 + 3000 + 7000 + 6000,
+    );
+    assert!(fx.path("Library/Caches/Google/Chrome/Default").exists());
+}
+
+#[test]
+fn xcode_folders() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "xcode",
+        &["home/Library/Developer/Xcode/DerivedData/App-abcdef"],
+        2 * MB,
+    );
+    // The only OS version, and an archive or device that can't be read, are kept
+    for (name, rel) in [
+        ("xcode-device-support", "home/Library/Developer/Xcode/iOS DeviceSupport/17.2 (21C62)"),
+        ("xcode-archives", "home/Library/Developer/Xcode/Archives/2024-01-02/App.xcarchive"),
+        (
+            "core-simulator",
+            "home/Library/Developer/CoreSimulator/Devices/5A1C0F2E-0000-4000-8000-000000000001",
+        ),
+    ] {
+        let scanned = cleaner(name).scan();
+        assert_eq!(fx.rel_paths(&scanned), [rel], "{name} entries");
+        assert!(scanned.entries[0].keep_by_default, "{name} keeps {rel}");
+        assert!(clean(&*cleaner(name)).entries.is_empty(), "{name} cleaned");
+        assert!(fx.root().join(rel).exists());
+    }
+}
+
+#[test]
+fn homebrew_cache() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "homebrew",
+        &[
+            "home/Library/Caches/Homebrew/downloads",
+            "home/Library/Caches/Homebrew/wget-1.21.tar.gz",
+        ],
+        4 * MB,
+    );
+}
+
+#[test]
+fn package_manager_caches() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "package-managers",
+        &[
+            "home/.cargo/registry/cache",
+            "home/.npm/_cacache",
+            "home/Library/Caches/Yarn",
+            "home/Library/Caches/pip",
+        ],
+        4000 + 5000 + 3000 + 6000,
+    );
+}
+
+#[test]
+fn package_manager_caches_name_their_tool() {
+    let fx = Fixture::new();
+    fx.file("Library/pnpm/store/v3/files/00/abc", 1000);
+    fx.file(".gradle/caches/modules-2/junit.jar", 2000);
+    fx.file(".m2/repository/org/junit/junit.jar", 3000);
+    fx.file(".gem/ruby/3.2.0/cache/rake.gem", 400);
+    fx.file(".gem/ruby/3.3.0/cache/rails.gem", 500);
+    fx.file(".cargo/git/checkouts/serde-1a2b/src/lib.rs", 600);
+    // Go writes its module cache read-only
+    let module = fx.file("go/pkg/mod/golang.org/x/text@v0.14.0/doc.go", 700);
+    for dir in module.ancestors().skip(1).take(4) {
+        std::fs::set_permissions(dir, std::os::unix::fs::PermissionsExt::from_mode(0o555)).unwrap();
+    }
+    // Installed gems themselves are not a cache
+    fx.file(".gem/ruby/3.3.0/gems/rails/lib.rb", 800);
+
+    let result = cleaner("package-managers").scan();
+    let tool = |rel: &str| detail_of(&result, &fx.path(rel));
+    assert_eq!(tool("go/pkg/mod"), "Go modules");
+    assert_eq!(tool(".gem/ruby/3.3.0/cache"), "RubyGems");
+    assert_eq!(tool(".cargo/git/checkouts"), "cargo git");
+
+    assert_scan_and_clean(
+        &fx,
+        "package-managers",
+        &[
+            "home/.cargo/git/checkouts",
+            "home/.gem/ruby/3.2.0/cache",
+            "home/.gem/ruby/3.3.0/cache",
+            "home/.gradle/caches",
+            "home/.m2/repository",
+            "home/Library/pnpm/store",
+            "home/go/pkg/mod",
+        ],
+        1000 + 2000 + 3000 + 400 + 500 + 600 + 700,
+    );
+    assert!(fx.path(".gem/ruby/3.3.0/gems/rails/lib.rb").exists());
+}
+
+#[test]
+fn runtime_versions_keep_defaults_and_pinned_versions() {
+    let fx = Fixture::new();
+    // nvm: default 20, a project pins 18
+    fx.file(".nvm/versions/node/v16.20.2/bin/node", 1000);
+    fx.file(".nvm/versions/node/v18.17.0/bin/node", 1000);
+    fx.file(".nvm/versions/node/v20.11.0/bin/node", 1000);
+    fx.file_with(".nvm/alias/default", b"20\n");
+    fx.file_with("Code/api/.nvmrc", b"v18\n");
+    // pyenv: a project uses a virtualenv made from 3.11
+    fx.file(".pyenv/versions/3.9.18/bin/python", 2000);
+    let env = fx.file(".pyenv/versions/3.11.4/envs/tool-env/bin/python", 2000);
+    fx.file(".pyenv/versions/3.12.1/bin/python", 2000);
+    fx.symlink(".pyenv/versions/tool-env", env.parent().unwrap().parent().unwrap());
+    fx.file_with(".pyenv/version", b"3.12.1\n");
+    fx.file_with("Code/tool/.python-version", b"tool-env\n");
+    // rustup: default stable, a project pins 1.75
+    fx.file(".rustup/toolchains/stable-aarch64-apple-darwin/bin/rustc", 3000);
+    fx.file(".rustup/toolchains/1.70.0-aarch64-apple-darwin/bin/rustc", 3000);
+    fx.file(".rustup/toolchains/1.75.0-aarch64-apple-darwin/bin/rustc", 3000);
+    fx.file_with(
+        ".rustup/settings.toml",
+        b"default_toolchain = \"stable-aarch64-apple-darwin\"\n",
+    );
+    fx.file_with("Code/cli/rust-toolchain.toml", b"[toolchain]\nchannel = \"1.75\"\n");
+    // rbenv with no default: the only version stays
+    fx.file(".rbenv/versions/3.2.2/bin/ruby", 4000);
+    // Version files in build output don't count
+    fx.file_with("Code/api/node_modules/dep/.nvmrc", b"16\n");
+
+    let result = cleaner("runtime-versions").scan();
+    assert_eq!(
+        detail_of(&result, &fx.path(".nvm/versions/node/v16.20.2")),
+        "nvm node 16.20.2, not a default or pinned by any project"
+    );
+
+    assert_scan_and_clean(
+        &fx,
+        "runtime-versions",
+        &[
+            "home/.nvm/versions/node/v16.20.2",
+            "home/.pyenv/versions/3.9.18",
+            "home/.rustup/toolchains/1.70.0-aarch64-apple-darwin",
+        ],
+        1000 + 2000 + 3000,
+    );
+    assert!(fx.path(".pyenv/versions/3.11.4").exists());
+}
+
+#[test]
+fn runtime_versions_follow_nvm_aliases_and_keep_virtualenv_bases() {
+    let fx = Fixture::new();
+    // nvm: default is the newest LTS, through two alias files
+    fx.file(".nvm/versions/node/v16.20.2/bin/node", 1000);
+    fx.file(".nvm/versions/node/v18.17.0/bin/node", 1000);
+    fx.file(".nvm/versions/node/v20.11.0/bin/node", 1000);
+    fx.file_with(".nvm/alias/default", b"lts/*\n");
+    fx.file_with(".nvm/alias/lts/*", b"lts/hydrogen\n");
+    fx.file_with(".nvm/alias/lts/hydrogen", b"v18.17.0\n");
+    // pyenv: 3.9 is no default but holds an unpinned virtualenv
+    fx.file(".pyenv/versions/3.9.18/bin/python", 2000);
+    fx.file(".pyenv/versions/3.9.18/envs/scraper/bin/python", 500);
+    fx.file(".pyenv/versions/3.12.1/bin/python", 2000);
+    fx.file_with(".pyenv/version", b"3.12.1\n");
+
+    let result = cleaner("runtime-versions").scan();
+    assert_eq!(
+        fx.rel_paths(&result),
+        [
+            "home/.nvm/versions/node/v16.20.2",
+            "home/.nvm/versions/node/v20.11.0",
+            "home/.pyenv/versions/3.9.18",
+        ]
+    );
+    let base = fx.path(".pyenv/versions/3.9.18");
+    assert!(entry_at(&result, &base).keep_by_default);
+    assert_eq!(
+        detail_of(&result, &base),
+        "pyenv python 3.9.18, not a default or pinned by any project; holds virtualenvs scraper"
+    );
+
+    clean(&*cleaner("runtime-versions"));
+    assert!(base.exists());
+    assert!(fx.path(".nvm/versions/node/v18.17.0").exists());
+    assert!(!fx.path(".nvm/versions/node/v20.11.0").exists());
+}
+
+#[test]
+fn runtime_versions_keep_everything_behind_an_unknown_alias() {
+    let fx = Fixture::new();
+    fx.file(".nvm/versions/node/v16.20.2/bin/node", 1000);
+    fx.file(".nvm/versions/node/v20.11.0/bin/node", 1000);
+    // No alias file says which version "work" is
+    fx.file_with(".nvm/alias/default", b"work\n");
+
+    assert!(cleaner("runtime-versions").scan().entries.is_empty());
+}
+
+#[test]
+fn runtime_versions_keep_everything_behind_a_name_that_is_not_a_version() {
+    let fx = Fixture::new();
+    // pyenv's default is the system Python
+    fx.file(".pyenv/versions/3.9.18/bin/python", 1000);
+    fx.file(".pyenv/versions/3.12.1/bin/python", 1000);
+    fx.file_with(".pyenv/version", b"system\n");
+    // A project asks for whichever LTS release
+    fx.file(".asdf/installs/nodejs/18.17.0/bin/node", 1000);
+    fx.file(".asdf/installs/nodejs/20.11.0/bin/node", 1000);
+    fx.file_with("Code/api/.node-version", b"lts/*\n");
+
+    assert!(cleaner("runtime-versions").scan().entries.is_empty());
+}
+
+#[test]
+fn derived_data_selects_only_stale_projects() {
+    let fx = Fixture::new();
+    let dd = "Library/Developer/Xcode/DerivedData";
+    let info = |folder: &str, workspace: &std::path::Path, last: Option<&str>| {
+        let date = last
+            .map(|d| format!("<key>LastAccessedDate</key><date>{d}</date>"))
+            .unwrap_or_default();
+        let plist = format!(
+            "<?xml version=\"1.0\"?><plist version=\"1.0\"><dict>\
+             <key>WorkspacePath</key><string>{}</string>{date}</dict></plist>",
+            workspace.display()
+        );
+        fx.file(&format!("{dd}/{folder}/Build/out.o"), MB);
+        fx.file_with(&format!("{dd}/{folder}/info.plist"), plist.as_bytes())
+    };
+    // Built just now: kept
+    info("Active-aaaa", &fx.dir("Code/Active/Active.xcodeproj"), None);
+    // Not built for years
+    info("Dormant-bbbb", &fx.dir("Code/Dormant/Dormant.xcworkspace"), Some("2020-01-02T03:04:05Z"));
+    // Project deleted since
+    info("Gone-cccc", &fx.path("Code/Gone/Gone.xcodeproj"), None);
+    // No date in the plist, and the plist itself is old
+    let old = info("Quiet-dddd", &fx.dir("Code/Quiet/Quiet.xcodeproj"), None);
+    fx.age(&old, 400);
+    fx.file(&format!("{dd}/ModuleCache.noindex/Foundation.pcm"), MB);
+
+    let result = cleaner("xcode").scan();
+    let entry = |folder: &str| {
+        let path = fx.path(&format!("{dd}/{folder}"));
+        (detail_of(&result, &path), entry_at(&result, &path).keep_by_default)
+    };
+    assert_eq!(entry("Active-aaaa"), ("Active, built today".to_string(), true));
+    assert!(entry("Dormant-bbbb").0.starts_with("Dormant, last built "));
+    assert_eq!(entry("Gone-cccc"), ("Gone, project no longer exists".to_string(), false));
+    assert_eq!(entry("Quiet-dddd"), ("Quiet, last built 400 days ago".to_string(), false));
+    assert!(entry("ModuleCache.noindex").1);
+
+    let cleaned = clean(&*cleaner("xcode"));
+    assert_eq!(
+        fx.rel_paths(&cleaned),
+        [
+            format!("home/{dd}/Dormant-bbbb"),
+            format!("home/{dd}/Gone-cccc"),
+            format!("home/{dd}/Quiet-dddd"),
+        ]
+    );
+    assert!(fx.path(&format!("{dd}/Active-aaaa")).exists());
+    assert!(fx.path(&format!("{dd}/ModuleCache.noindex")).exists());
+}
+
+#[test]
+fn derived_data_has_its_own_staleness() {
+    let fx = Fixture::new();
+    let dd = "Library/Developer/Xcode/DerivedData/App-aaaa";
+    let plist = format!(
+        "<?xml version=\"1.0\"?><plist version=\"1.0\"><dict>\
+         <key>WorkspacePath</key><string>{}</string></dict></plist>",
+        fx.dir("Code/App/App.xcodeproj").display()
+    );
+    fx.file(&format!("{dd}/Build/out.o"), MB);
+    let info = fx.file_with(&format!("{dd}/info.plist"), plist.as_bytes());
+    fx.age(&info, 120);
+
+    let kept = |days| xcode::XcodeDerivedData::new(days).scan().entries[0].keep_by_default;
+    assert!(!kept(90));
+    assert!(kept(180));
+}
+
+#[test]
+fn device_support_keeps_the_two_newest_versions_per_platform() {
+    let fx = Fixture::new();
+    let xcode = "Library/Developer/Xcode";
+    for (platform, folder) in [
+        ("iOS", "16.4 (20E247)"),
+        ("iOS", "iPhone15,2 17.2 (21C62)"),
+        ("iOS", "17.2 (21C62)"),
+        ("iOS", "17.0.1 (21A340)"),
+        ("iOS", "Shared"),
+        ("watchOS", "9.1 (20S75)"),
+        ("watchOS", "10.2 (21S364)"),
+    ] {
+        fx.file(&format!("{xcode}/{platform} DeviceSupport/{folder}/Symbols/dyld"), 1000);
+    }
+
+    let result = cleaner("xcode-device-support").scan();
+    let listed: Vec<(String, String, bool)> = result
+        .entries
+        .iter()
+        .map(|e| {
+            let name = e.path.file_name().unwrap().to_string_lossy().to_string();
+            (e.heading.clone().unwrap(), name, e.keep_by_default)
+        })
+        .collect();
+    let row = |p: &str, n: &str, keep| (p.to_string(), n.to_string(), keep);
+    assert_eq!(listed.len(), 7);
+    assert_eq!(listed[0].0, "iOS");
+    assert!(listed.contains(&row("iOS", "iPhone15,2 17.2 (21C62)", true)));
+    assert!(listed.contains(&row("iOS", "17.2 (21C62)", true)));
+    assert!(listed.contains(&row("iOS", "17.0.1 (21A340)", true)));
+    assert!(listed.contains(&row("iOS", "16.4 (20E247)", false)));
+    assert!(listed.contains(&row("iOS", "Shared", true)));
+    assert!(listed.contains(&row("watchOS", "10.2 (21S364)", true)));
+    assert!(listed.contains(&row("watchOS", "9.1 (20S75)", true)));
+    let old = fx.path(&format!("{xcode}/iOS DeviceSupport/16.4 (20E247)"));
+    assert_eq!(detail_of(&result, &old), "iOS 16.4 (20E247), older than the 2 newest");
+
+    let cleaned = clean(&*cleaner("xcode-device-support"));
+    assert_eq!(
+        fx.rel_paths(&cleaned),
+        [format!("home/{xcode}/iOS DeviceSupport/16.4 (20E247)")]
+    );
+}
+
+#[test]
+fn archives_keep_the_latest_build_of_each_version() {
+    let fx = Fixture::new();
+    let archive = |rel: &str, app: &str, version: &str, build: &str, created: &str| {
+        let plist = format!(
+            "<?xml version=\"1.0\"?><plist version=\"1.0\"><dict>\
+             <key>Name</key><string>{app}</string>\
+             <key>CreationDate</key><date>{created}</date>\
+             <key>ApplicationProperties</key><dict>\
+             <key>CFBundleIdentifier</key><string>com.example.{app}</string>\
+             <key>CFBundleShortVersionString</key><string>{version}</string>\
+             <key>CFBundleVersion</key><string>{build}</string>\
+             </dict></dict></plist>"
+        );
+        let dir = format!("Library/Developer/Xcode/Archives/{rel}");
+        fx.file(&format!("{dir}/Products/app.bin"), 1000);
+        fx.file_with(&format!("{dir}/Info.plist"), plist.as_bytes());
+    };
+    archive("2024-03-01/Notes 3-1-24.xcarchive", "Notes", "1.2", "45", "2024-03-01T10:00:00Z");
+    archive("2024-03-04/Notes 3-4-24.xcarchive", "Notes", "1.2", "47", "2024-03-04T10:00:00Z");
+    archive("2024-02-01/Notes 2-1-24.xcarchive", "Notes", "1.1", "40", "2024-02-01T10:00:00Z");
+    archive("2024-03-02/Atlas 3-2-24.xcarchive", "Atlas", "2.0", "7", "2024-03-02T10:00:00Z");
+
+    let result = cleaner("xcode-archives").scan();
+    let listed: Vec<(String, String, bool)> = result
+        .entries
+        .iter()
+        .map(|e| {
+            let name = e.path.file_stem().unwrap().to_string_lossy().to_string();
+            (e.heading.clone().unwrap(), name, e.keep_by_default)
+        })
+        .collect();
+    let row = |app: &str, name: &str, keep| (app.to_string(), name.to_string(), keep);
+    // Listed by app, newest version and build first
+    assert_eq!(
+        listed,
+        [
+            row("Atlas", "Atlas 3-2-24", true),
+            row("Notes", "Notes 3-4-24", true),
+            row("Notes", "Notes 3-1-24", false),
+            row("Notes", "Notes 2-1-24", true),
+        ]
+    );
+    assert_eq!(
+        result.entries[2].detail.as_deref(),
+        Some("1.2 (45), archived 2024-03-01, superseded by build 47")
+    );
+
+    let cleaned = clean(&*cleaner("xcode-archives"));
+    assert_eq!(
+        fx.rel_paths(&cleaned),
+        ["home/Library/Developer/Xcode/Archives/2024-03-01/Notes 3-1-24.xcarchive"]
+    );
+}
+
+#[test]
+fn simulators_flag_orphaned_and_idle_devices() {
+    let fx = Fixture::new();
+    fx.file_with(
+        "/Library/Developer/CoreSimulator/Profiles/Runtimes/iOS 17.2.simruntime/Contents/Info.plist",
+        plist_with(&[("CFBundleIdentifier", "com.apple.CoreSimulator.SimRuntime.iOS-17-2")]).as_bytes(),
+    );
+    // Runtimes mounted from disk images are in CoreSimulator's image list
+    fx.file_with(
+        "/Library/Developer/CoreSimulator/Images/images.plist",
+        b"<?xml version=\"1.0\"?><plist version=\"1.0\"><dict><key>images</key><array><dict>\
+          <key>runtimeInfo</key><dict><key>bundleIdentifier</key>\
+          <string>com.apple.CoreSimulator.SimRuntime.watchOS-10-2</string></dict>\
+          </dict></array></dict></plist>",
+    );
+
+    let devices = "Library/Developer/CoreSimulator/Devices";
+    let device = |udid: &str, name: &str, runtime: &str, state: u64, booted: Option<&str>| {
+        let booted = booted
+            .map(|d| format!("<key>lastBootedAt</key><date>{d}</date>"))
+            .unwrap_or_default();
+        let plist = format!(
+            "<?xml version=\"1.0\"?><plist version=\"1.0\"><dict>\
+             <key>UDID</key><string>{udid}</string>\
+             <key>name</key><string>{name}</string>\
+             <key>runtime</key><string>com.apple.CoreSimulator.SimRuntime.{runtime}</string>\
+             <key>state</key><integer>{state}</integer>{booted}</dict></plist>"
+        );
+        fx.file(&format!("{devices}/{udid}/data/blob"), 1000);
+        fx.file_with(&format!("{devices}/{udid}/device.plist"), plist.as_bytes());
+    };
+    // Used just now (the data folder was written)
+    device("AAAA", "iPhone 15", "iOS-17-2", 1, None);
+    // Booted long ago
+    device("BBBB", "iPad Air", "iOS-17-2", 1, Some("2021-05-01T00:00:00Z"));
+    // Its runtime was removed
+    device("CCCC", "iPhone 11", "iOS-15-0", 1, None);
+    // Running right now, however old its last recorded boot
+    device("DDDD", "Apple Watch", "watchOS-10-2", 3, Some("2021-05-01T00:00:00Z"));
+    fx.file(&format!("{devices}/device_set.plist"), 300);
+
+    let result = cleaner("core-simulator").scan();
+    let listed: Vec<(String, String, bool)> = result
+        .entries
+        .iter()
+        .map(|e| (e.heading.clone().unwrap(), e.detail.clone().unwrap(), e.keep_by_default))
+        .collect();
+    let row = |os: &str, detail: &str, keep| (os.to_string(), detail.to_string(), keep);
+    // By platform, newest OS first
+    assert_eq!(listed[0].0, "iOS 17.2");
+    assert!(listed[0].1.starts_with("iPad Air (iOS 17.2), last booted "));
+    assert!(!listed[0].2);
+    assert_eq!(listed[1], row("iOS 17.2", "iPhone 15 (iOS 17.2), booted today", true));
+    assert_eq!(listed[2], row("iOS 15.0", "iPhone 11 (iOS 15.0), runtime no longer installed", false));
+    assert_eq!(listed[3], row("watchOS 10.2", "Apple Watch (watchOS 10.2), running", true));
+    assert_eq!(listed.len(), 4);
+
+    let cleaned = clean(&*cleaner("core-simulator"));
+    assert_eq!(
+        fx.rel_paths(&cleaned),
+        [format!("home/{devices}/BBBB"), format!("home/{devices}/CCCC")]
+    );
+    assert!(fx.path(&format!("{devices}/device_set.plist")).exists());
+}
+
+#[test]
+fn simulators_whose_runtime_cannot_be_checked_are_kept() {
+    let fx = Fixture::new();
+    // No image list: a runtime missing from disk may just be unmounted
+    let plist = "<?xml version=\"1.0\"?><plist version=\"1.0\"><dict>\
+                 <key>name</key><string>iPhone 11</string>\
+                 <key>runtime</key><string>com.apple.CoreSimulator.SimRuntime.iOS-15-0</string>\
+                 <key>state</key><integer>1</integer></dict></plist>";
+    let devices = "Library/Developer/CoreSimulator/Devices";
+    fx.file(&format!("{devices}/CCCC/data/blob"), 1000);
+    fx.file_with(&format!("{devices}/CCCC/device.plist"), plist.as_bytes());
+
+    let result = cleaner("core-simulator").scan();
+    let entry = entry_at(&result, &fx.path(&format!("{devices}/CCCC")));
+    assert_eq!(entry.detail.as_deref(), Some("iPhone 11 (iOS 15.0), runtime not found"));
+    assert!(entry.keep_by_default);
+}
+
+#[test]
+fn trash() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "trash",
+        &["home/.Trash/Project", "home/.Trash/old report.pdf"],
+        12_800,
+    );
+    assert!(fx.path(".Trash").is_dir());
+}
+
+#[test]
+fn ds_store_skips_library_and_dependency_dirs() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "ds-store",
+        &["home/Documents/.DS_Store", "home/Pictures/Trip/.DS_Store"],
+        2 * 6148,
+    );
+    assert!(fx.path("Projects/app/node_modules/.DS_Store").exists());
+}
+
+#[test]
+fn language_files_keep_english_and_base() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "language-files",
+        &[
+            "Applications/Editor.app/Contents/Resources/de.lproj",
+            "Applications/Editor.app/Contents/Resources/ja.lproj",
+        ],
+        700,
+    );
+    assert!(fx.path("/Applications/Editor.app/Contents/Resources/en.lproj").exists());
+    assert!(fx.path("/Applications/Editor.app/Contents/Resources/Base.lproj").exists());
+}
+
+#[test]
+fn duplicates_are_reported_as_groups() {
+    let fx = test_support::populated();
+    let c = cleaner("duplicates");
+    let result = c.scan();
+    assert_eq!(
+        fx.rel_paths(&result),
+        ["home/Downloads/IMG_0001 copy.jpg", "home/Pictures/IMG_0001.jpg"]
+    );
+    // Only the copy counts towards reclaimable space
+    assert_eq!(result.total_bytes, 2 * MB);
+
+    assert_eq!(result.groups.len(), 1);
+    let group = &result.groups[0];
+    assert_eq!(group.size_bytes, 2 * MB);
+    assert_eq!(group.hash.len(), 64);
+    assert_eq!(group.members.len(), 2);
+    assert!(group.members.contains(&group.original));
+
+    clean(&*c);
+    assert!(group.original.exists());
+    let pair = [fx.path("Pictures/IMG_0001.jpg"), fx.path("Downloads/IMG_0001 copy.jpg")];
+    assert_eq!(pair.iter().filter(|p| p.exists()).count(), 1);
+    assert!(fx.path("Desktop/IMG_0001 edited.jpg").exists());
+    assert!(fx.path("Documents/other.bin").exists());
+}
+
+#[test]
+fn duplicates_reuse_cached_hashes_until_a_file_changes() {
+    use crate::hash_cache::{FileKey, HashCache, HashKind};
+
+    let fx = test_support::populated();
+    cleaner("duplicates").scan();
+
+    // Poison the cache: claim other.bin hashes like the photo
+    let photo = test_support::content(1, 2 * MB as usize);
+    let other = fx.path("Documents/other.bin");
+    let cache = HashCache::open();
+    let key = FileKey::of(&other).unwrap();
+    cache.insert(key, HashKind::Partial, blake3::hash(&photo[..4096]));
+    cache.insert(key, HashKind::Full, blake3::hash(&photo));
+    cache.save().unwrap();
+
+    let result = cleaner("duplicates").scan();
+    assert!(result.groups[0].members.contains(&other), "cached hash was not used");
+
+    // Touching the file changes its key, so the real hash is computed again
+    fx.age(&other, 1);
+    let result = cleaner("duplicates").scan();
+    assert_eq!(result.groups.len(), 1);
+    assert!(!result.groups[0].members.contains(&other));
+}
+
+#[test]
+fn duplicate_selection_always_leaves_a_copy() {
+    let fx = test_support::populated();
+    fx.file_with("Documents/IMG_0001 (2).jpg", &test_support::content(1, 2 * MB as usize));
+    let mut result = cleaner("duplicates").scan();
+    assert_eq!(result.groups[0].members.len(), 3);
+
+    let mut selected = vec![true; result.entries.len()];
+    result.enforce_survivors(&mut selected);
+    let kept: Vec<_> = result
+        .entries
+        .iter()
+        .zip(&selected)
+        .filter(|(_, s)| !**s)
+        .map(|(e, _)| e.path.clone())
+        .collect();
+    assert_eq!(kept, [result.groups[0].original.clone()]);
+
+    // Picking another copy to keep moves the original
+    let other = fx.path("Documents/IMG_0001 (2).jpg");
+    result.set_original(0, &other);
+    assert!(result.is_original(&other));
+    let others: Vec<_> = result.entries.iter().filter(|e| e.path != other).collect();
+    assert!(others.iter().all(|e| e.duplicate_of.as_ref() == Some(&other)));
+
+    // Even if the original vanished, one remaining member is kept
+    let idx = result.entries.iter().position(|e| e.path == other).unwrap();
+    result.remove_entry(idx);
+    let mut selected = vec![true; result.entries.len()];
+    result.enforce_survivors(&mut selected);
+    assert_eq!(selected.iter().filter(|s| !**s).count(), 1);
+}
+
+#[test]
+fn privacy_data() {
+    let fx = test_support::populated();
+    let result = cleaner("privacy").scan();
+    assert!(!result.errors.is_empty(), "logout warning missing");
+    assert_scan_and_clean(
+        &fx,
+        "privacy",
+        &[
+            "home/Library/Application Support/Firefox/Profiles/abcd.default/places.sqlite",
+            "home/Library/Application Support/Google/Chrome/Default/History",
+            "home/Library/Cookies/Cookies.binarycookies",
+            "home/Library/Safari/History.db",
+        ],
+        9000 + 1000 + 4000 + 5000,
+    );
+}
+
+#[test]
+fn old_files_need_size_and_age() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "old-files",
+        &["home/Downloads/old-footage.mov"],
+        20 * MB,
+    );
+    assert!(fx.path("Downloads/recent-movie.mov").exists());
+    assert!(fx.path("Documents/small-old.txt").exists());
+}
+
+#[test]
+fn old_files_say_which_date_they_used() {
+    let fx = test_support::populated();
+    let result = cleaner("old-files").scan();
+    assert_eq!(fx.rel_paths(&result), ["home/Downloads/old-footage.mov"]);
+    let detail = result.entries[0].detail.as_deref().unwrap();
+    assert!(detail.starts_with("last accessed "), "{detail}");
+}
+
+#[test]
+fn old_files_leave_only_downloads_partials_to_their_category() {
+    let fx = Fixture::new();
+    fx.age(&fx.file("Downloads/disk.iso.part", 12 * MB), 400);
+    fx.age(&fx.file("Downloads/site.zip.download/site.zip", 12 * MB), 400);
+    // Same names outside ~/Downloads are ordinary old files
+    fx.age(&fx.file("Documents/Backups/disk.iso.part", 12 * MB), 400);
+
+    let result = cleaner("old-files").scan();
+    assert_eq!(fx.rel_paths(&result), ["home/Documents/Backups/disk.iso.part"]);
+}
+
+#[test]
+fn incomplete_downloads_skip_ones_still_in_progress() {
+    let fx = Fixture::new();
+    let stalled = fx.file("Downloads/movie.mkv.crdownload", 5 * MB);
+    fx.age(&stalled, 2);
+    let part = fx.file("Downloads/disk.iso.part", 3 * MB);
+    fx.age(&part, 1);
+    let placeholder = fx.file("Downloads/disk.iso", 0);
+    fx.age(&placeholder, 1);
+    let safari = fx.file("Downloads/report.pdf.download/report.pdf", 2 * MB);
+    fx.age(&safari, 3);
+    fx.age(&fx.file("Downloads/report.pdf.download/Info.plist", 100), 3);
+    age_dir(&fx.path("Downloads/report.pdf.download"), 3);
+    let temp = fx.file("Downloads/upload.tmp", 0);
+    fx.age(&temp, 5);
+    // Not partial downloads, or still being written
+    fx.age(&fx.file("Downloads/notes.tmp", 100), 5);
+    fx.age(&fx.file("Downloads/done.pdf", MB), 5);
+    fx.file("Downloads/active.zip.crdownload", 4 * MB);
+
+    assert_scan_and_clean(
+        &fx,
+        "incomplete-downloads",
+        &[
+            "home/Downloads/disk.iso",
+            "home/Downloads/disk.iso.part",
+            "home/Downloads/movie.mkv.crdownload",
+            "home/Downloads/report.pdf.download",
+            "home/Downloads/upload.tmp",
+        ],
+        10 * MB + 100,
+    );
+    assert!(fx.path("Downloads/active.zip.crdownload").exists());
+    assert!(fx.path("Downloads/done.pdf").exists());
+}
+
+/// Backdate a folder's modification time, as [`Fixture::age`] does for files.
+fn age_dir(path: &std::path::Path, days: u64) {
+    let when = std::time::SystemTime::now() - std::time::Duration::from_secs(days * 86400);
+    std::fs::File::open(path)
+        .and_then(|f| f.set_times(std::fs::FileTimes::new().set_modified(when)))
+        .unwrap();
+}
+
+#[test]
+fn installers_flag_installed_apps_and_old_downloads() {
+    let fx = Fixture::new();
+    fx.file_with(
+        "/Applications/Slack.app/Contents/Info.plist",
+        plist_with(&[("CFBundleIdentifier", "com.tinyspeck.slackmacgap"), ("CFBundleShortVersionString", "4.36.140")])
+            .as_bytes(),
+    );
+    fx.dir("/Applications/Utilities/Docker.app/Contents");
+    fx.dir("/Applications/zoom.us.app/Contents");
+    fx.dir("Applications/Tool Helper.app/Contents");
+    fx.dir("/Applications/Firefox.app/Contents");
+    fx.dir("/Applications/Python 3.12/Python Launcher.app/Contents");
+
+    fx.file("Downloads/Slack-4.36.140-macOS.dmg", 3 * MB);
+    fx.file("Downloads/Docker Desktop Installer.pkg", 2 * MB);
+    fx.file("Downloads/zoomusInstallerFull.pkg", MB);
+    fx.file_with(
+        "Downloads/Tool.zip",
+        &zip_listing(&["Tool Helper.app/", "Tool Helper.app/Contents/Info.plist"]),
+    );
+    let old = fx.file("Downloads/Unknown_2.1.dmg", MB);
+    fx.age(&old, 90);
+    // Too new to flag, not an app, or not in Downloads itself
+    fx.file("Downloads/NewThing.dmg", MB);
+    fx.file("Downloads/Firefox Developer Edition 128.0b3.dmg", MB);
+    fx.file("Downloads/python-3.12.1-macos11.pkg", MB);
+    fx.file_with("Downloads/photos.zip", &zip_listing(&["IMG_0001.jpg"]));
+    let nested = fx.file("Downloads/old/Slack-4.0.dmg", MB);
+    fx.age(&nested, 90);
+
+    let result = cleaner("installers").scan();
+
+    assert_eq!(
+        fx.rel_paths(&result),
+        [
+            "home/Downloads/Docker Desktop Installer.pkg",
+            "home/Downloads/Slack-4.36.140-macOS.dmg",
+            "home/Downloads/Tool.zip",
+            "home/Downloads/Unknown_2.1.dmg",
+            "home/Downloads/zoomusInstallerFull.pkg",
+        ]
+    );
+    let detail = |name: &str| detail_of(&result, &fx.path(&format!("Downloads/{name}")));
+    assert_eq!(detail("Slack-4.36.140-macOS.dmg"), "Slack 4.36.140 is installed");
+    assert_eq!(detail("Docker Desktop Installer.pkg"), "Docker is installed");
+    assert_eq!(detail("zoomusInstallerFull.pkg"), "zoom.us is installed");
+    assert_eq!(detail("Tool.zip"), "Tool Helper is installed");
+    assert_eq!(detail("Unknown_2.1.dmg"), "Unknown_2.1 not installed, downloaded 90 days ago");
+
+    // Old installers are this category's, not Old Files'
+    let big_old = fx.file("Downloads/Huge.dmg", 20 * MB);
+    fx.age(&big_old, 400);
+    assert!(cleaner("old-files").scan().entries.is_empty());
+}
+
+fn plist_with(pairs: &[(&str, &str)]) -> String {
+    let body: String = pairs
+        .iter()
+        .map(|(k, v)| format!("<key>{k}</key><string>{v}</string>"))
+        .collect();
+    format!("<?xml version=\"1.0\"?><plist version=\"1.0\"><dict>{body}</dict></plist>")
+}
+
+/// A zip holding empty, stored entries with these names.
+fn zip_listing(names: &[&str]) -> Vec<u8> {
+    let mut local = Vec::new();
+    let mut central = Vec::new();
+    for name in names {
+        let offset = local.len() as u32;
+        local.extend_from_slice(b"PK\x03\x04");
+        local.extend_from_slice(&[0u8; 22]);
+        local.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        local.extend_from_slice(&0u16.to_le_bytes());
+        local.extend_from_slice(name.as_bytes());
+
+        central.extend_from_slice(b"PK\x01\x02");
+        central.extend_from_slice(&[0u8; 24]);
+        central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        central.extend_from_slice(&[0u8; 12]);
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+    let mut zip = local;
+    let central_offset = zip.len() as u32;
+    zip.extend_from_slice(&central);
+    zip.extend_from_slice(b"PK\x05\x06");
+    zip.extend_from_slice(&[0u8; 4]);
+    zip.extend_from_slice(&(names.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(names.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    zip.extend_from_slice(&central_offset.to_le_bytes());
+    zip.extend_from_slice(&0u16.to_le_bytes());
+    zip
+}
+
+#[test]
+fn broken_symlinks_leave_live_links() {
+    let fx = test_support::populated();
+    let c = cleaner("broken-symlinks");
+    let result = c.scan();
+    assert_eq!(
+        fx.rel_paths(&result),
+        [
+            "home/Library/Application Support/Stale/link",
+            "usr/local/bin/oldtool",
+        ]
+    );
+
+    clean(&*c);
+    assert!(c.scan().entries.is_empty());
+    assert!(fx.path("bin/tool").symlink_metadata().is_ok());
+}
+
+#[test]
+fn broken_symlinks_skip_files_on_an_unplugged_volume() {
+    let fx = Fixture::new();
+    let path = fx.file_with("Library/Caches/Parallels/disk.hdd", b"blocks");
+    let volume = fx.dir("/Volumes/Backup");
+    crate::offload::offload(&path, &volume).unwrap();
+    fx.symlink("Library/dangling", &fx.path("nowhere"));
+
+    // The volume is ejected, so the offload link dangles too
+    std::fs::rename(&volume, fx.path("/Volumes/Ejected")).unwrap();
+
+    let result = cleaner("broken-symlinks").scan();
+    assert_eq!(fx.rel_paths(&result), ["home/Library/dangling"]);
+}
+
+#[test]
+fn empty_folders_ignore_hidden_and_protected() {
+    let fx = test_support::populated();
+    let c = cleaner("empty-folders");
+    let result = c.scan();
+    assert_eq!(
+        fx.rel_paths(&result),
+        [
+            "home/Library/Application Support/Abandoned",
+            "home/Library/Containers/com.example.gone",
+        ]
+    );
+    assert_eq!(result.total_bytes, 0);
+
+    clean(&*c);
+    assert!(!fx.path("Library/Application Support/Abandoned").exists());
+    assert!(!fx.path("Library/Containers/com.example.gone").exists());
+    assert!(fx.path("Library/Preferences/.hidden-empty").exists());
+    assert!(fx.path("Library/Containers").exists());
+}
+
+#[test]
+fn screenshots_older_than_a_month() {
+    let fx = test_support::populated();
+    assert_scan_and_clean(
+        &fx,
+        "screenshots",
+        &[
+            "home/Desktop/Screen Recording 2023-02-02 at 11.00.00.mov",
+            "home/Desktop/Screenshot 2023-01-01 at 10.00.00.png",
+        ],
+        300_000 + 900_000,
+    );
+    assert!(fx.path("Desktop/Screenshot 2099-01-01 at 09.00.00.png").exists());
+    assert!(fx.path("Desktop/Notes.png").exists());
+}
+
+#[test]
+fn large_files() {
+    let fx = test_support::populated();
+    let c = cleaner("large-files");
+    let result = c.scan();
+    assert_eq!(
+        fx.rel_paths(&result),
+        ["home/Downloads/old-footage.mov", "home/Downloads/recent-movie.mov"]
+    );
+    assert_eq!(result.total_bytes, 50 * MB);
+}
+
+#[test]
+fn storage_by_type_uses_extensions_then_contents() {
+    let fx = Fixture::new();
+    fx.file("Movies/trip.mov", 3 * MB);
+    fx.file_with("Downloads/IMG_0001", b"\xFF\xD8\xFF\xE0 not really a jpeg");
+    fx.file_with("Documents/report.PDF", b"%PDF-1.7");
+    fx.file_with("Projects/tool/build", b"#!/bin/sh\necho hi\n");
+    fx.file_with("Projects/tool/blob", b"\x00\x01\x02\x03\x04");
+    fx.file("Applications/Editor.app/Contents/MacOS/Editor", MB);
+    fx.file("Applications/Editor.app/Contents/Resources/icon.png", MB);
+    // Skipped like in the large file finder
+    fx.file("Projects/tool/node_modules/dep/index.js", MB);
+    fx.file("Library/Caches/big.mov", 5 * MB);
+
+    let report = storage_by_type(None);
+
+    let kind = |k: FileKind| report.kinds.iter().find(|s| s.kind == k);
+    assert_eq!(report.kinds[0].kind, FileKind::Video);
+    assert_eq!(kind(FileKind::Video).unwrap().bytes, 3 * MB);
+    assert_eq!(kind(FileKind::Images).unwrap().count, 1);
+    assert_eq!(kind(FileKind::Documents).unwrap().count, 1);
+    assert_eq!(kind(FileKind::SourceCode).unwrap().count, 1);
+    assert_eq!(kind(FileKind::Other).unwrap().count, 1);
+    // The bundle counts as one app, its icon is not an image
+    let apps = kind(FileKind::Apps).unwrap();
+    assert_eq!((apps.count, apps.bytes), (1, 2 * MB));
+    assert_eq!(apps.largest[0].0, fx.path("Applications/Editor.app"));
+    assert_eq!(report.total_bytes, report.kinds.iter().map(|k| k.bytes).sum::<u64>());
+    assert!(report.total_bytes < 6 * MB);
+}
+
+#[test]
+fn missing_directories_scan_empty() {
+    let _fx = Fixture::new();
+    for c in all_cleaners(10 * MB, None) {
+        let result = c.scan();
+        assert!(result.entries.is_empty(), "{} found entries in an empty home", c.name());
+        assert_eq!(result.total_bytes, 0);
+    }
+}
+
+fn duplicates_with(policy: KeepPolicy, edit: impl FnOnce(&mut KeepRule)) -> Box<dyn Cleaner> {
+    let mut rule = KeepRule {
+        policy,
+        ..KeepRule::default()
+    };
+    edit(&mut rule);
+    Box::new(duplicates::DuplicateFinder::with_rule(rule))
+}
+
+#[test]
+fn duplicates_keep_policy_picks_the_original() {
+    let fx = test_support::populated();
+    let pictures = fx.path("Pictures/IMG_0001.jpg");
+    let downloads = fx.path("Downloads/IMG_0001 copy.jpg");
+    fx.age(&downloads, 30);
+
+    let deleted = |c: Box<dyn Cleaner>| {
+        let result = c.scan();
+        let copies: Vec<_> = result.entries.iter().filter(|e| e.duplicate_of.is_some()).collect();
+        assert_eq!(copies.len(), 1);
+        assert_eq!(Some(&result.groups[0].original), copies[0].duplicate_of.as_ref());
+        (copies[0].path.clone(), result.groups[0].original.clone())
+    };
+
+    assert_eq!(
+        deleted(duplicates_with(KeepPolicy::Oldest, |_| {})),
+        (pictures.clone(), downloads.clone())
+    );
+    assert_eq!(
+        deleted(duplicates_with(KeepPolicy::Newest, |_| {})),
+        (downloads.clone(), pictures.clone())
+    );
+    assert_eq!(
+        deleted(duplicates_with(KeepPolicy::PreferredFolder, |r| {
+            r.preferred_folders = vec![fx.path("Downloads")];
+        })),
+        (pictures.clone(), downloads.clone())
+    );
+    // Ties fall back to the shorter path
+    assert_eq!(
+        deleted(duplicates_with(KeepPolicy::ShortestPath, |_| {})),
+        (downloads.clone(), pictures.clone())
+    );
+}
+
+#[test]
+fn duplicates_never_offer_protected_copies() {
+    let fx = test_support::populated();
+    let third = test_support::content(1, 2 * MB as usize);
+    fx.file_with("Documents/Backup/IMG_0001.jpg", &third);
+
+    let c = duplicates_with(KeepPolicy::ShortestPath, |r| {
+        r.protected_roots = vec![fx.path("Downloads"), fx.path("Documents/Backup")];
+    });
+    let result = c.scan();
+    assert!(result.entries.is_empty(), "protected copies were offered");
+    assert!(result.groups.is_empty());
+
+    // Protection never makes the group lose its original either
+    let c = duplicates_with(KeepPolicy::ShortestPath, |r| {
+        r.protected_roots = vec![fx.path("Documents/Backup")];
+    });
+    clean(&*c);
+    assert!(fx.path("Pictures/IMG_0001.jpg").exists());
+    assert!(!fx.path("Downloads/IMG_0001 copy.jpg").exists());
+    assert!(fx.path("Documents/Backup/IMG_0001.jpg").exists());
+}
+
+/// A 288x192 picture: a seeded 9x8 grid of brightness blocks under pixel noise.
+fn picture(seed: u8) -> image::RgbImage {
+    let cells = test_support::content(seed, 9 * 8);
+    let noise = test_support::content(seed.wrapping_add(100), 288 * 192);
+    image::RgbImage::from_fn(288, 192, |x, y| {
+        let base = cells[(y / 24 * 9 + x / 32) as usize] / 2 + 64;
+        let v = base.wrapping_add(noise[(y * 288 + x) as usize] % 32);
+        image::Rgb([v, v / 2 + 40, 255 - v])
+    })
+}
+
+#[test]
+fn similar_images_group_resized_and_reencoded_copies() {
+    let fx = Fixture::new();
+    let sunset = picture(1);
+    fx.dir("Pictures");
+    fx.dir("Downloads");
+    fx.dir("Desktop");
+    fx.dir("Documents");
+    sunset.save(fx.path("Pictures/sunset.png")).unwrap();
+    sunset.save(fx.path("Desktop/sunset.bmp")).unwrap();
+    image::imageops::resize(&sunset, 144, 96, image::imageops::FilterType::Triangle)
+        .save(fx.path("Downloads/sunset-small.png"))
+        .unwrap();
+    // A byte-identical copy is left to the duplicate finder
+    std::fs::copy(fx.path("Pictures/sunset.png"), fx.path("Downloads/sunset copy.png")).unwrap();
+    picture(2).save(fx.path("Documents/other.png")).unwrap();
+
+    let result = cleaner("similar-images").scan();
+    assert_eq!(result.groups.len(), 1);
+    let group = &result.groups[0];
+    assert_eq!(group.members.len(), 3);
+    assert!(group.members.contains(&fx.path("Downloads/sunset-small.png")));
+    assert_eq!(
+        group
+            .members
+            .iter()
+            .filter(|m| m.ends_with("sunset.png") || m.ends_with("sunset copy.png"))
+            .count(),
+        1
+    );
+
+    // Full resolution and least compressed wins
+    assert_eq!(group.original, fx.path("Desktop/sunset.bmp"));
+    let original = &result.entries[0];
+    assert_eq!(original.path, group.original);
+    let info = original.image.as_ref().unwrap();
+    assert_eq!((info.width, info.height), (288, 192));
+    assert_eq!(info.thumb_size, [64, 43]);
+    assert_eq!(info.thumb_rgba.len(), 64 * 43 * 4);
+
+    let copies: u64 = result.entries[1..].iter().map(|e| e.size_bytes).sum();
+    assert_eq!(result.total_bytes, copies);
+
+    clean(&*cleaner("similar-images"));
+    assert!(fx.path("Desktop/sunset.bmp").exists());
+    assert!(fx.path("Documents/other.png").exists());
+    assert!(!fx.path("Downloads/sunset-small.png").exists());
+    let pngs = [fx.path("Pictures/sunset.png"), fx.path("Downloads/sunset copy.png")];
+    assert_eq!(pngs.iter().filter(|p| p.exists()).count(), 1);
+}
+
+#[test]
+fn duplicate_folders_are_reported_whole() {
+    let fx = Fixture::new();
+    let tree = |root: &str, last: u8| {
+        fx.file_with(&format!("{root}/a.jpg"), &test_support::content(10, 2 * MB as usize));
+        fx.file_with(&format!("{root}/b.jpg"), &test_support::content(11, MB as usize));
+        fx.file_with(&format!("{root}/raw/notes.txt"), &[1, 2, 3, last]);
+    };
+    tree("Pictures/Import 2023", 4);
+    tree("Downloads/Import 2023 copy", 4);
+    // Same layout and sizes, one byte different deep inside
+    tree("Desktop/Import edited", 5);
+
+    let result = cleaner("duplicates").scan();
+    let folders: Vec<_> = result
+        .groups
+        .iter()
+        .filter(|g| g.original.is_dir())
+        .collect();
+    assert_eq!(folders.len(), 1);
+    assert_eq!(
+        folders[0].members,
+        [fx.path("Downloads/Import 2023 copy"), fx.path("Pictures/Import 2023")]
+    );
+    assert_eq!(folders[0].size_bytes, 3 * MB + 4);
+
+    // Files inside the matched folders aren't offered again one by one;
+    // the edited tree's photos still match the original's
+    for entry in &result.entries {
+        assert!(!entry.path.starts_with(fx.path("Downloads/Import 2023 copy/")) || entry.path.is_dir());
+        assert!(!entry.path.starts_with(fx.path("Pictures/Import 2023/")) || entry.path.is_dir());
+    }
+    assert!(result
+        .entries
+        .iter()
+        .all(|e| !e.path.ends_with("raw")));
+
+    clean(&*cleaner("duplicates"));
+    let survivors = [fx.path("Pictures/Import 2023"), fx.path("Downloads/Import 2023 copy")];
+    assert_eq!(survivors.iter().filter(|p| p.exists()).count(), 1);
+    assert!(fx.path("Desktop/Import edited/raw/notes.txt").exists());
+}
+
+#[test]
+fn duplicate_folders_holding_a_protected_folder_are_never_offered() {
+    let fx = Fixture::new();
+    for root in ["Pictures/Import 2023", "Downloads/Import 2023 copy"] {
+        fx.file_with(&format!("{root}/a.jpg"), &test_support::content(10, 2 * MB as usize));
+        fx.file_with(&format!("{root}/raw/notes.txt"), &[1, 2, 3]);
+    }
+
+    let c = duplicates_with(KeepPolicy::ShortestPath, |r| {
+        r.protected_roots = vec![fx.path("Downloads/Import 2023 copy/raw")];
+    });
+    let result = c.scan();
+    assert!(result
+        .entries
+        .iter()
+        .all(|e| !fx.path("Downloads/Import 2023 copy/raw").starts_with(&e.path)));
+
+    clean(&*c);
+    assert!(fx.path("Downloads/Import 2023 copy/raw/notes.txt").exists());
+    assert!(fx.path("Pictures/Import 2023/raw/notes.txt").exists());
+}
+
+#[test]
+fn folders_with_unseen_contents_never_match() {
+    let fx = Fixture::new();
+    for (root, dep) in [("Documents/app", 1u8), ("Documents/app-copy", 2)] {
+        fx.file_with(&format!("{root}/src/main.rs"), &test_support::content(20, MB as usize));
+        fx.file_with(&format!("{root}/src/lib.rs"), &test_support::content(21, MB as usize));
+        // Skipped by the walk, and different: the folders aren't identical
+        fx.file_with(&format!("{root}/node_modules/dep.js"), &[dep]);
+    }
+
+    let result = cleaner("duplicates").scan();
+    assert!(result.groups.iter().all(|g| !g.members.contains(&fx.path("Documents/app"))));
+    // The identical source folders inside are still found
+    assert!(result.groups.iter().any(|g| g.members.contains(&fx.path("Documents/app/src"))));
+}
+
+#[test]
+fn large_duplicates_are_prescreened_by_sampling() {
+    let fx = Fixture::new();
+    let size = 2 * MB;
+    let data = test_support::content(12, size as usize);
+    let poked = |offset: u64| {
+        let mut data = data.clone();
+        data[offset as usize] ^= 0xAB;
+        data
+    };
+    fx.file_with("Downloads/vm.img", &data);
+    fx.file_with("Documents/vm backup.img", &data);
+    // Differs inside the middle sample: pruned without a full read
+    fx.file_with("Desktop/vm middle.img", &poked(size / 2));
+    // Differs outside every sample: only the full hash tells them apart
+    fx.file_with("Desktop/vm sneaky.img", &poked(MB / 2));
+
+    let cache = crate::hash_cache::HashCache::open();
+    let sample = |rel: &str| duplicates::sampled_hash(&fx.path(rel), &cache).unwrap();
+    assert_eq!(sample("Downloads/vm.img"), sample("Desktop/vm sneaky.img"));
+    assert_ne!(sample("Downloads/vm.img"), sample("Desktop/vm middle.img"));
+
+    let finder = duplicates::DuplicateFinder::with_rule(KeepRule::default())
+        .with_sample_threshold(MB);
+    let result = finder.scan();
+    assert_eq!(
+        fx.rel_paths(&result),
+        ["home/Documents/vm backup.img", "home/Downloads/vm.img"]
+    );
+    assert_eq!(result.total_bytes, size);
+}
+
+#[test]
+fn project_artifacts_only_come_from_untouched_projects() {
+    let fx = Fixture::new();
+    let old = |rel: &str, size: u64| {
+        let path = fx.file(rel, size);
+        fx.age(&path, 200);
+    };
+    // Stale Rust project: its target folder goes
+    old("Code/old-cli/Cargo.toml", 100);
+    old("Code/old-cli/src/main.rs", 100);
+    fx.file("Code/old-cli/target/CACHEDIR.TAG", 100);
+    fx.file("Code/old-cli/target/debug/old-cli", 2 * MB);
+    // Stale Python project: its venv and every __pycache__ go
+    old("Code/tool/pyproject.toml", 100);
+    old("Code/tool/pkg/mod.py", 100);
+    fx.file("Code/tool/.venv/pyvenv.cfg", 50);
+    fx.file("Code/tool/.venv/lib/site.py", MB);
+    fx.file("Code/tool/pkg/__pycache__/mod.pyc", 10);
+    // A folder that only shares the name of an artifact stays
+    old("Code/notes/Cargo.toml", 100);
+    fx.file("Code/notes/target/draft.txt", 100);
+    // Recently edited project: left alone however big
+    fx.file("Code/web/package.json", 100);
+    fx.file("Code/web/node_modules/react/index.js", 3 * MB);
+    // Not a project at all
+    fx.file("Documents/build/report.pdf", MB);
+
+    let result = cleaner("project-artifacts").scan();
+    assert_eq!(
+        detail_of(&result, &fx.path("Code/old-cli/target")),
+        "Rust project old-cli, untouched 200 days"
+    );
+
+    assert_scan_and_clean(
+        &fx,
+        "project-artifacts",
+        &[
+            "home/Code/old-cli/target",
+            "home/Code/tool/.venv",
+            "home/Code/tool/pkg/__pycache__",
+        ],
+        100 + 2 * MB + 50 + MB + 10,
+    );
+    assert!(fx.path("Code/notes/target/draft.txt").exists());
+    assert!(fx.path("Code/web/node_modules").exists());
+}
@@ -1,9 +1,70 @@
+use super::project_artifacts::{MAX_STALE_DAYS, MIN_STALE_DAYS};
 use crate::cleaner::{Cleaner, ScanEntry, ScanResult};
 use crate::utils;
+use std::cmp::Reverse;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime};
 
 // --- Xcode Derived Data ---
 
-pub struct XcodeDerivedData;
+/// What a DerivedData folder's `info.plist` says about its project.
+struct DerivedDataInfo {
+    workspace: Option<PathBuf>,
+    last_built: Option<SystemTime>,
+}
+
+fn derived_data_info(folder: &Path) -> DerivedDataInfo {
+    let plist_path = folder.join("info.plist");
+    let info = plist::Value::from_file(&plist_path).ok();
+    let dict = info.as_ref().and_then(|v| v.as_dictionary());
+    let workspace = dict
+        .and_then(|d| d.get("WorkspacePath"))
+        .and_then(|v| v.as_string())
+        .map(PathBuf::from);
+    // Xcode updates LastAccessedDate on every build; fall back to file dates
+    let last_built = dict
+        .and_then(|d| d.get("LastAccessedDate"))
+        .and_then(|v| v.as_date())
+        .map(SystemTime::from)
+        .or_else(|| {
+            [plist_path, folder.join("Logs/Build")]
+                .iter()
+                .filter_map(|p| p.metadata().ok()?.modified().ok())
+                .max()
+        });
+    DerivedDataInfo { workspace, last_built }
+}
+
+/// Projects not built for this many days have their DerivedData selected.
+pub const DEFAULT_DERIVED_DATA_DAYS: u32 = 90;
+
+/// Days used by scans started from the UI. Separate from the build-artifact
+/// setting: rebuilding a project in Xcode costs more than a `cargo build`.
+static DERIVED_DATA_DAYS: AtomicU32 = AtomicU32::new(DEFAULT_DERIVED_DATA_DAYS);
+
+pub fn derived_data_days() -> u32 {
+    DERIVED_DATA_DAYS.load(Ordering::Relaxed)
+}
+
+pub fn set_derived_data_days(days: u32) {
+    DERIVED_DATA_DAYS.store(days.clamp(MIN_STALE_DAYS, MAX_STALE_DAYS), Ordering::Relaxed);
+}
+
+/// Build data for each Xcode project. Folders of projects that are gone or
+/// haven't been built for [`derived_data_days`] are selected; the rest are
+/// offered but kept by default, since removing them forces a full rebuild.
+pub struct XcodeDerivedData {
+    stale_days: u32,
+}
+
+impl XcodeDerivedData {
+    pub fn new(stale_days: u32) -> Self {
+        Self { stale_days }
+    }
+}
 
 impl Cleaner for XcodeDerivedData {
     fn name(&self) -> &'static str {
@@ -15,18 +76,73 @@ impl Cleaner for XcodeDerivedData {
     }
 
     fn scan(&self) -> ScanResult {
-        scan_directory(
+        let mut result = scan_directory(
             &utils::home_dir().join("Library/Developer/Xcode/DerivedData"),
-        )
-    }
+        );
+        let stale_since = SystemTime::now()
+            .checked_sub(Duration::from_secs(self.stale_days as u64 * 86400))
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        for entry in &mut result.entries {
+            let name = entry.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            // ModuleCache.noindex and friends are shared by every project
+            if name.ends_with(".noindex") {
+                entry.keep_by_default = true;
+                entry.detail = Some("Shared by all projects".to_string());
+                continue;
+            }
 
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        clean_directory(self, dry_run)
+            let info = derived_data_info(&entry.path);
+            let Some(workspace) = info.workspace else {
+                entry.detail = Some("Unknown project".to_string());
+                continue;
+            };
+            let project = workspace
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            if !workspace.exists() {
+                entry.detail = Some(format!("{project}, project no longer exists"));
+                continue;
+            }
+            let days = info
+                .last_built
+                .and_then(|t| SystemTime::now().duration_since(t).ok())
+                .map(|d| d.as_secs() / 86400);
+            let stale = info.last_built.is_none_or(|t| t < stale_since);
+            entry.keep_by_default = !stale;
+            entry.detail = Some(match days {
+                Some(0) => format!("{project}, built today"),
+                Some(days) => format!("{project}, last built {days} days ago"),
+                None => format!("{project}, never built"),
+            });
+        }
+        result
     }
 }
 
-// --- iOS Device Support ---
+// --- Device Support ---
+
+/// OS versions kept per platform.
+const KEEP_OS_VERSIONS: usize = 2;
+
+/// "17.2" and "21C62" from a folder named "17.2 (21C62)" or, as newer Xcode
+/// names them, "iPhone15,2 17.2 (21C62)".
+fn parse_device_support(name: &str) -> Option<(String, Option<String>)> {
+    let version = name.split_whitespace().find(|w| {
+        w.starts_with(|c: char| c.is_ascii_digit()) && w.chars().all(|c| c.is_ascii_digit() || c == '.')
+    })?;
+    let build = name
+        .split_once('(')
+        .and_then(|(_, rest)| rest.split_once(')'))
+        .map(|(build, _)| build.to_string());
+    Some((version.to_string(), build))
+}
 
+/// Debug symbols copied from each device OS version, for iOS, watchOS, tvOS
+/// and visionOS. The newest [`KEEP_OS_VERSIONS`] versions of each platform are
+/// kept by default, since a connected device needs them again.
 pub struct XcodeDeviceSupport;
 
 impl Cleaner for XcodeDeviceSupport {
@@ -35,22 +151,113 @@ impl Cleaner for XcodeDeviceSupport {
     }
 
     fn label(&self) -> &'static str {
-        "Xcode iOS Device Support"
+        "Xcode Device Support"
     }
 
     fn scan(&self) -> ScanResult {
-        scan_directory(
-            &utils::home_dir().join("Library/Developer/Xcode/iOS DeviceSupport"),
-        )
-    }
-
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        clean_directory(self, dry_run)
+        let xcode = utils::home_dir().join("Library/Developer/Xcode");
+        let mut platforms: Vec<(String, PathBuf)> = std::fs::read_dir(&xcode)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                let platform = name.strip_suffix(" DeviceSupport")?.to_string();
+                Some((platform, e.path()))
+            })
+            .collect();
+        platforms.sort();
+
+        let mut result = ScanResult {
+            entries: Vec::new(),
+            total_bytes: 0,
+            errors: Vec::new(),
+            groups: Vec::new(),
+        };
+        for (platform, dir) in platforms {
+            let mut found = scan_directory(&dir);
+            result.errors.append(&mut found.errors);
+            result.total_bytes += found.total_bytes;
+
+            let parsed: Vec<_> = found
+                .entries
+                .into_iter()
+                .map(|e| {
+                    let name = e.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                    (parse_device_support(&name), e)
+                })
+                .collect();
+            let mut versions: Vec<Vec<u64>> = parsed
+                .iter()
+                .filter_map(|(p, _)| p.as_ref().map(|(v, _)| utils::version_key(v)))
+                .collect();
+            versions.sort_by_key(|v| Reverse(v.clone()));
+            versions.dedup();
+            let newest = &versions[..versions.len().min(KEEP_OS_VERSIONS)];
+
+            let mut entries: Vec<(Vec<u64>, ScanEntry)> = parsed
+                .into_iter()
+                .map(|(parsed, mut entry)| {
+                    entry.heading = Some(platform.clone());
+                    let Some((version, build)) = parsed else {
+                        // Unknown layout: shown, but left alone
+                        entry.keep_by_default = true;
+                        entry.detail = Some("Unrecognized version".to_string());
+                        return (Vec::new(), entry);
+                    };
+                    let key = utils::version_key(&version);
+                    let build = build.map(|b| format!(" ({b})")).unwrap_or_default();
+                    entry.keep_by_default = newest.contains(&key);
+                    entry.detail = Some(if entry.keep_by_default {
+                        format!("{platform} {version}{build}, one of the {KEEP_OS_VERSIONS} newest")
+                    } else {
+                        format!("{platform} {version}{build}, older than the {KEEP_OS_VERSIONS} newest")
+                    });
+                    (key, entry)
+                })
+                .collect();
+            entries.sort_by_key(|(key, _)| Reverse(key.clone()));
+            result.entries.extend(entries.into_iter().map(|(_, e)| e));
+        }
+        result
     }
 }
 
 // --- Xcode Archives ---
 
+/// What an `.xcarchive`'s `Info.plist` says about the build inside.
+struct ArchiveInfo {
+    app: String,
+    bundle_id: Option<String>,
+    version: String,
+    build: String,
+    created: Option<SystemTime>,
+}
+
+fn archive_info(archive: &Path) -> Option<ArchiveInfo> {
+    let info = plist::Value::from_file(archive.join("Info.plist")).ok()?;
+    let dict = info.as_dictionary()?;
+    let props = dict.get("ApplicationProperties").and_then(|v| v.as_dictionary());
+    let prop = |key: &str| props?.get(key)?.as_string().map(str::to_string);
+    let app = dict
+        .get("Name")
+        .and_then(|v| v.as_string())
+        .map(str::to_string)
+        .or_else(|| {
+            let path = prop("ApplicationPath")?;
+            Some(Path::new(&path).file_stem()?.to_string_lossy().to_string())
+        })?;
+    Some(ArchiveInfo {
+        app,
+        bundle_id: prop("CFBundleIdentifier"),
+        version: prop("CFBundleShortVersionString").unwrap_or_default(),
+        build: prop("CFBundleVersion").unwrap_or_default(),
+        created: dict.get("CreationDate").and_then(|v| v.as_date()).map(SystemTime::from),
+    })
+}
+
+/// Archived builds, listed under their app. The latest archive of each app
+/// version is kept by default; earlier builds of the same version are not.
 pub struct XcodeArchives;
 
 impl Cleaner for XcodeArchives {
@@ -63,18 +270,245 @@ impl Cleaner for XcodeArchives {
     }
 
     fn scan(&self) -> ScanResult {
-        scan_directory(
-            &utils::home_dir().join("Library/Developer/Xcode/Archives"),
-        )
-    }
+        let root = utils::home_dir().join("Library/Developer/Xcode/Archives");
+        let mut entries = Vec::new();
+        let mut total_bytes = 0u64;
+        let errors = Vec::new();
+
+        // Xcode files archives into one folder per day
+        let archives: Vec<PathBuf> = walkdir::WalkDir::new(&root)
+            .min_depth(1)
+            .max_depth(2)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 1 || e.path().extension().is_some_and(|x| x == "xcarchive"))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_dir() && e.path().extension().is_some_and(|x| x == "xcarchive"))
+            .map(|e| e.into_path())
+            .collect();
+        let infos: Vec<(PathBuf, Option<ArchiveInfo>)> = archives
+            .into_iter()
+            .map(|path| {
+                let info = archive_info(&path);
+                (path, info)
+            })
+            .collect();
+
+        // The one each (app, version) keeps: the newest, by creation date then build
+        let latest = |info: &ArchiveInfo| {
+            infos
+                .iter()
+                .filter_map(|(_, i)| i.as_ref())
+                .filter(|i| i.app == info.app && i.bundle_id == info.bundle_id && i.version == info.version)
+                .max_by_key(|i| (i.created, utils::version_key(&i.build)))
+                .map(|i| i.build.clone())
+        };
+
+        let mut sorted: Vec<(String, Vec<u64>, Option<SystemTime>, ScanEntry)> = Vec::new();
+        for (path, info) in &infos {
+            let size = utils::dir_size(path);
+            total_bytes += size;
+            let mut entry = ScanEntry::new(path.clone(), size);
+            let Some(info) = info else {
+                entry.keep_by_default = true;
+                entry.heading = Some("Unreadable archives".to_string());
+                entry.detail = Some("No readable Info.plist".to_string());
+                sorted.push((String::new(), Vec::new(), None, entry));
+                continue;
+            };
+            let created = info.created.map(utils::format_date).unwrap_or_default();
+            let what = format!("{} ({}), archived {created}", info.version, info.build);
+            let latest_build = latest(info).unwrap_or_default();
+            entry.keep_by_default = latest_build == info.build;
+            entry.detail = Some(if entry.keep_by_default {
+                format!("{what}, latest of {}", info.version)
+            } else {
+                format!("{what}, superseded by build {latest_build}")
+            });
+            entry.heading = Some(info.app.clone());
+            sorted.push((info.app.clone(), utils::version_key(&info.version), info.created, entry));
+        }
 
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        clean_directory(self, dry_run)
+        // By app, then newest version and archive first
+        sorted.sort_by_key(|(app, version, created, _)| {
+            (app.is_empty(), app.clone(), Reverse(version.clone()), Reverse(*created))
+        });
+        entries.extend(sorted.into_iter().map(|(.., e)| e));
+
+        ScanResult {
+            entries,
+            total_bytes,
+            errors,
+            groups: Vec::new(),
+        }
     }
 }
 
 // --- CoreSimulator ---
 
+/// Simulators not booted for this long are selected.
+const SIMULATOR_IDLE_DAYS: u64 = 180;
+
+/// `device.plist` state of a running simulator.
+const STATE_BOOTED: u64 = 3;
+
+/// Runtime disk images CoreSimulator knows about (Xcode 15 and later).
+const RUNTIME_IMAGES: &str = "/Library/Developer/CoreSimulator/Images/images.plist";
+
+/// Folders holding installed simulator runtimes (`*.simruntime`): downloaded
+/// ones, those mounted from disk images by Xcode 15 and later, and those
+/// bundled inside older Xcodes.
+const RUNTIME_DIRS: &[&str] = &[
+    "/Library/Developer/CoreSimulator/Profiles/Runtimes",
+    "/Library/Developer/CoreSimulator/Volumes/*/Library/Developer/CoreSimulator/Profiles/Runtimes",
+    "/Applications/Xcode.app/Contents/Developer/Platforms/*/Library/Developer/CoreSimulator/Profiles/Runtimes",
+];
+
+/// "com.apple.CoreSimulator.SimRuntime.iOS-17-2" gives "iOS 17.2".
+fn runtime_display(id: &str) -> String {
+    let last = id.rsplit('.').next().unwrap_or(id);
+    match last.split_once('-') {
+        Some((platform, version)) => format!("{platform} {}", version.replace('-', ".")),
+        None => last.to_string(),
+    }
+}
+
+/// Identifiers of the runtimes `simctl list runtimes -j` reports as
+/// available; ones whose disk image is gone are listed as unavailable.
+fn available_runtimes(json: &str) -> Option<Vec<String>> {
+    let list: serde_json::Value = serde_json::from_str(json).ok()?;
+    let runtimes = list.get("runtimes")?.as_array()?;
+    Some(
+        runtimes
+            .iter()
+            .filter(|runtime| runtime["isAvailable"] == true)
+            .filter_map(|runtime| runtime["identifier"].as_str())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Runtimes as `simctl` lists them, if Xcode's tools can be run.
+fn simctl_runtimes() -> Option<Vec<String>> {
+    let output = Command::new("xcrun")
+        .args(["simctl", "list", "runtimes", "-j"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    available_runtimes(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Runtimes recorded in CoreSimulator's list of runtime disk images.
+fn image_runtimes() -> Option<Vec<String>> {
+    fn collect(value: &plist::Value, found: &mut Vec<String>) {
+        match value {
+            plist::Value::Dictionary(dict) => {
+                for (key, value) in dict {
+                    match value.as_string() {
+                        Some(id) if key == "bundleIdentifier" => found.push(id.to_string()),
+                        _ => collect(value, found),
+                    }
+                }
+            }
+            plist::Value::Array(items) => items.iter().for_each(|v| collect(v, found)),
+            _ => {}
+        }
+    }
+    let images = plist::Value::from_file(utils::system_path(RUNTIME_IMAGES)).ok()?;
+    let mut found = Vec::new();
+    collect(&images, &mut found);
+    Some(found)
+}
+
+/// Identifiers of installed simulator runtimes, and whether the list is
+/// complete: it is when `simctl` or CoreSimulator's image list could be read.
+/// Otherwise it holds only the runtime bundles found on disk.
+fn installed_runtimes() -> (Vec<String>, bool) {
+    // A fixture root stands in for the whole system, simctl included
+    if utils::root_override().is_none() {
+        if let Some(runtimes) = simctl_runtimes() {
+            return (runtimes, true);
+        }
+    }
+    let images = image_runtimes();
+    let complete = images.is_some();
+    let mut runtimes = images.unwrap_or_default();
+    runtimes.extend(runtime_bundles());
+    (runtimes, complete)
+}
+
+/// Identifiers of the runtime bundles in [`RUNTIME_DIRS`].
+fn runtime_bundles() -> Vec<String> {
+    let mut runtimes = Vec::new();
+    for pattern in RUNTIME_DIRS {
+        let mut dirs = vec![utils::system_path("/")];
+        for component in pattern.trim_start_matches('/').split('/') {
+            dirs = dirs
+                .iter()
+                .flat_map(|dir| match component {
+                    "*" => std::fs::read_dir(dir)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|e| e.ok())
+                        .map(|e| e.path())
+                        .collect(),
+                    _ => vec![dir.join(component)],
+                })
+                .collect();
+        }
+        for runtime in dirs
+            .iter()
+            .flat_map(|dir| std::fs::read_dir(dir).into_iter().flatten())
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|x| x == "simruntime"))
+        {
+            let id = plist::Value::from_file(runtime.join("Contents/Info.plist"))
+                .ok()
+                .and_then(|v| v.as_dictionary()?.get("CFBundleIdentifier")?.as_string().map(str::to_string))
+                .unwrap_or_else(|| {
+                    // "iOS 17.2.simruntime" is com.apple.CoreSimulator.SimRuntime.iOS-17-2
+                    let stem = runtime.file_stem().unwrap_or_default().to_string_lossy();
+                    format!("com.apple.CoreSimulator.SimRuntime.{}", stem.replace([' ', '.'], "-"))
+                });
+            runtimes.push(id);
+        }
+    }
+    runtimes
+}
+
+/// What a simulator's `device.plist` says about it.
+struct SimDevice {
+    name: String,
+    runtime: String,
+    booted: bool,
+    last_booted: Option<SystemTime>,
+}
+
+fn sim_device(folder: &Path) -> Option<SimDevice> {
+    let plist_path = folder.join("device.plist");
+    let info = plist::Value::from_file(&plist_path).ok()?;
+    let dict = info.as_dictionary()?;
+    let text = |key: &str| dict.get(key)?.as_string().map(str::to_string);
+    // Older Xcodes don't record boots; the data folder changes while one runs
+    let last_booted = dict
+        .get("lastBootedAt")
+        .and_then(|v| v.as_date())
+        .map(SystemTime::from)
+        .or_else(|| folder.join("data").metadata().ok()?.modified().ok());
+    Some(SimDevice {
+        name: text("name")?,
+        runtime: text("runtime")?,
+        booted: dict.get("state").and_then(|v| v.as_unsigned_integer()) == Some(STATE_BOOTED),
+        last_booted,
+    })
+}
+
+/// Simulator devices, listed by OS. Devices whose runtime is no longer
+/// installed, or not booted for [`SIMULATOR_IDLE_DAYS`], are selected. They
+/// are deleted through `simctl`, so CoreSimulator forgets them too.
 pub struct CoreSimulator;
 
 impl Cleaner for CoreSimulator {
@@ -87,13 +521,80 @@ impl Cleaner for CoreSimulator {
     }
 
     fn scan(&self) -> ScanResult {
-        scan_directory(
+        let mut result = scan_directory(
             &utils::home_dir().join("Library/Developer/CoreSimulator/Devices"),
-        )
+        );
+        // device_set.plist and the like sit beside the device folders
+        result.entries.retain(|e| e.path.is_dir());
+        result.total_bytes = result.entries.iter().map(|e| e.size_bytes).sum();
+
+        let (runtimes, complete) = installed_runtimes();
+        let idle_since = SystemTime::now()
+            .checked_sub(Duration::from_secs(SIMULATOR_IDLE_DAYS * 86400))
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let mut sorted: Vec<(String, String, ScanEntry)> = Vec::new();
+        for mut entry in result.entries.drain(..) {
+            let Some(device) = sim_device(&entry.path) else {
+                entry.keep_by_default = true;
+                entry.heading = Some("Unknown devices".to_string());
+                entry.detail = Some("No readable device.plist".to_string());
+                sorted.push((String::new(), String::new(), entry));
+                continue;
+            };
+            let os = runtime_display(&device.runtime);
+            let days = device
+                .last_booted
+                .and_then(|t| SystemTime::now().duration_since(t).ok())
+                .map(|d| d.as_secs() / 86400);
+            // Without the full list, a runtime not found may still be installed
+            let missing = !runtimes.contains(&device.runtime);
+            let orphaned = missing && complete;
+            let unknown = missing && !complete;
+            let idle = device.last_booted.is_none_or(|t| t < idle_since);
+            let status = if device.booted {
+                "running".to_string()
+            } else if orphaned {
+                "runtime no longer installed".to_string()
+            } else if unknown {
+                "runtime not found".to_string()
+            } else {
+                match days {
+                    Some(0) => "booted today".to_string(),
+                    Some(days) => format!("last booted {days} days ago"),
+                    None => "never booted".to_string(),
+                }
+            };
+            entry.keep_by_default = device.booted || unknown || !(orphaned || idle);
+            entry.detail = Some(format!("{} ({os}), {status}", device.name));
+            entry.heading = Some(os.clone());
+            sorted.push((os, device.name, entry));
+        }
+
+        // By platform, newest OS first, then by device name
+        sorted.sort_by_key(|(os, name, _)| {
+            let platform = os.split(' ').next().unwrap_or_default().to_string();
+            (os.is_empty(), platform, Reverse(utils::version_key(os)), name.clone())
+        });
+        result.entries = sorted.into_iter().map(|(.., e)| e).collect();
+        result
     }
 
-    fn clean(&self, dry_run: bool) -> ScanResult {
-        clean_directory(self, dry_run)
+    fn remove(&self, path: &Path) -> io::Result<u64> {
+        if utils::root_override().is_some() || sim_device(path).is_none() {
+            return utils::safe_remove(path);
+        }
+        let size = utils::entry_size(path);
+        let udid = path.file_name().unwrap_or_default();
+        match Command::new("xcrun").args(["simctl", "delete"]).arg(udid).output() {
+            Ok(output) if output.status.success() => Ok(size),
+            Ok(output) => Err(io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            )),
+            // Without Xcode's tools, the folder is all there is to remove
+            Err(e) if e.kind() == io::ErrorKind::NotFound => utils::safe_remove(path),
+            Err(e) => Err(e),
+        }
     }
 }
 
@@ -109,6 +610,7 @@ fn scan_directory(dir: &std::path::Path) -> ScanResult {
             entries,
             total_bytes,
             errors,
+            groups: Vec::new(),
         };
     }
 
@@ -118,10 +620,7 @@ fn scan_directory(dir: &std::path::Path) -> ScanResult {
                 let path = entry.path();
                 let size = utils::entry_size(&path);
                 total_bytes += size;
-                entries.push(ScanEntry {
-                    path,
-                    size_bytes: size,
-                });
+                entries.push(ScanEntry::new(path, size));
             }
         }
         Err(e) => {
@@ -129,39 +628,45 @@ fn scan_directory(dir: &std::path::Path) -> ScanResult {
         }
     }
 
-    entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    entries.sort_by_key(|e| Reverse(e.size_bytes));
 
     ScanResult {
         entries,
         total_bytes,
         errors,
+        groups: Vec::new(),
     }
 }
 
-fn clean_directory(cleaner: &dyn Cleaner, dry_run: bool) -> ScanResult {
-    let mut result = cleaner.scan();
-    if dry_run {
-        return result;
-    }
-
-    let mut cleaned_entries = Vec::new();
-    let mut total_freed = 0u64;
-
-    for entry in result.entries.drain(..) {
-        match utils::safe_remove(&entry.path) {
-            Ok(freed) => {
-                total_freed += freed;
-                cleaned_entries.push(entry);
-            }
-            Err(e) => {
-                result
-                    .errors
-                    .push(format!("Failed to remove {}: {e}", entry.path.display()));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simctl_lists_only_available_runtimes() {
+        let json = r#"{
+          "devicetypes": [
+            { "name": "iPhone 15", "identifier": "com.apple.CoreSimulator.SimDeviceType.iPhone-15" }
+          ],
+          "runtimes": [
+            {
+              "identifier": "com.apple.CoreSimulator.SimRuntime.iOS-17-2",
+              "isAvailable": true,
+              "supportedDeviceTypes": [
+                { "identifier": "com.apple.CoreSimulator.SimDeviceType.iPhone-15" }
+              ]
+            },
+            {
+              "identifier": "com.apple.CoreSimulator.SimRuntime.iOS-16-4",
+              "isAvailable": false,
+              "availabilityError": "The runtime's disk image is missing"
             }
-        }
+          ]
+        }"#;
+        assert_eq!(
+            available_runtimes(json).unwrap(),
+            ["com.apple.CoreSimulator.SimRuntime.iOS-17-2"]
+        );
+        assert_eq!(available_runtimes("xcrun: error: unable to find utility"), None);
     }
-
-    result.entries = cleaned_entries;
-    result.total_bytes = total_freed;
-    result
 }
@@ -1,16 +1,143 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// One item found during a scan.
 pub struct ScanEntry {
     pub path: PathBuf,
     pub size_bytes: u64,
+    /// Running app (or process) holding this entry; such entries are skipped.
+    pub in_use_by: Option<String>,
+    /// Whether `in_use_by` is a running `.app`, which can be asked to quit,
+    /// rather than a daemon or command-line process.
+    pub held_by_app: bool,
+    /// For duplicates: the copy that is kept when this one is deleted.
+    pub duplicate_of: Option<PathBuf>,
+    /// For similar images: resolution and a preview.
+    pub image: Option<ImageInfo>,
+    /// Short note shown beside the path, such as the date that made it qualify.
+    pub detail: Option<String>,
+    /// Offered but left unselected after a scan, like the build data of a
+    /// project still being worked on.
+    pub keep_by_default: bool,
+    /// Heading the entry is listed under, such as the app an archive is of.
+    /// Entries sharing a heading are adjacent.
+    pub heading: Option<String>,
+}
+
+impl ScanEntry {
+    pub fn new(path: PathBuf, size_bytes: u64) -> Self {
+        Self {
+            path,
+            size_bytes,
+            in_use_by: None,
+            held_by_app: false,
+            duplicate_of: None,
+            image: None,
+            detail: None,
+            keep_by_default: false,
+            heading: None,
+        }
+    }
+}
+
+/// Decoded details of an image entry.
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    /// Size of the RGBA preview in pixels.
+    pub thumb_size: [usize; 2],
+    pub thumb_rgba: Vec<u8>,
+}
+
+/// Copies of one file: identical ones, or for similar images, versions that
+/// look alike. Every member is also an entry of the result; the original is
+/// the copy that survives a clean.
+pub struct DuplicateGroup {
+    /// Hex content hash shared by all members (for similar images, the
+    /// perceptual hash of the original).
+    pub hash: String,
+    /// Size of a single copy (of the original, for similar images).
+    pub size_bytes: u64,
+    pub members: Vec<PathBuf>,
+    pub original: PathBuf,
 }
 
 /// Result of scanning a single category.
 pub struct ScanResult {
     pub entries: Vec<ScanEntry>,
+    /// Bytes that cleaning the entries would free.
     pub total_bytes: u64,
     pub errors: Vec<String>,
+    /// Duplicate groups over `entries` (empty for other categories).
+    pub groups: Vec<DuplicateGroup>,
+}
+
+impl ScanResult {
+    /// Whether `path` is the designated survivor of a duplicate group.
+    pub fn is_original(&self, path: &Path) -> bool {
+        self.groups.iter().any(|g| g.original == path)
+    }
+
+    /// Make `path` the copy that survives its group.
+    pub fn set_original(&mut self, group: usize, path: &Path) {
+        let Some(g) = self.groups.get_mut(group) else {
+            return;
+        };
+        if !g.members.iter().any(|m| m == path) {
+            return;
+        }
+        g.original = path.to_path_buf();
+        for entry in &mut self.entries {
+            if g.members.contains(&entry.path) {
+                entry.duplicate_of = (entry.path != path).then(|| path.to_path_buf());
+            }
+        }
+    }
+
+    /// Entries selected after a scan: all but those in use or kept by
+    /// default, leaving every duplicate group a copy.
+    pub fn default_selection(&self) -> Vec<bool> {
+        let mut selected: Vec<bool> = self
+            .entries
+            .iter()
+            .map(|e| e.in_use_by.is_none() && !e.keep_by_default)
+            .collect();
+        self.enforce_survivors(&mut selected);
+        selected
+    }
+
+    /// Clear selections that would leave a duplicate group without any copy:
+    /// the original is never selected, and if it is gone already, the first
+    /// remaining member is kept instead.
+    pub fn enforce_survivors(&self, selected: &mut [bool]) {
+        let index_of = |path: &Path| self.entries.iter().position(|e| e.path == path);
+        for group in &self.groups {
+            if let Some(idx) = index_of(&group.original) {
+                if let Some(sel) = selected.get_mut(idx) {
+                    *sel = false;
+                }
+                continue;
+            }
+            let present: Vec<usize> = group.members.iter().filter_map(|m| index_of(m)).collect();
+            if !present.is_empty() && present.iter().all(|&i| selected.get(i) == Some(&true)) {
+                selected[present[0]] = false;
+            }
+        }
+    }
+
+    /// Remove a deleted entry (and its group membership), recomputing the total.
+    pub fn remove_entry(&mut self, idx: usize) {
+        let entry = self.entries.remove(idx);
+        // Groups stay even with one member left, so the last copy stays protected
+        for group in &mut self.groups {
+            group.members.retain(|m| *m != entry.path);
+        }
+        self.total_bytes = self
+            .entries
+            .iter()
+            .filter(|e| !self.is_original(&e.path))
+            .map(|e| e.size_bytes)
+            .sum();
+    }
 }
 
 /// The trait every cleaner module implements.
@@ -24,7 +151,16 @@ pub trait Cleaner: Send {
     /// Scan and return what would be cleaned. Never deletes anything.
     fn scan(&self) -> ScanResult;
 
-    /// Actually delete the entries when dry_run is false.
-    /// When dry_run is true, behaves like scan().
-    fn clean(&self, dry_run: bool) -> ScanResult;
+    /// Delete one entry this cleaner reported. Returns bytes freed. Cleaning
+    /// a category removes each selected entry through this.
+    fn remove(&self, path: &Path) -> std::io::Result<u64> {
+        crate::utils::safe_remove(path)
+    }
+
+    /// Whether entries belong to apps that may be running. Data of running
+    /// apps is never offered: such entries are marked with `in_use_by` during
+    /// scan and never deleted while in use.
+    fn skips_in_use(&self) -> bool {
+        false
+    }
 }
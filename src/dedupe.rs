@@ -0,0 +1,287 @@
+use std::fs::{self, File, FileTimes};
+use std::io::{self, Read};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+const CHUNK_SIZE: usize = 65536;
+
+/// How a duplicate was turned into a reference to its original.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LinkMethod {
+    /// Copy-on-write clone: shares blocks but keeps its own inode and metadata.
+    Cloned,
+    /// Hard link: the path now names the original's inode.
+    HardLinked,
+}
+
+impl LinkMethod {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LinkMethod::Cloned => "cloned",
+            LinkMethod::HardLinked => "hard-linked",
+        }
+    }
+}
+
+/// Replace `copy` with a clone of (or hard link to) `original`, after checking
+/// the two are byte-for-byte identical. The copy's owner, permissions and
+/// modification time are kept: clones get them reapplied, and a hard link is
+/// only made when they already match the original's (a hard link can't carry
+/// its own).
+/// Returns the method used and the bytes freed.
+pub fn link_to_original(original: &Path, copy: &Path) -> io::Result<(LinkMethod, u64)> {
+    let orig_meta = fs::symlink_metadata(original)?;
+    let copy_meta = fs::symlink_metadata(copy)?;
+
+    if !orig_meta.is_file() || !copy_meta.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only regular files can be linked",
+        ));
+    }
+    if orig_meta.dev() == copy_meta.dev() && orig_meta.ino() == copy_meta.ino() {
+        return Ok((LinkMethod::HardLinked, 0));
+    }
+    if orig_meta.dev() != copy_meta.dev() {
+        return Err(io::Error::new(
+            io::ErrorKind::CrossesDevices,
+            "original is on a different volume",
+        ));
+    }
+    if orig_meta.len() != copy_meta.len() || !same_contents(original, copy)? {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "contents changed since the scan",
+        ));
+    }
+
+    // Other hard links keep the copy's blocks alive, so nothing is freed then
+    let freed = if copy_meta.nlink() > 1 { 0 } else { copy_meta.len() };
+    let temp = temp_path(copy);
+    let _ = fs::remove_file(&temp);
+
+    let method = if clone_file(original, &temp).is_ok() {
+        if let Err(e) = restore_metadata(&temp, &copy_meta) {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+        LinkMethod::Cloned
+    } else {
+        let _ = fs::remove_file(&temp);
+        if !same_metadata(&orig_meta, &copy_meta) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "no clone support here, and a hard link would change its owner, permissions or dates",
+            ));
+        }
+        fs::hard_link(original, &temp)?;
+        LinkMethod::HardLinked
+    };
+
+    // Atomic swap: the copy's path never goes missing
+    if let Err(e) = fs::rename(&temp, copy) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    Ok((method, freed))
+}
+
+/// Byte-for-byte comparison (the scan only compared hashes).
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    let mut fa = File::open(a)?;
+    let mut fb = File::open(b)?;
+    let mut buf_a = vec![0u8; CHUNK_SIZE];
+    let mut buf_b = vec![0u8; CHUNK_SIZE];
+
+    loop {
+        let n = read_full(&mut fa, &mut buf_a)?;
+        let m = read_full(&mut fb, &mut buf_b)?;
+        crate::throttle::consume_io((n + m) as u64);
+        crate::progress::bytes_done((n + m) as u64);
+        if n != m || buf_a[..n] != buf_b[..m] {
+            return Ok(false);
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+fn read_full(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn same_metadata(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    a.mode() == b.mode()
+        && a.uid() == b.uid()
+        && a.gid() == b.gid()
+        && a.mtime() == b.mtime()
+        && a.mtime_nsec() == b.mtime_nsec()
+}
+
+/// Give a fresh clone the replaced copy's owner, permissions and timestamps.
+fn restore_metadata(path: &Path, meta: &fs::Metadata) -> io::Result<()> {
+    let current = fs::symlink_metadata(path)?;
+    if current.uid() != meta.uid() || current.gid() != meta.gid() {
+        std::os::unix::fs::chown(path, Some(meta.uid()), Some(meta.gid()))?;
+    }
+
+    let mut times = FileTimes::new();
+    if let Ok(accessed) = meta.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    if let Ok(modified) = meta.modified() {
+        times = times.set_modified(modified);
+    }
+    // Times before permissions, which may drop our write access
+    File::open(path)?.set_times(times)?;
+    fs::set_permissions(path, meta.permissions())
+}
+
+/// Hidden sibling of `copy`, so the final rename stays on one filesystem.
+fn temp_path(copy: &Path) -> PathBuf {
+    let name = copy.file_name().unwrap_or_default().to_string_lossy();
+    copy.with_file_name(format!(".{name}.tidymac-dedupe"))
+}
+
+#[cfg(target_os = "macos")]
+fn clone_file(src: &Path, dst: &Path) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let src = CString::new(src.as_os_str().as_bytes())?;
+    let dst = CString::new(dst.as_os_str().as_bytes())?;
+    // APFS only; other volumes fail with ENOTSUP
+    if unsafe { libc::clonefile(src.as_ptr(), dst.as_ptr(), 0) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn clone_file(src: &Path, dst: &Path) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let from = File::open(src)?;
+    let to = fs::OpenOptions::new().write(true).create_new(true).open(dst)?;
+    // Btrfs/XFS reflink; other filesystems fail with EOPNOTSUPP or EXDEV
+    if unsafe { libc::ioctl(to.as_raw_fd(), libc::FICLONE, from.as_raw_fd()) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn clone_file(_src: &Path, _dst: &Path) -> io::Result<()> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, Fixture};
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, SystemTime};
+
+    fn set_mtime(path: &Path, when: SystemTime) {
+        File::options()
+            .write(true)
+            .open(path)
+            .and_then(|f| f.set_times(FileTimes::new().set_modified(when)))
+            .unwrap();
+    }
+
+    #[test]
+    fn replaces_identical_copy_and_keeps_its_metadata() {
+        let fx = Fixture::new();
+        let data = test_support::content(3, CHUNK_SIZE * 3 + 17);
+        let original = fx.file_with("Pictures/photo.jpg", &data);
+        let copy = fx.file_with("Downloads/photo copy.jpg", &data);
+        let when = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        set_mtime(&original, when);
+        set_mtime(&copy, when);
+
+        let (method, freed) = link_to_original(&original, &copy).unwrap();
+
+        assert_eq!(freed, data.len() as u64);
+        assert_eq!(fs::read(&copy).unwrap(), data);
+        let (orig_meta, copy_meta) = (fs::metadata(&original).unwrap(), fs::metadata(&copy).unwrap());
+        assert_eq!(copy_meta.modified().unwrap(), when);
+        match method {
+            LinkMethod::HardLinked => assert_eq!(orig_meta.ino(), copy_meta.ino()),
+            LinkMethod::Cloned => assert_ne!(orig_meta.ino(), copy_meta.ino()),
+        }
+        assert!(!temp_path(&copy).exists());
+
+        // Running again is a no-op
+        assert_eq!(link_to_original(&original, &copy).unwrap().1, 0);
+    }
+
+    #[test]
+    fn refuses_files_that_differ() {
+        let fx = Fixture::new();
+        let data = test_support::content(4, 5000);
+        let mut edited = data.clone();
+        edited[4999] ^= 1;
+        let original = fx.file_with("a.bin", &data);
+        let copy = fx.file_with("b.bin", &edited);
+
+        let err = link_to_original(&original, &copy).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::read(&copy).unwrap(), edited);
+        assert!(!temp_path(&copy).exists());
+    }
+
+    #[test]
+    fn never_changes_a_copys_permissions() {
+        let fx = Fixture::new();
+        let data = test_support::content(5, 4096);
+        let original = fx.file_with("a.bin", &data);
+        let copy = fx.file_with("b.bin", &data);
+        fs::set_permissions(&original, fs::Permissions::from_mode(0o644)).unwrap();
+        fs::set_permissions(&copy, fs::Permissions::from_mode(0o600)).unwrap();
+
+        // Clones keep their own mode; without clone support the copy is left alone
+        match link_to_original(&original, &copy) {
+            Ok((method, _)) => assert_eq!(method, LinkMethod::Cloned),
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::Unsupported),
+        }
+        let mode = fs::metadata(&copy).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+        assert_eq!(fs::read(&copy).unwrap(), data);
+    }
+
+    #[test]
+    fn copies_whose_dates_differ_keep_their_own() {
+        let fx = Fixture::new();
+        let data = test_support::content(6, 4096);
+        let original = fx.file_with("a.bin", &data);
+        let copy = fx.file_with("b.bin", &data);
+        let (old, new) = (
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_500_000_000),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000),
+        );
+        set_mtime(&original, old);
+        set_mtime(&copy, new);
+
+        // Cloned where supported; a hard link would take the original's date
+        match link_to_original(&original, &copy) {
+            Ok((method, _)) => assert_eq!(method, LinkMethod::Cloned),
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::Unsupported),
+        }
+
+        assert_eq!(fs::read(&copy).unwrap(), data);
+        assert_eq!(fs::metadata(&copy).unwrap().modified().unwrap(), new);
+        assert_eq!(fs::metadata(&original).unwrap().modified().unwrap(), old);
+    }
+}
@@ -23,9 +23,15 @@ pub fn get_disk_info() -> Option<DiskInfo> {
         return None;
     }
     let stat = unsafe { stat.assume_init() };
-    let block_size = stat.f_frsize as u64;
-    let total = stat.f_blocks as u64 * block_size;
-    let available = stat.f_bavail as u64 * block_size;
+    // Field widths differ between platforms (u32 block counts on macOS).
+    #[allow(clippy::unnecessary_cast)]
+    let (block_size, blocks, free_blocks) = (
+        stat.f_frsize as u64,
+        stat.f_blocks as u64,
+        stat.f_bavail as u64,
+    );
+    let total = blocks * block_size;
+    let available = free_blocks * block_size;
     let used = total.saturating_sub(available);
     Some(DiskInfo {
         total,
@@ -0,0 +1,389 @@
+use crate::progress;
+use crate::throttle;
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Never descended into while measuring: other disks, the data volume seen a
+/// second time through its firmlinks, and pseudo filesystems.
+const SKIP_DIRS: &[&str] = &["/Volumes", "/System/Volumes", "/dev", "/net"];
+
+/// Rows kept per folder; smaller ones only count towards `Listing::omitted`.
+/// Keeps the cache small for folders holding many thousands of files.
+const MAX_ENTRIES: usize = 200;
+
+/// One row of a folder listing.
+#[derive(Clone)]
+pub struct Item {
+    pub name: OsString,
+    /// Space allocated on disk; for folders, including everything inside.
+    pub size: u64,
+    /// Files and folders inside (1 for a file).
+    pub items: u64,
+    pub is_dir: bool,
+}
+
+/// Measured contents of one folder.
+#[derive(Clone)]
+pub struct Listing {
+    /// Everything inside, including omitted rows.
+    pub size: u64,
+    pub items: u64,
+    /// The largest rows, at most `MAX_ENTRIES`.
+    pub entries: Vec<Item>,
+    /// Rows left out of `entries`, and their total size.
+    pub omitted: u64,
+    pub omitted_size: u64,
+    /// Part of the tree couldn't be read, so the totals are a lower bound.
+    pub incomplete: bool,
+    pub measured: SystemTime,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortMode {
+    Size,
+    Name,
+    Items,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 3] = [SortMode::Size, SortMode::Name, SortMode::Items];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Size => "Size",
+            SortMode::Name => "Name",
+            SortMode::Items => "Items",
+        }
+    }
+}
+
+/// Order a listing's rows; ties fall back to the name.
+pub fn sort(entries: &mut [Item], mode: SortMode) {
+    match mode {
+        SortMode::Size => entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name))),
+        SortMode::Name => entries.sort_by_key(|e| e.name.to_string_lossy().to_lowercase()),
+        SortMode::Items => entries.sort_by(|a, b| b.items.cmp(&a.items).then_with(|| a.name.cmp(&b.name))),
+    }
+}
+
+/// Hardlinked files take space once; symlinks are not followed.
+fn allocated(meta: &fs::Metadata, seen: &Mutex<HashSet<(u64, u64)>>) -> u64 {
+    if meta.nlink() > 1 && !meta.is_dir() {
+        let first = seen
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((meta.dev(), meta.ino()));
+        if !first {
+            return 0;
+        }
+    }
+    meta.blocks() * 512
+}
+
+struct Walk {
+    seen: Mutex<HashSet<(u64, u64)>>,
+    listings: Mutex<HashMap<PathBuf, Listing>>,
+}
+
+impl Walk {
+    /// Measure `dir` and everything below it, recording a listing for every
+    /// folder. Returns (size, items, complete).
+    fn measure_dir(&self, dir: &Path) -> (u64, u64, bool) {
+        let mut entries = Vec::new();
+        let mut subdirs = Vec::new();
+        let mut incomplete = false;
+
+        match fs::read_dir(dir) {
+            Ok(read) => {
+                for entry in read {
+                    let Ok(entry) = entry else {
+                        incomplete = true;
+                        continue;
+                    };
+                    progress::file_visited();
+                    let path = entry.path();
+                    let Ok(meta) = fs::symlink_metadata(&path) else {
+                        incomplete = true;
+                        continue;
+                    };
+                    if meta.is_dir() {
+                        if !SKIP_DIRS.iter().any(|s| path == Path::new(s)) {
+                            subdirs.push((entry.file_name(), path, meta.blocks() * 512));
+                        }
+                        continue;
+                    }
+                    entries.push(Item {
+                        name: entry.file_name(),
+                        size: allocated(&meta, &self.seen),
+                        items: 1,
+                        is_dir: false,
+                    });
+                }
+            }
+            Err(_) => incomplete = true,
+        }
+
+        let measured: Vec<(u64, u64, bool)> = subdirs
+            .par_iter()
+            .map(|(_, path, _)| self.measure_dir(path))
+            .collect();
+        for ((name, _, own), (size, items, complete)) in subdirs.into_iter().zip(measured) {
+            incomplete |= !complete;
+            entries.push(Item {
+                name,
+                size: own + size,
+                items: items + 1,
+                is_dir: true,
+            });
+        }
+
+        entries.sort_by_key(|e| Reverse(e.size));
+        let size = entries.iter().map(|e| e.size).sum();
+        let items = entries.iter().map(|e| e.items).sum();
+        let rest = entries.split_off(entries.len().min(MAX_ENTRIES));
+        self.listings.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            dir.to_path_buf(),
+            Listing {
+                size,
+                items,
+                entries,
+                omitted: rest.len() as u64,
+                omitted_size: rest.iter().map(|e| e.size).sum(),
+                incomplete,
+                measured: SystemTime::now(),
+            },
+        );
+        (size, items, !incomplete)
+    }
+}
+
+/// Measure `root` in parallel, returning a listing for it and every folder
+/// below it.
+pub fn measure(root: &Path) -> HashMap<PathBuf, Listing> {
+    let walk = Walk {
+        seen: Mutex::new(HashSet::new()),
+        listings: Mutex::new(HashMap::new()),
+    };
+    throttle::hashing_pool().install(|| walk.measure_dir(root));
+    walk.listings.into_inner().unwrap_or_else(|e| e.into_inner())
+}
+
+/// `total` with a part that was `old` and is now `new`.
+fn shift(total: u64, old: u64, new: u64) -> u64 {
+    total.saturating_sub(old).saturating_add(new)
+}
+
+/// Listings from earlier measurements, so moving around the tree is instant.
+#[derive(Default)]
+pub struct UsageCache {
+    listings: HashMap<PathBuf, Listing>,
+}
+
+impl UsageCache {
+    pub fn get(&self, dir: &Path) -> Option<&Listing> {
+        self.listings.get(dir)
+    }
+
+    /// Store a fresh measurement of `dir`, replacing everything cached below
+    /// it and carrying the change in size up into cached parent folders.
+    pub fn update(&mut self, dir: &Path, fresh: HashMap<PathBuf, Listing>) {
+        let Some(new) = fresh.get(dir) else {
+            return;
+        };
+        let old = self.listings.get(dir).map(|l| (l.size, l.items));
+        let (new_size, new_items) = (new.size, new.items);
+
+        self.listings.retain(|path, _| !path.starts_with(dir));
+        self.listings.extend(fresh);
+
+        let Some((old_size, old_items)) = old else {
+            return;
+        };
+        let mut child = dir;
+        for parent in dir.ancestors().skip(1) {
+            let Some(listing) = self.listings.get_mut(parent) else {
+                break;
+            };
+            let name = child.file_name();
+            if let Some(item) = listing.entries.iter_mut().find(|e| Some(e.name.as_os_str()) == name) {
+                item.size = shift(item.size, old_size, new_size);
+                item.items = shift(item.items, old_items, new_items);
+            } else {
+                listing.omitted_size = shift(listing.omitted_size, old_size, new_size);
+            }
+            listing.size = shift(listing.size, old_size, new_size);
+            listing.items = shift(listing.items, old_items, new_items);
+            child = parent;
+        }
+    }
+}
+
+/// A treemap rectangle.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tile {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+/// Worst aspect ratio of a row of `areas` laid along a side of length `side`.
+fn worst_ratio(areas: &[f32], side: f32) -> f32 {
+    let sum: f32 = areas.iter().sum();
+    let max = areas.iter().cloned().fold(0.0, f32::max);
+    let min = areas.iter().cloned().fold(f32::INFINITY, f32::min);
+    let (side2, sum2) = (side * side, sum * sum);
+    (side2 * max / sum2).max(sum2 / (side2 * min))
+}
+
+/// Squarified treemap of `sizes` (largest first) inside `area`. Tiles are
+/// returned in the order of `sizes`; zero sizes get empty tiles.
+pub fn treemap(sizes: &[u64], area: Tile) -> Vec<Tile> {
+    let mut tiles = vec![Tile::default(); sizes.len()];
+    let total: u64 = sizes.iter().sum();
+    if total == 0 || area.w <= 0.0 || area.h <= 0.0 {
+        return tiles;
+    }
+    let scale = area.w * area.h / total as f32;
+    let areas: Vec<f32> = sizes.iter().map(|&s| s as f32 * scale).collect();
+    let count = areas.iter().take_while(|&&a| a > 0.0).count();
+
+    let mut free = area;
+    let mut start = 0;
+    while start < count {
+        let side = free.w.min(free.h);
+        let mut end = start + 1;
+        let mut best = worst_ratio(&areas[start..end], side);
+        while end < count {
+            let next = worst_ratio(&areas[start..=end], side);
+            if next > best {
+                break;
+            }
+            best = next;
+            end += 1;
+        }
+
+        let row: f32 = areas[start..end].iter().sum();
+        if free.w >= free.h {
+            // A column on the left
+            let width = (row / free.h).min(free.w);
+            let mut y = free.y;
+            for i in start..end {
+                let h = areas[i] / width;
+                tiles[i] = Tile { x: free.x, y, w: width, h };
+                y += h;
+            }
+            free.x += width;
+            free.w -= width;
+        } else {
+            // A row along the top
+            let height = (row / free.w).min(free.h);
+            let mut x = free.x;
+            for i in start..end {
+                let w = areas[i] / height;
+                tiles[i] = Tile { x, y: free.y, w, h: height };
+                x += w;
+            }
+            free.y += height;
+            free.h -= height;
+        }
+        start = end;
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Fixture;
+
+    #[test]
+    fn sizes_add_up_and_every_folder_is_listed() {
+        let fx = Fixture::new();
+        fx.file_with("Projects/app/src/main.rs", &[1u8; 10_000]);
+        fx.file_with("Projects/app/big.bin", &[2u8; 200_000]);
+        fx.file_with("Projects/notes.txt", b"hi");
+        let root = fx.path("Projects");
+
+        let listings = measure(&root);
+
+        let top = &listings[&root];
+        assert_eq!(top.entries[0].name, "app");
+        assert!(top.entries[0].is_dir);
+        let app = &listings[&root.join("app")];
+        assert_eq!(app.entries[0].name, "big.bin");
+        assert!(app.entries[0].size >= 200_000);
+        assert_eq!(top.size, top.entries.iter().map(|e| e.size).sum::<u64>());
+        assert!(top.entries[0].size > app.size);
+        // app, src, main.rs, big.bin, notes.txt
+        assert_eq!(top.items, 5);
+        assert!(listings.contains_key(&root.join("app/src")));
+        assert!(!top.incomplete);
+    }
+
+    #[test]
+    fn only_the_largest_rows_are_kept() {
+        let fx = Fixture::new();
+        for i in 0..MAX_ENTRIES + 5 {
+            fx.file_with(&format!("Downloads/small-{i}.txt"), b"x");
+        }
+        fx.file_with("Downloads/big.bin", &[1u8; 100_000]);
+        let root = fx.path("Downloads");
+
+        let top = &measure(&root)[&root];
+
+        assert_eq!(top.entries.len(), MAX_ENTRIES);
+        assert_eq!(top.entries[0].name, "big.bin");
+        assert_eq!(top.omitted, 6);
+        assert_eq!(top.items, MAX_ENTRIES as u64 + 6);
+        let shown: u64 = top.entries.iter().map(|e| e.size).sum();
+        assert_eq!(top.size, shown + top.omitted_size);
+    }
+
+    #[test]
+    fn remeasuring_a_folder_updates_its_parents() {
+        let fx = Fixture::new();
+        fx.file_with("Projects/app/a.bin", &[1u8; 100_000]);
+        fx.file_with("Projects/other.bin", &[1u8; 50_000]);
+        let root = fx.path("Projects");
+        let app = root.join("app");
+
+        let mut cache = UsageCache::default();
+        cache.update(&root, measure(&root));
+        let before = cache.get(&root).unwrap().size;
+
+        fx.file_with("Projects/app/b.bin", &[1u8; 300_000]);
+        cache.update(&app, measure(&app));
+
+        let top = cache.get(&root).unwrap();
+        let grown = cache.get(&app).unwrap().size;
+        assert!(top.size >= before + 300_000);
+        assert_eq!(top.items, 4);
+        let app_item = top.entries.iter().find(|e| e.name == "app").unwrap();
+        assert!(app_item.size > grown);
+    }
+
+    #[test]
+    fn treemap_fills_the_area_in_proportion() {
+        let sizes = [60, 20, 10, 10, 0];
+        let area = Tile { x: 0.0, y: 0.0, w: 200.0, h: 100.0 };
+
+        let tiles = treemap(&sizes, area);
+
+        assert_eq!(tiles.len(), sizes.len());
+        let total: f32 = tiles.iter().map(|t| t.w * t.h).sum();
+        assert!((total - 20_000.0).abs() < 1.0);
+        assert!((tiles[0].w * tiles[0].h - 12_000.0).abs() < 1.0);
+        assert_eq!(tiles[4], Tile::default());
+        for t in &tiles[..4] {
+            assert!(t.x >= 0.0 && t.y >= 0.0 && t.x + t.w <= 200.01 && t.y + t.h <= 100.01);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Compressing must save at least this fraction, or the original is kept.
+#[cfg(target_os = "macos")]
+const MIN_COMPRESS_SAVING: f64 = 0.05;
+
+/// Something to do with a single, explicitly selected file.
+#[derive(Clone, PartialEq)]
+pub enum FileAction {
+    Trash,
+    /// Move into this folder, normally the root of another volume.
+    MoveTo(PathBuf),
+    /// Replace with a zip archive next to it.
+    Compress,
+    /// Copy to this volume and leave a symlink behind, see [`crate::offload`].
+    Offload(PathBuf),
+    /// Undo an offload, putting the file back in place of its link.
+    BringBack,
+}
+
+impl FileAction {
+    pub fn verb(&self) -> &'static str {
+        match self {
+            FileAction::Trash => "move to Trash",
+            FileAction::MoveTo(_) => "move",
+            FileAction::Compress => "compress",
+            FileAction::Offload(_) => "offload",
+            FileAction::BringBack => "bring back",
+        }
+    }
+
+    pub fn progress_label(&self) -> &'static str {
+        match self {
+            FileAction::Trash => "Moving to Trash",
+            FileAction::MoveTo(_) => "Moving",
+            FileAction::Compress => "Compressing",
+            FileAction::Offload(_) => "Offloading",
+            FileAction::BringBack => "Bringing back",
+        }
+    }
+
+    /// Carry out the action. Returns the bytes freed on this volume and a
+    /// short note for the report.
+    pub fn apply(&self, path: &Path) -> io::Result<(u64, String)> {
+        match self {
+            FileAction::Trash => {
+                move_to_trash(path)?;
+                // Space only comes back once the Trash is emptied
+                Ok((0, "moved to Trash".to_string()))
+            }
+            FileAction::MoveTo(dir) => {
+                let size = fs::symlink_metadata(path)?.len();
+                let dest = move_to(path, dir)?;
+                Ok((size, format!("moved to {}", dest.display())))
+            }
+            FileAction::Compress => {
+                let (archive, freed) = compress(path)?;
+                let name = archive.file_name().unwrap_or_default().to_string_lossy();
+                Ok((freed, format!("compressed to {name}")))
+            }
+            FileAction::Offload(volume) => {
+                let size = fs::symlink_metadata(path)?.len();
+                let copy = crate::offload::offload(path, volume)?;
+                Ok((size, format!("offloaded to {}", copy.display())))
+            }
+            FileAction::BringBack => {
+                let volume = crate::offload::bring_back(path)?;
+                let name = volume.file_name().unwrap_or_default().to_string_lossy();
+                Ok((0, format!("brought back from {name}")))
+            }
+        }
+    }
+}
+
+/// Mounted volumes other than the startup disk, as move destinations.
+pub fn other_volumes() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(crate::utils::system_path("/Volumes")) else {
+        return Vec::new();
+    };
+    let mut volumes: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        // The startup disk appears as a symlink to "/"
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .map(|e| e.path())
+        .collect();
+    volumes.sort();
+    volumes
+}
+
+/// Move to the Trash through Finder, so "Put Back" works.
+#[cfg(target_os = "macos")]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    let escaped = path
+        .to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    let script = format!("tell application \"Finder\" to delete POSIX file \"{escaped}\"");
+    let output = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(&script)
+        .output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn move_to_trash(_path: &Path) -> io::Result<()> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+/// Move `path` into `dir`, never overwriting. Across volumes the file is
+/// copied, checked by hash, and only then removed from its old place.
+pub fn move_to(path: &Path, dir: &Path) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name"))?;
+    let dest = dir.join(name);
+    if dest.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dest.display()),
+        ));
+    }
+
+    if fs::metadata(path)?.dev() == fs::metadata(dir)?.dev() {
+        fs::rename(path, &dest)?;
+    } else {
+        move_by_copy(path, &dest)?;
+    }
+    Ok(dest)
+}
+
+fn move_by_copy(path: &Path, dest: &Path) -> io::Result<()> {
+    let meta = fs::metadata(path)?;
+    if !meta.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only files can be moved",
+        ));
+    }
+
+    let copied = (|| {
+        let hash = crate::offload::copy_hashed(path, dest)?;
+        if crate::offload::hash_file(dest)? != hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the copy doesn't match the original",
+            ));
+        }
+        // The original must not have changed while it was copied
+        let now = fs::metadata(path)?;
+        if now.len() != meta.len() || now.mtime() != meta.mtime() || now.mtime_nsec() != meta.mtime_nsec()
+        {
+            return Err(io::Error::other("file changed while it was being copied"));
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = copied {
+        // Never leave a partial copy behind
+        let _ = fs::remove_file(dest);
+        return Err(e);
+    }
+    fs::remove_file(path)
+}
+
+/// Zip `path` in place with `ditto` (keeps macOS metadata), then remove the
+/// original. Returns the archive and the bytes saved.
+#[cfg(target_os = "macos")]
+fn compress(path: &Path) -> io::Result<(PathBuf, u64)> {
+    let size = fs::symlink_metadata(path)?.len();
+    let mut archive = path.as_os_str().to_os_string();
+    archive.push(".zip");
+    let archive = PathBuf::from(archive);
+    if archive.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", archive.display()),
+        ));
+    }
+
+    let status = std::process::Command::new("ditto")
+        .args(["-c", "-k", "--sequesterRsrc", "--keepParent"])
+        .arg(path)
+        .arg(&archive)
+        .status()?;
+    let zipped = fs::metadata(&archive).map(|m| m.len()).unwrap_or(0);
+    if !status.success() || zipped == 0 {
+        let _ = fs::remove_file(&archive);
+        return Err(io::Error::other("ditto could not create the archive"));
+    }
+    if zipped as f64 > size as f64 * (1.0 - MIN_COMPRESS_SAVING) {
+        let _ = fs::remove_file(&archive);
+        return Err(io::Error::other(
+            "already compressed (a zip would save under 5%), kept as is",
+        ));
+    }
+
+    fs::remove_file(path)?;
+    Ok((archive, size - zipped))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn compress(_path: &Path) -> io::Result<(PathBuf, u64)> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, Fixture, MB};
+    use std::fs::{File, FileTimes};
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn moves_without_overwriting() {
+        let fx = Fixture::new();
+        let path = fx.file_with("Movies/film.mov", b"frames");
+        let volume = fx.dir("/Volumes/Backup");
+
+        let dest = move_to(&path, &volume).unwrap();
+        assert_eq!(dest, volume.join("film.mov"));
+        assert!(!path.exists());
+
+        let again = fx.file_with("Movies/film.mov", b"other frames");
+        let err = move_to(&again, &volume).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&dest).unwrap(), b"frames");
+        assert!(again.exists());
+    }
+
+    #[test]
+    fn copies_across_volumes_with_metadata() {
+        let fx = Fixture::new();
+        let data = test_support::content(9, 2 * MB as usize + 5);
+        let path = fx.file_with("Movies/film.mov", &data);
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        let when = SystemTime::UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+        fx.age(&path, 0);
+        File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_times(FileTimes::new().set_modified(when)))
+            .unwrap();
+        let dest = fx.dir("/Volumes/Backup").join("film.mov");
+
+        move_by_copy(&path, &dest).unwrap();
+
+        assert!(!path.exists());
+        assert_eq!(fs::read(&dest).unwrap(), data);
+        let meta = fs::metadata(&dest).unwrap();
+        assert_eq!(meta.modified().unwrap(), when);
+        assert_eq!(meta.permissions().mode() & 0o777, 0o640);
+    }
+
+    #[test]
+    fn lists_mounted_volumes_but_not_the_startup_disk() {
+        let fx = Fixture::new();
+        fx.dir("/Volumes/Backup");
+        fx.dir("/Volumes/.timemachine");
+        fx.symlink("/Volumes/Macintosh HD", &fx.path("/"));
+
+        assert_eq!(other_volumes(), [fx.path("/Volumes/Backup")]);
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Entries unused for this many days are dropped when the cache is saved.
+const MAX_IDLE_DAYS: u64 = 90;
+
+const FILE_NAME: &str = "hash-cache-v1";
+
+/// Held while a cache is merged with the file and written back, so scans
+/// running side by side don't drop each other's hashes.
+static SAVE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Identity of a file's contents as far as the filesystem can tell: any
+/// write changes the size or mtime, so a stale hash is never served.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileKey {
+    dev: u64,
+    ino: u64,
+    size: u64,
+    mtime_ns: i64,
+}
+
+impl FileKey {
+    pub fn from_metadata(meta: &fs::Metadata) -> Self {
+        Self {
+            dev: meta.dev(),
+            ino: meta.ino(),
+            size: meta.len(),
+            mtime_ns: meta.mtime().saturating_mul(1_000_000_000).saturating_add(meta.mtime_nsec()),
+        }
+    }
+
+    pub fn of(path: &Path) -> Option<Self> {
+        fs::metadata(path).ok().map(|m| Self::from_metadata(&m))
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Which hash of the file is stored.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashKind {
+    /// The first few KB only.
+    Partial,
+    /// Head, middle and tail blocks of a very large file.
+    Sampled,
+    Full,
+}
+
+impl HashKind {
+    fn tag(self) -> &'static str {
+        match self {
+            HashKind::Partial => "p",
+            HashKind::Sampled => "s",
+            HashKind::Full => "f",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "p" => Some(HashKind::Partial),
+            "s" => Some(HashKind::Sampled),
+            "f" => Some(HashKind::Full),
+            _ => None,
+        }
+    }
+}
+
+/// Hashes from earlier scans, stored in the app data folder. Shared by the
+/// hashing threads of one scan, loaded before it starts and saved after.
+pub struct HashCache {
+    path: PathBuf,
+    /// (hash, day it was last used)
+    records: Mutex<HashMap<(FileKey, HashKind), (blake3::Hash, u64)>>,
+    dirty: AtomicBool,
+}
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86400)
+        .unwrap_or(0)
+}
+
+impl HashCache {
+    /// Load the on-disk cache. A missing or unreadable file just means an
+    /// empty cache.
+    pub fn open() -> Self {
+        let path = crate::utils::app_data_dir().join(FILE_NAME);
+        let records = fs::File::open(&path)
+            .map(|f| Self::parse(BufReader::new(f)))
+            .unwrap_or_default();
+        Self {
+            path,
+            records: Mutex::new(records),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    fn parse(reader: impl BufRead) -> HashMap<(FileKey, HashKind), (blake3::Hash, u64)> {
+        let mut records = HashMap::new();
+        for line in reader.lines().map_while(Result::ok) {
+            // Lines that don't parse (older formats, truncation) are skipped
+            let fields: Vec<&str> = line.split(' ').collect();
+            let [tag, dev, ino, size, mtime_ns, used, hex] = fields[..] else {
+                continue;
+            };
+            let parsed = (|| {
+                let kind = HashKind::from_tag(tag)?;
+                let key = FileKey {
+                    dev: dev.parse().ok()?,
+                    ino: ino.parse().ok()?,
+                    size: size.parse().ok()?,
+                    mtime_ns: mtime_ns.parse().ok()?,
+                };
+                let hash = blake3::Hash::from_hex(hex).ok()?;
+                Some(((key, kind), (hash, used.parse().ok()?)))
+            })();
+            if let Some((k, v)) = parsed {
+                records.insert(k, v);
+            }
+        }
+        records
+    }
+
+    pub fn get(&self, key: FileKey, kind: HashKind) -> Option<blake3::Hash> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        let (hash, used) = records.get_mut(&(key, kind))?;
+        let day = today();
+        if *used != day {
+            *used = day;
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        Some(*hash)
+    }
+
+    pub fn insert(&self, key: FileKey, kind: HashKind, hash: blake3::Hash) {
+        self.records
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((key, kind), (hash, today()));
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Write the cache back if anything changed, dropping long-unused entries.
+    /// Whatever other scans saved since this one opened the file is merged in.
+    pub fn save(&self) -> io::Result<()> {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let _saving = SAVE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        if let Ok(file) = fs::File::open(&self.path) {
+            for (k, (hash, used)) in Self::parse(BufReader::new(file)) {
+                let entry = records.entry(k).or_insert((hash, used));
+                entry.1 = entry.1.max(used);
+            }
+        }
+        let cutoff = today().saturating_sub(MAX_IDLE_DAYS);
+        records.retain(|_, (_, used)| *used >= cutoff);
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write a sibling and rename, so a crash never leaves half a cache
+        let temp = self.path.with_extension("tmp");
+        let mut out = BufWriter::new(fs::File::create(&temp)?);
+        for ((key, kind), (hash, used)) in records.iter() {
+            writeln!(
+                out,
+                "{} {} {} {} {} {} {}",
+                kind.tag(),
+                key.dev,
+                key.ino,
+                key.size,
+                key.mtime_ns,
+                used,
+                hash.to_hex()
+            )?;
+        }
+        out.flush()?;
+        drop(out);
+        fs::rename(&temp, &self.path)
+    }
+}
+
+/// Size of the on-disk cache, for the settings dialog.
+pub fn disk_size() -> u64 {
+    fs::metadata(crate::utils::app_data_dir().join(FILE_NAME))
+        .map(|m| m.len())
+        .unwrap_or(0)
+}
+
+/// Forget every cached hash.
+pub fn clear() -> io::Result<()> {
+    match fs::remove_file(crate::utils::app_data_dir().join(FILE_NAME)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Fixture;
+    use std::fs::{File, FileTimes};
+    use std::time::Duration;
+
+    #[test]
+    fn survives_a_round_trip_to_disk() {
+        let fx = Fixture::new();
+        let path = fx.file_with("Documents/a.bin", b"hello");
+        let key = FileKey::of(&path).unwrap();
+        let hash = blake3::hash(b"hello");
+
+        let cache = HashCache::open();
+        assert!(cache.get(key, HashKind::Full).is_none());
+        cache.insert(key, HashKind::Full, hash);
+        cache.save().unwrap();
+
+        let reloaded = HashCache::open();
+        assert_eq!(reloaded.get(key, HashKind::Full), Some(hash));
+        assert!(reloaded.get(key, HashKind::Partial).is_none());
+        assert!(disk_size() > 0);
+
+        clear().unwrap();
+        assert!(HashCache::open().get(key, HashKind::Full).is_none());
+    }
+
+    #[test]
+    fn side_by_side_scans_keep_each_others_hashes() {
+        let fx = Fixture::new();
+        let a = FileKey::of(&fx.file_with("a.bin", b"aaa")).unwrap();
+        let b = FileKey::of(&fx.file_with("b.bin", b"bbb")).unwrap();
+
+        let first = HashCache::open();
+        let second = HashCache::open();
+        first.insert(a, HashKind::Full, blake3::hash(b"aaa"));
+        second.insert(b, HashKind::Full, blake3::hash(b"bbb"));
+        first.save().unwrap();
+        second.save().unwrap();
+
+        let reloaded = HashCache::open();
+        assert_eq!(reloaded.get(a, HashKind::Full), Some(blake3::hash(b"aaa")));
+        assert_eq!(reloaded.get(b, HashKind::Full), Some(blake3::hash(b"bbb")));
+    }
+
+    #[test]
+    fn any_change_to_the_file_changes_its_key() {
+        let fx = Fixture::new();
+        let path = fx.file_with("a.bin", b"hello");
+        let before = FileKey::of(&path).unwrap();
+
+        // Same size, new contents and a new mtime
+        fs::write(&path, b"jello").unwrap();
+        let later = SystemTime::now() + Duration::from_secs(5);
+        File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_times(FileTimes::new().set_modified(later)))
+            .unwrap();
+        assert!(before != FileKey::of(&path).unwrap());
+
+        // A different file never shares a key
+        let other = fx.file_with("b.bin", b"jello");
+        assert!(FileKey::of(&other).unwrap() != FileKey::of(&path).unwrap());
+    }
+
+    #[test]
+    fn ignores_corrupt_lines() {
+        let records = HashCache::parse(
+            format!(
+                "garbage\nf 1 2 3 4 5 nothex\nf 1 2 3 4 {} {}\n",
+                today(),
+                blake3::hash(b"x").to_hex()
+            )
+            .as_bytes(),
+        );
+        assert_eq!(records.len(), 1);
+    }
+}
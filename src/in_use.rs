@@ -0,0 +1,303 @@
+use std::path::{Path, PathBuf};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::cleaner::ScanEntry;
+use crate::utils;
+
+/// Path components that belong to a well-known app but don't match its
+/// bundle ID or name (e.g. `~/Library/Caches/Google/Chrome`).
+const KNOWN_OWNERS: &[(&str, &str)] = &[
+    ("Chrome", "Google Chrome"),
+    ("com.google.Chrome", "Google Chrome"),
+    ("Firefox", "Firefox"),
+    ("org.mozilla.firefox", "Firefox"),
+    ("Safari", "Safari"),
+    ("com.apple.Safari", "Safari"),
+    ("Cookies.binarycookies", "Safari"),
+];
+
+/// A running `.app` bundle.
+struct RunningApp {
+    name: String,
+    bundle_id: Option<String>,
+    pids: Vec<u32>,
+}
+
+/// A file currently held open by some process.
+struct OpenFile {
+    path: PathBuf,
+    pid: u32,
+    command: String,
+}
+
+/// Point-in-time view of running apps and the files they hold open.
+/// Capture once per scan or clean run — building it spawns `lsof`.
+pub struct InUseSnapshot {
+    apps: Vec<RunningApp>,
+    open_files: Vec<OpenFile>,
+}
+
+impl InUseSnapshot {
+    pub fn capture() -> Self {
+        Self {
+            apps: running_apps(),
+            open_files: open_files(),
+        }
+    }
+
+    /// Name of the app (or process) using `path`, if any.
+    pub fn owner_of(&self, path: &Path) -> Option<String> {
+        self.holder(path).map(|(name, _)| name)
+    }
+
+    /// The app or process using `path`, and whether it is a running `.app`.
+    /// Open-file holders win over owner matching since they are exact.
+    fn holder(&self, path: &Path) -> Option<(String, bool)> {
+        if let Some(open) = self.open_files.iter().find(|f| f.path.starts_with(path)) {
+            let app = self.apps.iter().find(|a| a.pids.contains(&open.pid));
+            return Some(match app {
+                Some(app) => (app.name.clone(), true),
+                None => (open.command.clone(), false),
+            });
+        }
+
+        // Apps keep their data under a Library folder; elsewhere a folder that
+        // shares an app's name ("~/Documents/Notes") says nothing about it
+        let home = utils::home_dir();
+        let relative = path.strip_prefix(&home).unwrap_or(path);
+        let in_library = relative
+            .components()
+            .skip_while(|c| c.as_os_str() != "Library")
+            .skip(1);
+        for component in in_library {
+            let comp = component.as_os_str().to_string_lossy();
+
+            if let Some((_, owner)) = KNOWN_OWNERS.iter().find(|(c, _)| *c == comp) {
+                if self.apps.iter().any(|a| a.name == *owner) {
+                    return Some((owner.to_string(), true));
+                }
+            }
+
+            let app = self.apps.iter().find(|a| {
+                a.name.eq_ignore_ascii_case(&comp)
+                    || a
+                        .bundle_id
+                        .as_deref()
+                        .is_some_and(|id| id.eq_ignore_ascii_case(&comp))
+            });
+            if let Some(app) = app {
+                return Some((app.name.clone(), true));
+            }
+        }
+
+        None
+    }
+
+    /// Mark every entry whose owner app is running or which is held open.
+    pub fn mark_entries(&self, entries: &mut [ScanEntry]) {
+        for entry in entries {
+            let holder = self.holder(&entry.path);
+            entry.held_by_app = holder.as_ref().is_some_and(|(_, is_app)| *is_app);
+            entry.in_use_by = holder.map(|(name, _)| name);
+        }
+    }
+}
+
+/// Collect running `.app` bundles, grouping helper processes under their app.
+fn running_apps() -> Vec<RunningApp> {
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+    );
+
+    let mut apps: Vec<RunningApp> = Vec::new();
+    for (pid, process) in sys.processes() {
+        let Some(bundle) = process.exe().and_then(outermost_app_bundle) else {
+            continue;
+        };
+        let name = bundle
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        if let Some(app) = apps.iter_mut().find(|a| a.name == name) {
+            app.pids.push(pid.as_u32());
+            continue;
+        }
+        apps.push(RunningApp {
+            name,
+            bundle_id: bundle_identifier(&bundle),
+            pids: vec![pid.as_u32()],
+        });
+    }
+    apps
+}
+
+/// `/Applications/Foo.app/Contents/Frameworks/Bar.app/...` -> `/Applications/Foo.app`
+fn outermost_app_bundle(exe: &Path) -> Option<PathBuf> {
+    exe.ancestors()
+        .filter(|p| p.extension().is_some_and(|ext| ext == "app"))
+        .last()
+        .map(Path::to_path_buf)
+}
+
+fn bundle_identifier(bundle: &Path) -> Option<String> {
+    let info = plist::Value::from_file(bundle.join("Contents/Info.plist")).ok()?;
+    info.as_dictionary()?
+        .get("CFBundleIdentifier")?
+        .as_string()
+        .map(str::to_string)
+}
+
+/// Files held open by the current user's processes, via a single `lsof` run.
+fn open_files() -> Vec<OpenFile> {
+    let user = std::env::var("USER").unwrap_or_default();
+    let output = match std::process::Command::new("lsof")
+        .args(["-n", "-P", "-w", "-F", "pcn", "-u", &user])
+        .output()
+    {
+        Ok(o) => o,
+        Err(_) => return vec![],
+    };
+
+    // `-F pcn` emits one field per line: p<pid>, c<command>, then n<name> per file.
+    let text = String::from_utf8_lossy(&output.stdout);
+    let home = utils::home_dir();
+    let mut files = Vec::new();
+    let mut pid = 0u32;
+    let mut command = String::new();
+    for line in text.lines() {
+        let (tag, value) = line.split_at(line.len().min(1));
+        match tag {
+            "p" => pid = value.parse().unwrap_or(0),
+            "c" => command = value.to_string(),
+            "n" if value.starts_with('/') => {
+                let path = PathBuf::from(value);
+                // Only files under home can belong to a cleaned category
+                if path.starts_with(&home) {
+                    files.push(OpenFile {
+                        path,
+                        pid,
+                        command: command.clone(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    files
+}
+
+/// Ask an app to quit gracefully and wait up to `timeout` for it to exit.
+/// Returns true once no process of that app is left running.
+pub fn quit_app(name: &str, timeout: std::time::Duration) -> bool {
+    let script = format!("tell application \"{}\" to quit", name.replace('"', "\\\""));
+    let _ = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(&script)
+        .output();
+
+    let start = std::time::Instant::now();
+    while start.elapsed() < timeout {
+        if !running_apps().iter().any(|a| a.name == name) {
+            return true;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Fixture;
+
+    fn snapshot(apps: &[(&str, Option<&str>, u32)], open: &[(&Path, u32, &str)]) -> InUseSnapshot {
+        InUseSnapshot {
+            apps: apps
+                .iter()
+                .map(|(name, id, pid)| RunningApp {
+                    name: name.to_string(),
+                    bundle_id: id.map(str::to_string),
+                    pids: vec![*pid],
+                })
+                .collect(),
+            open_files: open
+                .iter()
+                .map(|(path, pid, command)| OpenFile {
+                    path: path.to_path_buf(),
+                    pid: *pid,
+                    command: command.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn marked(snapshot: &InUseSnapshot, paths: &[PathBuf]) -> Vec<Option<String>> {
+        let mut entries: Vec<ScanEntry> = paths.iter().map(|p| ScanEntry::new(p.clone(), 1)).collect();
+        snapshot.mark_entries(&mut entries);
+        entries.into_iter().map(|e| e.in_use_by).collect()
+    }
+
+    #[test]
+    fn running_apps_claim_their_library_data() {
+        let fx = Fixture::new();
+        let snap = snapshot(
+            &[("Notes", Some("com.apple.Notes"), 10), ("Google Chrome", None, 11)],
+            &[],
+        );
+        let owners = marked(
+            &snap,
+            &[
+                fx.path("Library/Caches/com.apple.Notes"),
+                fx.path("Library/Application Support/notes/db"),
+                fx.path("Library/Caches/Google/Chrome/Default"),
+                fx.path("Library/Caches/com.apple.Safari"),
+            ],
+        );
+        assert_eq!(
+            owners,
+            [
+                Some("Notes".to_string()),
+                Some("Notes".to_string()),
+                Some("Google Chrome".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn folders_named_like_an_app_outside_library_are_not_claimed() {
+        let fx = Fixture::new();
+        let snap = snapshot(&[("Notes", Some("com.apple.Notes"), 10), ("Safari", None, 12)], &[]);
+        let owners = marked(
+            &snap,
+            &[fx.path("Documents/Notes/todo.txt"), fx.path("Downloads/Safari/page.html")],
+        );
+        assert_eq!(owners, [None, None]);
+    }
+
+    #[test]
+    fn open_files_mark_what_contains_them() {
+        let fx = Fixture::new();
+        let held = fx.path("Downloads/movie.mkv.part");
+        let snap = snapshot(&[("Firefox", None, 20)], &[(&held, 20, "firefox"), (&held, 99, "cp")]);
+        let owners = marked(&snap, &[held.clone(), fx.path("Downloads"), fx.path("Documents")]);
+        assert_eq!(
+            owners,
+            [Some("Firefox".to_string()), Some("Firefox".to_string()), None]
+        );
+
+        // A plain process is named by its command, and isn't an app to quit
+        let snap = snapshot(&[], &[(&held, 99, "cp")]);
+        assert_eq!(marked(&snap, std::slice::from_ref(&held)), [Some("cp".to_string())]);
+        let mut entries = [ScanEntry::new(held.clone(), 1)];
+        snap.mark_entries(&mut entries);
+        assert!(!entries[0].held_by_app);
+        snapshot(&[("Firefox", None, 20)], &[(&held, 20, "firefox")]).mark_entries(&mut entries);
+        assert!(entries[0].held_by_app);
+    }
+}
@@ -1,15 +1,28 @@
 mod analyzer;
 mod app;
+mod archive;
 mod categories;
 mod cleaner;
+mod dedupe;
 mod disk_info;
+mod disk_usage;
+mod file_actions;
+mod hash_cache;
+mod in_use;
 mod monitor;
+mod offload;
+mod progress;
 mod shredder;
+#[cfg(test)]
+mod test_support;
+mod throttle;
 mod utils;
 
 use eframe::egui;
 
 fn main() -> eframe::Result<()> {
+    throttle::load_saved_limits();
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_title("TidyMac")
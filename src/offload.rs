@@ -0,0 +1,324 @@
+use std::collections::HashSet;
+use std::fs::{self, File, FileTimes};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const CHUNK_SIZE: usize = 1_048_576;
+
+const MANIFEST_NAME: &str = "offload-manifest-v1";
+
+/// Folder created at the root of the destination volume.
+const OFFLOAD_DIR: &str = "TidyMac Offload";
+
+/// Serializes read-modify-write of the manifest across worker threads.
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// A file moved to another volume, with a symlink left in its place.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Offloaded {
+    pub original: PathBuf,
+    pub copy: PathBuf,
+    pub size: u64,
+    hash: blake3::Hash,
+}
+
+impl Offloaded {
+    /// Whether the volume holding the copy is connected.
+    pub fn available(&self) -> bool {
+        self.copy.is_file()
+    }
+}
+
+fn manifest_path() -> PathBuf {
+    crate::utils::app_data_dir().join(MANIFEST_NAME)
+}
+
+/// Every offloaded file still tracked, oldest first.
+pub fn list() -> Vec<Offloaded> {
+    let Ok(file) = File::open(manifest_path()) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [original, copy, size, hash] = fields[..] else {
+                return None;
+            };
+            Some(Offloaded {
+                original: PathBuf::from(original),
+                copy: PathBuf::from(copy),
+                size: size.parse().ok()?,
+                hash: blake3::Hash::from_hex(hash).ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Original paths of offloaded files, so their links are never reported as
+/// broken while the volume is away.
+pub fn offloaded_paths() -> HashSet<PathBuf> {
+    list().into_iter().map(|o| o.original).collect()
+}
+
+fn save(records: &[Offloaded]) -> io::Result<()> {
+    let path = manifest_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_extension("tmp");
+    let mut out = io::BufWriter::new(File::create(&temp)?);
+    for r in records {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}",
+            r.original.display(),
+            r.copy.display(),
+            r.size,
+            r.hash.to_hex()
+        )?;
+    }
+    out.flush()?;
+    drop(out);
+    fs::rename(&temp, &path)
+}
+
+fn edit_manifest(edit: impl FnOnce(&mut Vec<Offloaded>)) -> io::Result<()> {
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut records = list();
+    edit(&mut records);
+    save(&records)
+}
+
+/// Copy `src` to a new file `dst` with the same permissions and dates,
+/// returning the hash of what was read.
+pub(crate) fn copy_hashed(src: &Path, dst: &Path) -> io::Result<blake3::Hash> {
+    let mut from = File::open(src)?;
+    let meta = from.metadata()?;
+    let mut to = fs::OpenOptions::new().write(true).create_new(true).open(dst)?;
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut hasher = blake3::Hasher::new();
+    loop {
+        let n = from.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        to.write_all(&buf[..n])?;
+        hasher.update(&buf[..n]);
+        crate::throttle::consume_io(n as u64);
+        crate::progress::bytes_done(n as u64);
+    }
+    to.sync_all()?;
+
+    let mut times = FileTimes::new();
+    if let Ok(accessed) = meta.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    if let Ok(modified) = meta.modified() {
+        times = times.set_modified(modified);
+    }
+    to.set_times(times)?;
+    fs::set_permissions(dst, meta.permissions())?;
+    Ok(hasher.finalize())
+}
+
+/// Hash everything in `path`.
+pub(crate) fn hash_file(path: &Path) -> io::Result<blake3::Hash> {
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut hasher = blake3::Hasher::new();
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            return Ok(hasher.finalize());
+        }
+        hasher.update(&buf[..n]);
+        crate::throttle::consume_io(n as u64);
+    }
+}
+
+/// Hidden sibling used to swap a path atomically.
+fn temp_sibling(path: &Path, tag: &str) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.tidymac-{tag}"))
+}
+
+/// Move `path` to `volume`, check the copy by hash, and leave a symlink to
+/// it behind. Returns where the copy went.
+pub fn offload(path: &Path, volume: &Path) -> io::Result<PathBuf> {
+    let meta = fs::symlink_metadata(path)?;
+    if !meta.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only regular files can be offloaded",
+        ));
+    }
+    let text = path.to_string_lossy();
+    if text.contains('\t') || text.contains('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "names with tabs or line breaks can't be tracked",
+        ));
+    }
+
+    // Mirror the original location so copies never collide
+    let home = crate::utils::home_dir();
+    let rel = path.strip_prefix(&home).unwrap_or(path);
+    let copy = volume
+        .join(OFFLOAD_DIR)
+        .join(rel.to_string_lossy().trim_start_matches('/'));
+    if copy.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", copy.display()),
+        ));
+    }
+    if let Some(parent) = copy.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let copied = (|| {
+        let hash = copy_hashed(path, &copy)?;
+        if hash_file(&copy)? != hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the copy doesn't match the original",
+            ));
+        }
+        // The original must not have changed while it was copied
+        let now = fs::symlink_metadata(path)?;
+        if now.len() != meta.len() || now.mtime() != meta.mtime() || now.mtime_nsec() != meta.mtime_nsec()
+        {
+            return Err(io::Error::other("file changed while it was being copied"));
+        }
+        Ok(hash)
+    })();
+    let hash = match copied {
+        Ok(hash) => hash,
+        Err(e) => {
+            let _ = fs::remove_file(&copy);
+            return Err(e);
+        }
+    };
+
+    // Record before swapping: after a crash both copies exist and are tracked
+    let record = Offloaded {
+        original: path.to_path_buf(),
+        copy: copy.clone(),
+        size: meta.len(),
+        hash,
+    };
+    if let Err(e) = edit_manifest(|records| records.push(record.clone())) {
+        let _ = fs::remove_file(&copy);
+        return Err(e);
+    }
+
+    let link = temp_sibling(path, "offload");
+    let _ = fs::remove_file(&link);
+    let swapped = std::os::unix::fs::symlink(&copy, &link).and_then(|_| fs::rename(&link, path));
+    if let Err(e) = swapped {
+        let _ = fs::remove_file(&link);
+        let _ = edit_manifest(|records| records.retain(|r| *r != record));
+        let _ = fs::remove_file(&copy);
+        return Err(e);
+    }
+    Ok(copy)
+}
+
+/// Bring an offloaded file back to its original place, replacing the link,
+/// and delete the copy on the other volume. Returns the volume it came from.
+pub fn bring_back(original: &Path) -> io::Result<PathBuf> {
+    let record = list()
+        .into_iter()
+        .find(|r| r.original == original)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not an offloaded file"))?;
+
+    if !record.available() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not connected", record.copy.display()),
+        ));
+    }
+    let link_ok = fs::symlink_metadata(original).is_ok_and(|m| m.file_type().is_symlink())
+        && fs::read_link(original).is_ok_and(|t| t == record.copy);
+    if !link_ok {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the original path no longer links to the offloaded copy, left as is",
+        ));
+    }
+
+    let temp = temp_sibling(original, "restore");
+    let _ = fs::remove_file(&temp);
+    let restored = copy_hashed(&record.copy, &temp).and_then(|hash| {
+        if hash != record.hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the offloaded copy changed since it was offloaded",
+            ));
+        }
+        fs::rename(&temp, original)
+    });
+    if let Err(e) = restored {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    edit_manifest(|records| records.retain(|r| r.original != original))?;
+    fs::remove_file(&record.copy)?;
+    Ok(record
+        .copy
+        .ancestors()
+        .find(|a| a.file_name().is_some_and(|n| n == OFFLOAD_DIR))
+        .and_then(Path::parent)
+        .unwrap_or(&record.copy)
+        .to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, Fixture};
+
+    #[test]
+    fn offloads_and_brings_back() {
+        let fx = Fixture::new();
+        let data = test_support::content(7, CHUNK_SIZE + 123);
+        let path = fx.file_with("Movies/VMs/win.vmdk", &data);
+        let volume = fx.dir("/Volumes/Backup");
+
+        let copy = offload(&path, &volume).unwrap();
+
+        assert_eq!(copy, volume.join("TidyMac Offload/Movies/VMs/win.vmdk"));
+        assert!(fs::symlink_metadata(&path).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read(&path).unwrap(), data);
+        assert_eq!(list().len(), 1);
+        assert!(offloaded_paths().contains(&path));
+
+        assert_eq!(bring_back(&path).unwrap(), volume);
+        assert!(fs::symlink_metadata(&path).unwrap().is_file());
+        assert_eq!(fs::read(&path).unwrap(), data);
+        assert!(!copy.exists());
+        assert!(list().is_empty());
+    }
+
+    #[test]
+    fn never_replaces_a_link_that_was_changed() {
+        let fx = Fixture::new();
+        let path = fx.file_with("Movies/film.mov", b"frames");
+        let volume = fx.dir("/Volumes/Backup");
+        let copy = offload(&path, &volume).unwrap();
+
+        // The user put a new file where the link was
+        fs::remove_file(&path).unwrap();
+        fs::write(&path, b"new film").unwrap();
+
+        assert!(bring_back(&path).is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"new film");
+        assert!(copy.exists());
+        assert_eq!(list().len(), 1);
+    }
+}
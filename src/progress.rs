@@ -0,0 +1,169 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Work counters for the operation currently running (scan, clean or shred).
+// Only one such operation runs at a time, so they are process-wide and
+// updated lock-free from cleaner, hashing and worker threads. Work done while
+// no operation is running is not counted.
+static FILES_VISITED: AtomicU64 = AtomicU64::new(0);
+static BYTES_DONE: AtomicU64 = AtomicU64::new(0);
+static BYTES_TOTAL: AtomicU64 = AtomicU64::new(0);
+static ITEMS_DONE: AtomicU64 = AtomicU64::new(0);
+static ITEMS_TOTAL: AtomicU64 = AtomicU64::new(0);
+
+/// Id of the running operation, or 0 when none is.
+static ACTIVE: AtomicU64 = AtomicU64::new(0);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// When the operation started and how its completion is measured.
+static STARTED: Mutex<Option<(Instant, WorkKind)>> = Mutex::new(None);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum WorkKind {
+    /// Items are categories; byte work (hashing) credits the one still running.
+    Scan,
+    /// Completion is measured in bytes deleted or overwritten.
+    Transfer,
+}
+
+/// The running operation: counting stops once it is dropped, so keep it
+/// alive on the thread doing the work.
+#[must_use]
+pub struct Operation(u64);
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        // A newer operation may have started meanwhile; leave it counting
+        let _ = ACTIVE.compare_exchange(self.0, 0, Ordering::Relaxed, Ordering::Relaxed);
+    }
+}
+
+fn counting() -> bool {
+    ACTIVE.load(Ordering::Relaxed) != 0
+}
+
+/// Reset all counters for a new operation.
+pub fn begin(kind: WorkKind, items_total: u64, bytes_total: u64) -> Operation {
+    FILES_VISITED.store(0, Ordering::Relaxed);
+    BYTES_DONE.store(0, Ordering::Relaxed);
+    BYTES_TOTAL.store(bytes_total, Ordering::Relaxed);
+    ITEMS_DONE.store(0, Ordering::Relaxed);
+    ITEMS_TOTAL.store(items_total, Ordering::Relaxed);
+    *STARTED.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), kind));
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    ACTIVE.store(id, Ordering::Relaxed);
+    Operation(id)
+}
+
+pub fn file_visited() {
+    if !counting() {
+        return;
+    }
+    FILES_VISITED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Announce byte work discovered mid-operation (e.g. files queued for hashing).
+pub fn expect_bytes(bytes: u64) {
+    if !counting() {
+        return;
+    }
+    BYTES_TOTAL.fetch_add(bytes, Ordering::Relaxed);
+}
+
+pub fn bytes_done(bytes: u64) {
+    if !counting() {
+        return;
+    }
+    BYTES_DONE.fetch_add(bytes, Ordering::Relaxed);
+}
+
+pub fn item_done() {
+    if !counting() {
+        return;
+    }
+    ITEMS_DONE.fetch_add(1, Ordering::Relaxed);
+}
+
+pub struct Snapshot {
+    pub kind: WorkKind,
+    pub files_visited: u64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub items_done: u64,
+    pub items_total: u64,
+    pub elapsed: Duration,
+}
+
+pub fn snapshot() -> Snapshot {
+    let (started, kind) = STARTED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .unwrap_or((Instant::now(), WorkKind::Scan));
+    Snapshot {
+        kind,
+        files_visited: FILES_VISITED.load(Ordering::Relaxed),
+        bytes_done: BYTES_DONE.load(Ordering::Relaxed),
+        bytes_total: BYTES_TOTAL.load(Ordering::Relaxed),
+        items_done: ITEMS_DONE.load(Ordering::Relaxed),
+        items_total: ITEMS_TOTAL.load(Ordering::Relaxed),
+        elapsed: started.elapsed(),
+    }
+}
+
+impl Snapshot {
+    /// Overall completion in 0.0..=1.0.
+    pub fn fraction(&self) -> f32 {
+        let byte_frac = if self.bytes_total > 0 {
+            (self.bytes_done as f64 / self.bytes_total as f64).min(1.0)
+        } else {
+            0.0
+        };
+
+        let frac = match self.kind {
+            WorkKind::Transfer if self.bytes_total > 0 => byte_frac,
+            _ if self.items_total > 0 => {
+                // Byte work belongs to an unfinished item (duplicate hashing);
+                // once it's all done that item's completion is counted instead.
+                let done = self.items_done.min(self.items_total) as f64;
+                let running = if done < self.items_total as f64 && byte_frac < 1.0 {
+                    byte_frac
+                } else {
+                    0.0
+                };
+                (done + running) / self.items_total as f64
+            }
+            _ => byte_frac,
+        };
+        frac as f32
+    }
+
+    /// Bytes hashed/deleted/overwritten per second so far.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes_done as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Estimated time left, once enough progress exists to extrapolate.
+    pub fn eta(&self) -> Option<Duration> {
+        let frac = self.fraction() as f64;
+        if !(0.02..1.0).contains(&frac) || self.elapsed < Duration::from_secs(2) {
+            return None;
+        }
+        Some(self.elapsed.mul_f64((1.0 - frac) / frac))
+    }
+}
+
+/// Format a duration as "m:ss" (or "h:mm:ss").
+pub fn format_eta(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
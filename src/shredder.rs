@@ -2,7 +2,7 @@ use std::io::{Seek, SeekFrom, Write};
 use std::path::Path;
 
 const CHUNK_SIZE: usize = 65536;
-const PASSES: u32 = 3;
+pub const PASSES: u32 = 3;
 
 /// Securely shred a file by overwriting its content before deletion.
 /// Pass pattern: random, zeros, random.
@@ -71,6 +71,8 @@ fn shred_single_file(
                 fill_random(&mut buf[..chunk]);
             }
             file.write_all(&buf[..chunk])?;
+            crate::throttle::consume_io(chunk as u64);
+            crate::progress::bytes_done(chunk as u64);
             remaining -= chunk as u64;
         }
 
@@ -99,3 +101,42 @@ fn fill_random(buf: &mut [u8]) {
         *byte = (state >> 33) as u8;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, Fixture};
+
+    #[test]
+    fn overwrites_contents_before_removing() {
+        let fx = Fixture::new();
+        let original = test_support::content(7, CHUNK_SIZE * 2 + 123);
+        let path = fx.file_with("Documents/secret.txt", &original);
+        // A second hard link keeps the inode readable after the shred unlinks it
+        let witness = fx.path("witness");
+        std::fs::hard_link(&path, &witness).unwrap();
+
+        let mut messages = Vec::new();
+        let freed = shred_file(&path, &mut |m| messages.push(m.to_string())).unwrap();
+
+        assert_eq!(freed, original.len() as u64);
+        assert!(!path.exists());
+        assert_eq!(messages.len(), PASSES as usize);
+        let after = std::fs::read(&witness).unwrap();
+        assert_eq!(after.len(), original.len());
+        assert_ne!(after, original);
+    }
+
+    #[test]
+    fn shreds_directories_recursively() {
+        let fx = Fixture::new();
+        fx.file_with("Private/a.txt", b"alpha");
+        fx.file_with("Private/nested/b.txt", b"beta");
+        fx.file("Private/nested/empty.txt", 0);
+
+        let freed = shred_file(&fx.path("Private"), &mut |_| {}).unwrap();
+
+        assert_eq!(freed, 9);
+        assert!(!fx.path("Private").exists());
+    }
+}
@@ -0,0 +1,233 @@
+//! Synthetic filesystem fixtures for exercising cleaners in tests.
+//!
+//! A [`Fixture`] is a temp directory that stands in for `/` on the current
+//! thread (see `utils::root_override`), with the fake home at `<root>/home`.
+//! Cleaners built after the fixture only ever see paths inside it.
+
+use std::fs::{self, File, FileTimes};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+use crate::cleaner::ScanResult;
+use crate::utils;
+
+pub const MB: u64 = 1_048_576;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub struct Fixture {
+    root: PathBuf,
+}
+
+impl Fixture {
+    /// Create an empty fixture and make it this thread's filesystem root.
+    pub fn new() -> Self {
+        let root = std::env::temp_dir().join(format!(
+            "tidymac-fixture-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("home")).expect("create fixture home");
+        // Canonicalize so paths match what WalkDir and read_dir report
+        let root = root.canonicalize().expect("canonicalize fixture root");
+        utils::set_root_override(Some(root.clone()));
+        Self { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn home(&self) -> PathBuf {
+        self.root.join("home")
+    }
+
+    /// Resolve a home-relative path (`"Library/Caches"`) or a system path (`"/Applications"`).
+    pub fn path(&self, rel: &str) -> PathBuf {
+        match rel.strip_prefix('/') {
+            Some(abs) => self.root.join(abs),
+            None => self.home().join(rel),
+        }
+    }
+
+    pub fn dir(&self, rel: &str) -> PathBuf {
+        let path = self.path(rel);
+        fs::create_dir_all(&path).expect("create fixture dir");
+        path
+    }
+
+    /// Create a file of `size` bytes. Contents are sparse zeros, so sizes are
+    /// cheap but every such file hashes the same — use [`Fixture::file_with`]
+    /// where content matters.
+    pub fn file(&self, rel: &str, size: u64) -> PathBuf {
+        let path = self.path(rel);
+        fs::create_dir_all(path.parent().unwrap()).expect("create fixture parent");
+        File::create(&path)
+            .and_then(|f| f.set_len(size))
+            .expect("create fixture file");
+        path
+    }
+
+    pub fn file_with(&self, rel: &str, contents: &[u8]) -> PathBuf {
+        let path = self.path(rel);
+        fs::create_dir_all(path.parent().unwrap()).expect("create fixture parent");
+        fs::write(&path, contents).expect("write fixture file");
+        path
+    }
+
+    pub fn symlink(&self, rel: &str, target: &Path) -> PathBuf {
+        let path = self.path(rel);
+        fs::create_dir_all(path.parent().unwrap()).expect("create fixture parent");
+        std::os::unix::fs::symlink(target, &path).expect("create fixture symlink");
+        path
+    }
+
+    /// Backdate both access and modification time by `days`.
+    pub fn age(&self, path: &Path, days: u64) {
+        let when = SystemTime::now() - Duration::from_secs(days * 86400);
+        let times = FileTimes::new().set_accessed(when).set_modified(when);
+        File::options()
+            .write(true)
+            .open(path)
+            .and_then(|f| f.set_times(times))
+            .expect("set fixture file times");
+    }
+
+    /// Entry paths of a result relative to the fixture root, sorted.
+    pub fn rel_paths(&self, result: &ScanResult) -> Vec<String> {
+        let mut paths: Vec<String> = result
+            .entries
+            .iter()
+            .map(|e| {
+                e.path
+                    .strip_prefix(&self.root)
+                    .unwrap_or_else(|_| panic!("{} escaped the fixture", e.path.display()))
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        utils::set_root_override(None);
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Deterministic, non-repeating content so distinct files never hash alike.
+pub fn content(seed: u8, len: usize) -> Vec<u8> {
+    let mut state = 0x9E37_79B9u32 ^ seed as u32;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+/// A home/Library/Applications tree with something for every category.
+pub fn populated() -> Fixture {
+    let fx = Fixture::new();
+
+    // System caches (plus folders owned by other cleaners)
+    fx.file("Library/Caches/com.example.editor/Cache.db", 4096);
+    fx.file("Library/Caches/com.example.editor/fsCachedData/blob", 8192);
+    fx.file("Library/Caches/com.example.player/index", 2048);
+    fx.file("Library/Caches/Homebrew/wget-1.21.tar.gz", 3 * MB);
+    fx.file("Library/Caches/Homebrew/downloads/abc--node.bottle.tar.gz", MB);
+
+    // Browser caches
+    fx.file("Library/Caches/Google/Chrome/Default/Cache/Cache_Data/data_0", 5000);
+    fx.file("Library/Caches/Google/Chrome/Profile 1/Code Cache/js/index", 3000);
+    fx.file("Library/Caches/com.apple.Safari/Cache.db", 7000);
+    fx.file("Library/Caches/Firefox/Profiles/abcd.default/cache2/entries/E1", 6000);
+
+    // Logs
+    fx.file("Library/Logs/com.example.editor/editor.log", 1500);
+    fx.file("Library/Logs/DiagnosticReports/crash.ips", 2500);
+    fx.file("/Library/Logs/install.log", 900);
+
+    // Xcode
+    fx.file("Library/Developer/Xcode/DerivedData/App-abcdef/Build/out.o", 2 * MB);
+    fx.file("Library/Developer/Xcode/iOS DeviceSupport/17.2 (21C62)/Symbols/dyld", MB);
+    fx.file("Library/Developer/Xcode/Archives/2024-01-02/App.xcarchive/Info.plist", 600);
+    fx.file(
+        "Library/Developer/CoreSimulator/Devices/5A1C0F2E-0000-4000-8000-000000000001/data/blob",
+        MB,
+    );
+
+    // Package managers
+    fx.file(".npm/_cacache/content-v2/sha512/aa", 4000);
+    fx.file("Library/Caches/Yarn/v6/pkg.tgz", 5000);
+    fx.file("Library/Caches/pip/http/ab/cd", 3000);
+    fx.file(".cargo/registry/cache/index/serde-1.0.0.crate", 6000);
+
+    // Trash
+    fx.file(".Trash/old report.pdf", 12_000);
+    fx.file(".Trash/Project/notes.txt", 800);
+
+    // .DS_Store files (one inside a skipped dir)
+    fx.file("Documents/.DS_Store", 6148);
+    fx.file("Pictures/Trip/.DS_Store", 6148);
+    fx.file("Projects/app/node_modules/.DS_Store", 6148);
+
+    // Language files in an app bundle
+    fx.file("/Applications/Editor.app/Contents/Resources/en.lproj/Main.strings", 100);
+    fx.file("/Applications/Editor.app/Contents/Resources/Base.lproj/Main.nib", 200);
+    fx.file("/Applications/Editor.app/Contents/Resources/ja.lproj/Main.strings", 300);
+    fx.file("/Applications/Editor.app/Contents/Resources/de.lproj/Main.strings", 400);
+
+    // Duplicates: an identical pair, a same-size stranger and a near-twin
+    // that only differs after the partial-hash window
+    let photo = content(1, 2 * MB as usize);
+    fx.file_with("Pictures/IMG_0001.jpg", &photo);
+    fx.file_with("Downloads/IMG_0001 copy.jpg", &photo);
+    fx.file_with("Documents/other.bin", &content(2, 2 * MB as usize));
+    let mut near = photo.clone();
+    *near.last_mut().unwrap() ^= 0xFF;
+    fx.file_with("Desktop/IMG_0001 edited.jpg", &near);
+
+    // Privacy data
+    fx.file("Library/Safari/History.db", 9000);
+    fx.file("Library/Cookies/Cookies.binarycookies", 1000);
+    fx.file("Library/Application Support/Google/Chrome/Default/History", 4000);
+    fx.file("Library/Application Support/Firefox/Profiles/abcd.default/places.sqlite", 5000);
+
+    // Old and large files
+    let old = fx.file("Downloads/old-footage.mov", 20 * MB);
+    fx.age(&old, 400);
+    fx.file("Downloads/recent-movie.mov", 30 * MB);
+    let small_old = fx.file("Documents/small-old.txt", MB);
+    fx.age(&small_old, 400);
+
+    // Broken symlinks
+    let gone = fx.path("Library/Gone/target");
+    fx.symlink("Library/Application Support/Stale/link", &gone);
+    fx.symlink("/usr/local/bin/oldtool", &fx.path("/opt/oldtool/bin/oldtool"));
+    let live = fx.file("bin/real-tool", 10);
+    fx.symlink("bin/tool", &live);
+
+    // Empty folders (.DS_Store-only counts as empty)
+    fx.dir("Library/Application Support/Abandoned");
+    fx.file("Library/Containers/com.example.gone/.DS_Store", 6148);
+    fx.dir("Library/Preferences/.hidden-empty");
+
+    // Screenshots
+    let shot = fx.file("Desktop/Screenshot 2023-01-01 at 10.00.00.png", 300_000);
+    fx.age(&shot, 90);
+    let rec = fx.file("Desktop/Screen Recording 2023-02-02 at 11.00.00.mov", 900_000);
+    fx.age(&rec, 45);
+    fx.file("Desktop/Screenshot 2099-01-01 at 09.00.00.png", 200_000);
+    let not_shot = fx.file("Desktop/Notes.png", 100_000);
+    fx.age(&not_shot, 90);
+
+    fx
+}
@@ -0,0 +1,221 @@
+use std::fs;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// User-configurable limits that keep scans from saturating the machine.
+#[derive(Clone, Copy, PartialEq)]
+pub struct IoLimits {
+    /// Maximum number of categories scanned at once (also sizes the hashing pool).
+    pub max_concurrent_scans: usize,
+    /// Run worker threads at background priority (throttled disk I/O on macOS).
+    pub background_io: bool,
+    /// Cap for bytes read while hashing and written while shredding.
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl IoLimits {
+    pub const DEFAULT: IoLimits = IoLimits {
+        max_concurrent_scans: 4,
+        background_io: false,
+        max_bytes_per_sec: None,
+    };
+}
+
+static LIMITS: RwLock<IoLimits> = RwLock::new(IoLimits::DEFAULT);
+
+/// (window start, bytes consumed since then) for the shared byte budget.
+static BUDGET: Mutex<Option<(Instant, u64)>> = Mutex::new(None);
+
+pub fn limits() -> IoLimits {
+    *LIMITS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Apply `limits` and keep them for later runs.
+pub fn set_limits(limits: IoLimits) {
+    *LIMITS.write().unwrap_or_else(|e| e.into_inner()) = limits;
+    *BUDGET.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    // The limits still apply to this run if they can't be written
+    let _ = save(&limits);
+}
+
+const LIMITS_FILE: &str = "io-limits-v1";
+
+fn limits_path() -> std::path::PathBuf {
+    crate::utils::app_data_dir().join(LIMITS_FILE)
+}
+
+fn save(limits: &IoLimits) -> std::io::Result<()> {
+    let path = limits_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = format!(
+        "max_concurrent_scans={}\nbackground_io={}\nmax_bytes_per_sec={}\n",
+        limits.max_concurrent_scans,
+        limits.background_io,
+        limits.max_bytes_per_sec.unwrap_or(0)
+    );
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, text)?;
+    fs::rename(&temp, &path)
+}
+
+/// Limits saved by an earlier run; unknown or missing values keep their default.
+fn load() -> IoLimits {
+    let mut limits = IoLimits::DEFAULT;
+    let text = fs::read_to_string(limits_path()).unwrap_or_default();
+    for (key, value) in text.lines().filter_map(|l| l.split_once('=')) {
+        match key {
+            "max_concurrent_scans" => {
+                if let Ok(n) = value.parse::<usize>() {
+                    limits.max_concurrent_scans = n.max(1);
+                }
+            }
+            "background_io" => limits.background_io = value == "true",
+            "max_bytes_per_sec" => limits.max_bytes_per_sec = value.parse().ok().filter(|n| *n > 0),
+            _ => {}
+        }
+    }
+    limits
+}
+
+/// Restore the limits saved by an earlier run. Call once at startup.
+pub fn load_saved_limits() {
+    *LIMITS.write().unwrap_or_else(|e| e.into_inner()) = load();
+}
+
+/// Account for `bytes` of hashing/shredding I/O, sleeping the calling thread
+/// as long as needed to stay under `max_bytes_per_sec`. The budget is shared
+/// by all threads, so parallel hashing is capped as a whole.
+pub fn consume_io(bytes: u64) {
+    let Some(limit) = limits().max_bytes_per_sec.filter(|l| *l > 0) else {
+        return;
+    };
+
+    let wait = {
+        let mut budget = BUDGET.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let (start, used) = budget.get_or_insert((now, 0));
+
+        // Forget history after an idle second so bursts don't stall later work
+        let allowed = Duration::from_secs_f64(*used as f64 / limit as f64);
+        if now.duration_since(*start) > allowed + Duration::from_secs(1) {
+            *start = now;
+            *used = 0;
+        }
+
+        *used += bytes;
+        let due = *start + Duration::from_secs_f64(*used as f64 / limit as f64);
+        due.saturating_duration_since(now)
+    };
+
+    if !wait.is_zero() {
+        std::thread::sleep(wait);
+    }
+}
+
+/// Lower the calling thread's priority when background I/O is enabled.
+/// On macOS this is `PRIO_DARWIN_BG`, which also throttles its disk I/O.
+pub fn apply_thread_priority() {
+    if !limits().background_io {
+        return;
+    }
+
+    #[cfg(target_os = "macos")]
+    unsafe {
+        libc::setpriority(libc::PRIO_DARWIN_THREAD, 0, libc::PRIO_DARWIN_BG);
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe {
+        // On Linux, setpriority with a thread id renices just that thread
+        let tid = libc::gettid() as libc::id_t;
+        libc::setpriority(libc::PRIO_PROCESS, tid, 19);
+    }
+}
+
+/// Run `job` for every item on at most `max_concurrent_scans` worker threads.
+/// Blocks until all items are processed.
+pub fn run_bounded<T, F>(items: Vec<T>, job: F)
+where
+    T: Send,
+    F: Fn(T) + Sync,
+{
+    let workers = limits().max_concurrent_scans.clamp(1, items.len().max(1));
+    let queue = Mutex::new(items.into_iter());
+
+    std::thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| {
+                apply_thread_priority();
+                loop {
+                    let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                    match next {
+                        Some(item) => job(item),
+                        None => break,
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Rayon pool for hashing, sized by the concurrency limit instead of taking
+/// every core. Falls back to rayon's global pool if it can't be built.
+pub struct HashingPool(Option<Arc<rayon::ThreadPool>>);
+
+impl HashingPool {
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.0 {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+}
+
+/// The pool built for the current limits, kept until they change.
+static POOL: Mutex<Option<(usize, bool, Arc<rayon::ThreadPool>)>> = Mutex::new(None);
+
+pub fn hashing_pool() -> HashingPool {
+    let IoLimits {
+        max_concurrent_scans,
+        background_io,
+        ..
+    } = limits();
+    let threads = max_concurrent_scans.max(1);
+    let mut cached = POOL.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((n, bg, pool)) = cached.as_ref() {
+        if *n == threads && *bg == background_io {
+            return HashingPool(Some(pool.clone()));
+        }
+    }
+    let built = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .start_handler(|_| apply_thread_priority())
+        .build()
+        .ok()
+        .map(Arc::new);
+    *cached = built.clone().map(|pool| (threads, background_io, pool));
+    HashingPool(built)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Fixture;
+
+    #[test]
+    fn limits_survive_a_restart() {
+        let _fx = Fixture::new();
+        let limits = IoLimits {
+            max_concurrent_scans: 2,
+            background_io: true,
+            max_bytes_per_sec: Some(50 * 1_048_576),
+        };
+        save(&limits).unwrap();
+        assert!(load() == limits);
+
+        fs::remove_file(limits_path()).unwrap();
+        assert!(load() == IoLimits::DEFAULT);
+    }
+}
@@ -1,11 +1,45 @@
+use std::cell::RefCell;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use walkdir::WalkDir;
 
+thread_local! {
+    /// Directory standing in for `/` on this thread (test fixtures only).
+    /// Home then resolves to `<root>/home`.
+    static ROOT_OVERRIDE: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
 /// Get home directory or panic with a clear message.
 pub fn home_dir() -> PathBuf {
+    if let Some(root) = root_override() {
+        return root.join("home");
+    }
     dirs::home_dir().expect("Could not determine home directory")
 }
 
+/// Resolve an absolute system path such as `/Applications`.
+pub fn system_path(path: &str) -> PathBuf {
+    match root_override() {
+        Some(root) => root.join(path.trim_start_matches('/')),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Where TidyMac keeps its own state (hash cache and the like).
+pub fn app_data_dir() -> PathBuf {
+    home_dir().join("Library/Application Support/TidyMac")
+}
+
+/// The fixture root active on this thread, if any.
+pub fn root_override() -> Option<PathBuf> {
+    ROOT_OVERRIDE.with(|r| r.borrow().clone())
+}
+
+#[cfg(test)]
+pub fn set_root_override(root: Option<PathBuf>) {
+    ROOT_OVERRIDE.with(|r| *r.borrow_mut() = root);
+}
+
 /// Compute total size of a directory recursively.
 pub fn dir_size(path: &Path) -> u64 {
     WalkDir::new(path)
@@ -13,6 +47,7 @@ pub fn dir_size(path: &Path) -> u64 {
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
+        .inspect(|_| crate::progress::file_visited())
         .filter_map(|e| e.metadata().ok())
         .map(|m| m.len())
         .sum()
@@ -38,7 +73,16 @@ pub fn safe_remove(path: &Path) -> Result<u64, std::io::Error> {
     Ok(size)
 }
 
+/// Numeric parts of a version, for ordering: "3.11.4" gives [3, 11, 4].
+pub fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
 /// Parse human-readable size string ("100MB") into bytes.
+#[allow(dead_code)] // public helper, unused by the GUI
 pub fn parse_size(s: &str) -> Result<u64, String> {
     let s = s.trim();
     let (num_str, multiplier) = if let Some(n) = s.strip_suffix("GB") {
@@ -87,6 +131,29 @@ pub fn format_size(bytes: u64) -> String {
     }
 }
 
+/// YYYY-MM-DD (UTC) for a point in time; times before 1970 show as 1970-01-01.
+pub fn format_date(time: SystemTime) -> String {
+    let days = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86400)
+        .unwrap_or(0) as i64;
+    civil_date(days)
+}
+
+/// YYYY-MM-DD for a count of days since 1970-01-01 (Howard Hinnant's algorithm).
+pub fn civil_date(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
 /// Shorten a path for display by replacing home dir with ~.
 pub fn display_path(path: &Path) -> String {
     let home = home_dir();
@@ -96,3 +163,15 @@ pub fn display_path(path: &Path) -> String {
         path.display().to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_dates_handle_leap_days() {
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(11_016), "2000-02-29");
+        assert_eq!(civil_date(20_000), "2024-10-04");
+    }
+}